@@ -162,8 +162,8 @@ impl TryFrom<Ipld> for Receipt<Ipld> {
                 Ipld::Null => None,
                 ipld => Some(ipld),
             })
-            .and_then(|ipld| from_ipld(ipld.to_owned()).ok())
-            .map(Issuer::new);
+            .map(|ipld| Issuer::try_from(ipld.to_owned()))
+            .transpose()?;
 
         let prf = map
             .get(PROOF_KEY)
@@ -4,3 +4,40 @@
 
 /// Metadata key for an operation or function name.
 pub const OP_KEY: &str = "op";
+
+/// Metadata key for the category of error a failed computation ran into.
+pub const ERROR_CATEGORY_KEY: &str = "error_category";
+
+/// Metadata key for the error message of a failed computation.
+pub const ERROR_MESSAGE_KEY: &str = "error";
+
+/// Metadata key for the amount of fuel consumed by a computation.
+pub const FUEL_USED_KEY: &str = "fuel_used";
+
+/// Metadata key for the peak memory, in bytes, used by a computation.
+pub const PEAK_MEMORY_KEY: &str = "peak_memory";
+
+/// Metadata key for the wall-clock duration, in milliseconds, of a
+/// computation.
+pub const DURATION_KEY: &str = "duration_ms";
+
+/// Metadata key marking a computation as run deterministically, i.e. with a
+/// fixed clock and randomness seeded by its instruction, so its output can be
+/// reproduced by any node.
+pub const DETERMINISTIC_KEY: &str = "deterministic";
+
+/// Metadata key for log records written by a computation.
+pub const LOGS_KEY: &str = "logs";
+
+/// Metadata key for the WASI capabilities granted to a computation, i.e. its
+/// preopened directories, environment variables and arguments.
+pub const WASI_KEY: &str = "wasi";
+
+/// Metadata key for the outgoing HTTP requests made by a computation, each
+/// with the content Cid of its response.
+pub const HTTP_KEY: &str = "http";
+
+/// Metadata key marking whether a computation's receipt may be reused in
+/// place of running it again. Computations that made network calls are not
+/// cacheable.
+pub const CACHEABLE_KEY: &str = "cacheable";
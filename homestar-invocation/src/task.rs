@@ -14,7 +14,7 @@ pub mod config;
 pub mod instruction;
 mod result;
 
-pub use config::Resources;
+pub use config::{Resources, WasiRequest};
 pub use instruction::Instruction;
 use instruction::RunInstruction;
 pub use result::Result;
@@ -88,6 +88,11 @@ where
         &self.meta
     }
 
+    /// Get [Task] proofs, i.e. links to UCANs authorizing it.
+    pub fn prf(&self) -> &UcanPrf {
+        &self.prf
+    }
+
     /// Turn [Task] into owned [RunInstruction].
     pub fn into_instruction(self) -> RunInstruction<'a, T> {
         self.run
@@ -2,7 +2,7 @@
 //!
 //! [Invocation]: crate::Invocation
 
-use crate::{consts, Error, Unit};
+use crate::{consts, receipt::metadata::DETERMINISTIC_KEY, Error, Unit};
 use libipld::{serde::from_ipld, Ipld};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -11,6 +11,10 @@ use std::{collections::BTreeMap, time::Duration};
 const FUEL_KEY: &str = "fuel";
 const MEMORY_KEY: &str = "memory";
 const TIMEOUT_KEY: &str = "time";
+const WASI_KEY: &str = "wasi";
+const DIRS_KEY: &str = "dirs";
+const ENV_KEY: &str = "env";
+const ARGS_KEY: &str = "args";
 
 /// Resource configuration for defining fuel quota, timeout, etc.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
@@ -24,6 +28,98 @@ pub struct Resources {
     memory: Option<u64>,
     #[schemars(with = "Option<u64>", description = "Timeout in milliseconds")]
     time: Option<Duration>,
+    #[serde(default)]
+    #[schemars(
+        description = "Run deterministically, with a fixed clock and randomness seeded by the instruction"
+    )]
+    deterministic: bool,
+    #[serde(default)]
+    #[schemars(description = "WASI capabilities requested, granted only if allowed by the node")]
+    wasi: WasiRequest,
+}
+
+/// WASI capabilities requested by a task.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[schemars(
+    rename = "wasi",
+    description = "WASI capabilities: read-only preopened directories, environment variables and arguments"
+)]
+pub struct WasiRequest {
+    #[serde(default)]
+    #[schemars(description = "Guest paths of directories to preopen, read-only")]
+    dirs: Vec<String>,
+    #[serde(default)]
+    #[schemars(description = "Environment variables")]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    #[schemars(description = "Arguments")]
+    args: Vec<String>,
+}
+
+impl WasiRequest {
+    /// Create a new [WasiRequest].
+    pub fn new(dirs: Vec<String>, env: BTreeMap<String, String>, args: Vec<String>) -> Self {
+        Self { dirs, env, args }
+    }
+
+    /// Guest paths of directories to preopen, read-only.
+    pub fn dirs(&self) -> &[String] {
+        &self.dirs
+    }
+
+    /// Environment variables.
+    pub fn env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    /// Arguments.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Whether no capabilities are requested.
+    pub fn is_empty(&self) -> bool {
+        self.dirs.is_empty() && self.env.is_empty() && self.args.is_empty()
+    }
+}
+
+impl From<WasiRequest> for Ipld {
+    fn from(wasi: WasiRequest) -> Ipld {
+        Ipld::Map(BTreeMap::from([
+            (
+                DIRS_KEY.into(),
+                Ipld::List(wasi.dirs.into_iter().map(Ipld::String).collect()),
+            ),
+            (
+                ENV_KEY.into(),
+                Ipld::Map(
+                    wasi.env
+                        .into_iter()
+                        .map(|(key, value)| (key, Ipld::String(value)))
+                        .collect(),
+                ),
+            ),
+            (
+                ARGS_KEY.into(),
+                Ipld::List(wasi.args.into_iter().map(Ipld::String).collect()),
+            ),
+        ]))
+    }
+}
+
+impl TryFrom<Ipld> for WasiRequest {
+    type Error = Error<Unit>;
+
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+        let field = |key| map.get(key).cloned().unwrap_or(Ipld::Null);
+
+        Ok(WasiRequest {
+            dirs: from_ipld::<Option<_>>(field(DIRS_KEY))?.unwrap_or_default(),
+            env: from_ipld::<Option<_>>(field(ENV_KEY))?.unwrap_or_default(),
+            args: from_ipld::<Option<_>>(field(ARGS_KEY))?.unwrap_or_default(),
+        })
+    }
 }
 
 impl Default for Resources {
@@ -32,6 +128,8 @@ impl Default for Resources {
             fuel: Some(u64::MAX),
             memory: Some(consts::WASM_MAX_MEMORY),
             time: Some(Duration::from_millis(100_000)),
+            deterministic: false,
+            wasi: WasiRequest::default(),
         }
     }
 }
@@ -43,6 +141,8 @@ impl Resources {
             fuel: Some(fuel),
             memory: Some(memory),
             time: Some(time),
+            deterministic: false,
+            wasi: WasiRequest::default(),
         }
     }
 
@@ -75,11 +175,31 @@ impl Resources {
     pub fn set_memory(&mut self, memory: u64) {
         self.memory = Some(memory)
     }
+
+    /// Whether to run deterministically.
+    pub fn deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Set whether to run deterministically.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic
+    }
+
+    /// Get requested WASI capabilities.
+    pub fn wasi(&self) -> &WasiRequest {
+        &self.wasi
+    }
+
+    /// Set requested WASI capabilities.
+    pub fn set_wasi(&mut self, wasi: WasiRequest) {
+        self.wasi = wasi
+    }
 }
 
 impl From<Resources> for Ipld {
     fn from(resources: Resources) -> Ipld {
-        Ipld::Map(BTreeMap::from([
+        let mut map = BTreeMap::from([
             (
                 FUEL_KEY.into(),
                 resources.fuel().map(Ipld::from).unwrap_or(Ipld::Null),
@@ -95,7 +215,16 @@ impl From<Resources> for Ipld {
                     .map(|t| Ipld::from(t.as_millis() as i128))
                     .unwrap_or(Ipld::Null),
             ),
-        ]))
+        ]);
+
+        // Only included when set, leaving the Cids of existing tasks as is.
+        if resources.deterministic() {
+            map.insert(DETERMINISTIC_KEY.into(), Ipld::Bool(true));
+        }
+        if !resources.wasi().is_empty() {
+            map.insert(WASI_KEY.into(), resources.wasi.into());
+        }
+        Ipld::Map(map)
     }
 }
 
@@ -113,25 +242,42 @@ impl TryFrom<Ipld> for Resources {
     fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
         let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
 
-        let fuel = map.get(FUEL_KEY).and_then(|ipld| match ipld {
-            Ipld::Null => None,
-            ipld => from_ipld(ipld.to_owned()).ok(),
-        });
+        let fuel = map.get(FUEL_KEY).map_or(Ok(None), u64_from_ipld)?;
+        let memory = map.get(MEMORY_KEY).map_or(Ok(None), u64_from_ipld)?;
+        let time = map
+            .get(TIMEOUT_KEY)
+            .map_or(Ok(None), u64_from_ipld)?
+            .map(Duration::from_millis);
+
+        let deterministic = matches!(map.get(DETERMINISTIC_KEY), Some(Ipld::Bool(true)));
 
-        let memory = map.get(MEMORY_KEY).and_then(|ipld| match ipld {
-            Ipld::Null => None,
-            ipld => from_ipld(ipld.to_owned()).ok(),
-        });
+        let wasi = match map.get(WASI_KEY) {
+            Some(ipld @ Ipld::Map(_)) => WasiRequest::try_from(ipld.to_owned())?,
+            _ => WasiRequest::default(),
+        };
 
-        let time = map.get(TIMEOUT_KEY).and_then(|ipld| match ipld {
-            Ipld::Null => None,
-            ipld => {
-                let time = from_ipld(ipld.to_owned()).unwrap_or(100_000);
-                Some(Duration::from_millis(time))
-            }
-        });
+        Ok(Resources {
+            fuel,
+            memory,
+            time,
+            deterministic,
+            wasi,
+        })
+    }
+}
 
-        Ok(Resources { fuel, memory, time })
+/// Read an optional, non-negative integer resource from [Ipld].
+///
+/// Integral floats are accepted, saturating at [u64::MAX], as JSON encoders
+/// round [u64::MAX] up to `18446744073709552000`.
+fn u64_from_ipld(ipld: &Ipld) -> Result<Option<u64>, Error<Unit>> {
+    match ipld {
+        Ipld::Null => Ok(None),
+        Ipld::Integer(i) => u64::try_from(*i)
+            .map(Some)
+            .map_err(|_| Error::unexpected_ipld(ipld.to_owned())),
+        Ipld::Float(f) if f.is_finite() && *f >= 0.0 && f.fract() == 0.0 => Ok(Some(*f as u64)),
+        ipld => Err(Error::unexpected_ipld(ipld.to_owned())),
     }
 }
 
@@ -158,6 +304,55 @@ mod test {
         assert_eq!(config, ipld.try_into().unwrap())
     }
 
+    #[test]
+    fn ipld_roundtrip_deterministic() {
+        let mut config = Resources::default();
+        config.set_deterministic(true);
+        let ipld = Ipld::from(config.clone());
+
+        let Ipld::Map(ref map) = ipld else {
+            panic!("resources are not a map")
+        };
+        assert_eq!(map.get(DETERMINISTIC_KEY), Some(&Ipld::Bool(true)));
+        assert_eq!(config, ipld.try_into().unwrap())
+    }
+
+    #[test]
+    fn ipld_roundtrip_wasi() {
+        let mut config = Resources::default();
+        config.set_wasi(WasiRequest::new(
+            vec!["/data".to_string()],
+            BTreeMap::from([("MODE".to_string(), "fast".to_string())]),
+            vec!["--verbose".to_string()],
+        ));
+        let ipld = Ipld::from(config.clone());
+
+        let Ipld::Map(ref map) = ipld else {
+            panic!("resources are not a map")
+        };
+        assert!(map.contains_key(WASI_KEY));
+        assert_eq!(config, ipld.try_into().unwrap())
+    }
+
+    #[test]
+    fn invalid_resources() {
+        let invalid = |key: &str, value| {
+            Resources::try_from(Ipld::Map(BTreeMap::from([(key.into(), value)]))).is_err()
+        };
+
+        assert!(invalid(FUEL_KEY, Ipld::Integer(-1)));
+        assert!(invalid(MEMORY_KEY, Ipld::String("4GiB".into())));
+        assert!(invalid(TIMEOUT_KEY, Ipld::Float(1.5)));
+
+        // JSON encoders round `u64::MAX` up, past what fits a `u64`.
+        let resources = Resources::try_from(Ipld::Map(BTreeMap::from([(
+            FUEL_KEY.into(),
+            Ipld::Float(u64::MAX as f64),
+        )])))
+        .unwrap();
+        assert_eq!(resources.fuel(), Some(u64::MAX));
+    }
+
     #[test]
     fn ser_de() {
         let config = Resources::default();
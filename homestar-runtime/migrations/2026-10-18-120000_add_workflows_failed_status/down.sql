@@ -0,0 +1,10 @@
+ALTER TABLE workflows RENAME COLUMN status TO status_old;
+ALTER TABLE workflows RENAME COLUMN retries TO retries_old;
+ALTER TABLE workflows ADD COLUMN status TEXT CHECK(
+    status IN ('pending', 'completed', 'running', 'stuck')) NOT NULL DEFAULT
+            'pending';
+ALTER TABLE workflows ADD COLUMN retries INTEGER NOT NULL DEFAULT 0;
+UPDATE workflows SET status = (CASE status_old WHEN 'failed' THEN 'completed'
+    ELSE status_old END), retries = retries_old;
+ALTER TABLE workflows DROP COLUMN status_old;
+ALTER TABLE workflows DROP COLUMN retries_old;
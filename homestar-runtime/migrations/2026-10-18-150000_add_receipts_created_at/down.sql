@@ -0,0 +1,2 @@
+DROP INDEX receipts_created_at_index;
+ALTER TABLE receipts DROP COLUMN created_at;
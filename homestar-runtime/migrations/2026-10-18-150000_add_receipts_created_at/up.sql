@@ -0,0 +1,18 @@
+CREATE TABLE receipts_new (
+  cid          TEXT NOT NULL PRIMARY KEY,
+  ran          TEXT NOT NULL,
+  instruction  TEXT NOT NULL,
+  out          BLOB NOT NULL,
+  meta         BLOB NOT NULL,
+  issuer       TEXT,
+  prf          BLOB NOT NULL,
+  version      TEXT NOT NULL,
+  created_at   TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+INSERT INTO receipts_new (cid, ran, instruction, out, meta, issuer, prf, version)
+  SELECT cid, ran, instruction, out, meta, issuer, prf, version FROM receipts;
+DROP TABLE receipts;
+ALTER TABLE receipts_new RENAME TO receipts;
+
+CREATE INDEX instruction_index ON receipts (instruction);
+CREATE INDEX receipts_created_at_index ON receipts (created_at);
@@ -0,0 +1,10 @@
+CREATE TABLE blocks (
+  workflow_cid TEXT NOT NULL,
+  cid          TEXT NOT NULL,
+  data         BLOB NOT NULL,
+  created_at   TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  PRIMARY KEY(workflow_cid, cid)
+);
+
+CREATE INDEX blocks_cid_index ON blocks (cid);
+CREATE INDEX blocks_created_at_index ON blocks (created_at);
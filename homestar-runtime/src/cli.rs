@@ -3,10 +3,12 @@
 use crate::{
     network::rpc::Client,
     runner::{file, response},
-    KeyType,
+    workflow, KeyType,
 };
-use anyhow::anyhow;
-use clap::{ArgGroup, Args, Parser, Subcommand};
+use anyhow::{anyhow, Context};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use homestar_wasm::wasmtime::Runtime as WasmRuntime;
+use libipld::Cid;
 use serde::{Deserialize, Serialize};
 use std::{
     net::{IpAddr, Ipv6Addr, SocketAddr},
@@ -15,12 +17,16 @@ use std::{
 };
 use tarpc::context;
 
+mod db;
+pub use db::handle_db_command;
 mod error;
 pub use error::Error;
 mod init;
 pub use init::{handle_init_command, KeyArg, OutputMode};
 pub(crate) mod show;
 pub use show::ConsoleTable;
+mod validate;
+pub use validate::handle_validate_command;
 
 const DEFAULT_DB_PATH: &str = "homestar.db";
 const TMP_DIR: &str = "/tmp";
@@ -145,6 +151,155 @@ impl Default for RpcArgs {
     }
 }
 
+/// Workflow subcommands.
+#[derive(Debug, Subcommand)]
+pub enum WorkflowCommand {
+    /// List workflows known to the Homestar runtime.
+    List {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Workflow filters.
+        #[clap(flatten)]
+        filter: workflow::Filter,
+    },
+    /// Show the progress, resources, timestamps, and retries of a workflow.
+    Show {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Cid or local name of the workflow.
+        #[arg(
+            value_name = "CID|NAME",
+            index = 1,
+            required = true,
+            help = "Cid or local name of the workflow"
+        )]
+        workflow: String,
+    },
+    /// List the receipts of a workflow.
+    Receipts {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Cid of the workflow.
+        #[arg(
+            value_name = "CID",
+            index = 1,
+            required = true,
+            help = "Cid of the workflow"
+        )]
+        cid: Cid,
+    },
+}
+
+/// Module subcommands.
+#[derive(Debug, Subcommand)]
+pub enum ModuleCommand {
+    /// List the exported functions of a Wasm module, with their WIT types
+    /// and a JSON Schema of their args.
+    Inspect {
+        /// RPC host / port arguments, used when given a Cid.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Cid of the module, fetched by the node, or path to a local
+        /// module file.
+        #[arg(
+            value_name = "CID|FILE",
+            index = 1,
+            required = true,
+            help = "Cid of the module, fetched by a running node, or path to a local Wasm file"
+        )]
+        module: String,
+    },
+}
+
+/// Database arguments for commands working on the database directly,
+/// without a running node.
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct DbArgs {
+    /// Database URL, defaults to homestar.db.
+    #[arg(
+        long = "db",
+        env = "DATABASE_PATH",
+        value_hint = clap::ValueHint::AnyPath,
+        value_name = "DATABASE_PATH",
+        default_value = DEFAULT_DB_PATH,
+        help = "Database path (SQLite) [optional]"
+    )]
+    pub database_url: Option<String>,
+    /// Runtime configuration file (.toml).
+    #[arg(
+        short = 'c',
+        long = "config",
+        value_hint = clap::ValueHint::FilePath,
+        value_name = "CONFIG",
+        help = "Runtime configuration file (.toml) [optional]"
+    )]
+    pub runtime_config: Option<PathBuf>,
+}
+
+/// Database subcommands.
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Export a workflow and its receipts as a CARv1 archive.
+    Export {
+        /// Database arguments.
+        #[clap(flatten)]
+        args: DbArgs,
+        /// Cid of the workflow to export.
+        #[arg(
+            short = 'w',
+            long = "workflow",
+            value_name = "CID",
+            required = true,
+            help = "Cid of the workflow to export"
+        )]
+        workflow: Cid,
+        /// Path to write the archive to.
+        #[arg(
+            value_hint = clap::ValueHint::FilePath,
+            value_name = "OUTPUT",
+            index = 1,
+            required = true,
+            help = "Path to write the CARv1 archive (.car) to"
+        )]
+        output: PathBuf,
+    },
+    /// Import a workflow and its receipts from a CARv1 archive, so the
+    /// workflow is replayed rather than recomputed when run.
+    ///
+    /// Receipts are checked against the node's receipt policy.
+    Import {
+        /// Database arguments.
+        #[clap(flatten)]
+        args: DbArgs,
+        /// Path to read the archive from.
+        #[arg(
+            value_hint = clap::ValueHint::FilePath,
+            value_name = "INPUT",
+            index = 1,
+            required = true,
+            help = "Path to the CARv1 archive (.car) to import"
+        )]
+        input: PathBuf,
+    },
+    /// Prune receipts and workflows according to the node's retention
+    /// policy.
+    Prune {
+        /// Database arguments.
+        #[clap(flatten)]
+        args: DbArgs,
+        /// List what would be pruned, without deleting anything.
+        #[arg(
+            long = "dry-run",
+            default_value = "false",
+            help = "List what would be pruned, without deleting anything [optional]"
+        )]
+        dry_run: bool,
+    },
+}
+
 /// CLI Argument types.
 #[derive(Debug, Subcommand)]
 pub enum Command {
@@ -209,7 +364,9 @@ pub enum Command {
         name: Option<String>,
         /// IPVM-configured workflow file to run.
         /// Supported:
-        ///   - JSON (.json).
+        ///   - DAG-JSON (.json, .dag-json);
+        ///   - DAG-CBOR (.cbor, .dagcbor);
+        ///   - YAML (.yaml, .yml).
         #[arg(
             value_hint = clap::ValueHint::FilePath,
             value_name = "FILE",
@@ -218,20 +375,84 @@ pub enum Command {
             required = true,
             help = r#"IPVM-configured workflow file to run.
 Supported:
-  - JSON (.json)"#
+  - DAG-JSON (.json, .dag-json)
+  - DAG-CBOR (.cbor, .dagcbor)
+  - YAML (.yaml, .yml)"#
         )]
         workflow: file::ReadWorkflow,
     },
+    /// Cancel a workflow running on the Homestar runtime.
+    Cancel {
+        /// RPC host / port arguments.
+        #[clap(flatten)]
+        args: RpcArgs,
+        /// Cid or local name of the workflow to cancel.
+        #[arg(
+            value_name = "CID|NAME",
+            index = 1,
+            required = true,
+            help = "Cid or local name of the workflow to cancel"
+        )]
+        workflow: String,
+    },
     /// Get node identity / information.
     Node {
         /// RPC host / port arguments.
         #[clap(flatten)]
         args: RpcArgs,
     },
+    /// Inspect workflows run on the Homestar runtime.
+    Workflow {
+        /// Workflow subcommand.
+        #[clap(subcommand)]
+        command: WorkflowCommand,
+    },
+    /// Inspect Wasm modules.
+    Module {
+        /// Module subcommand.
+        #[clap(subcommand)]
+        command: ModuleCommand,
+    },
+    /// Work with the database directly, without a running node.
+    Db {
+        /// Database subcommand.
+        #[clap(subcommand)]
+        command: DbCommand,
+    },
+    /// Validate a workflow file offline, without a running node.
+    Validate {
+        /// Graph format to print the workflow's batched schedule in.
+        #[arg(
+            short = 'g',
+            long = "graph",
+            value_name = "FORMAT",
+            help = "Print the batched schedule as a graph (dot or mermaid) [optional]"
+        )]
+        graph: Option<GraphFormat>,
+        /// IPVM-configured workflow file to validate.
+        #[arg(
+            value_hint = clap::ValueHint::FilePath,
+            value_name = "FILE",
+            value_parser = clap::value_parser!(file::ReadWorkflow),
+            index = 1,
+            required = true,
+            help = "IPVM-configured workflow file to validate"
+        )]
+        workflow: file::ReadWorkflow,
+    },
     /// Get Homestar binary and other information.
     Info,
 }
 
+/// Graph formats a workflow's batched schedule can be printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz [dot](https://graphviz.org/doc/info/lang.html).
+    Dot,
+    /// [Mermaid](https://mermaid.js.org/syntax/flowchart.html) flowchart.
+    Mermaid,
+}
+
 impl Command {
     fn name(&self) -> &'static str {
         match self {
@@ -240,7 +461,12 @@ impl Command {
             Command::Stop { .. } => "stop",
             Command::Ping { .. } => "ping",
             Command::Run { .. } => "run",
+            Command::Cancel { .. } => "cancel",
             Command::Node { .. } => "node",
+            Command::Workflow { .. } => "workflow",
+            Command::Module { .. } => "module",
+            Command::Db { .. } => "db",
+            Command::Validate { .. } => "validate",
             Command::Info => "info",
         }
     }
@@ -283,6 +509,16 @@ impl Command {
                 response.echo_table()?;
                 Ok(())
             }
+            Command::Cancel { args, workflow } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.cancel(workflow).await??;
+                    Ok::<response::AckCancel, Error>(response)
+                })?;
+
+                response.echo_table()?;
+                Ok(())
+            }
             Command::Node { args } => {
                 let response = rt.block_on(async {
                     let client = args.client().await?;
@@ -293,11 +529,75 @@ impl Command {
                 response.echo_table()?;
                 Ok(())
             }
+            Command::Workflow {
+                command: WorkflowCommand::List { args, filter },
+            } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.list_workflows(filter).await??;
+                    Ok::<response::AckWorkflowList, Error>(response)
+                })?;
+
+                response.echo_table()?;
+                Ok(())
+            }
+            Command::Workflow {
+                command: WorkflowCommand::Show { args, workflow },
+            } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.show_workflow(workflow).await??;
+                    Ok::<Box<response::AckWorkflowDetail>, Error>(response)
+                })?;
+
+                response.echo_table()?;
+                Ok(())
+            }
+            Command::Workflow {
+                command: WorkflowCommand::Receipts { args, cid },
+            } => {
+                let response = rt.block_on(async {
+                    let client = args.client().await?;
+                    let response = client.workflow_receipts(cid).await??;
+                    Ok::<response::AckWorkflowReceipts, Error>(response)
+                })?;
+
+                response.echo_table()?;
+                Ok(())
+            }
+            Command::Module {
+                command: ModuleCommand::Inspect { args, module },
+            } => {
+                let response = if let Ok(cid) = Cid::try_from(module.as_str()) {
+                    rt.block_on(async {
+                        let client = args.client().await?;
+                        let response = client.describe_module(cid).await??;
+                        Ok::<response::AckModule, Error>(response)
+                    })?
+                } else {
+                    rt.block_on(describe_module_file(PathBuf::from(module)))?
+                };
+
+                response.echo_table()?;
+                Ok(())
+            }
             _ => Err(anyhow!("Invalid command {}", self.name()).into()),
         }
     }
 }
 
+/// Describe the exported functions of a local Wasm module file, without a
+/// running node.
+async fn describe_module_file(path: PathBuf) -> anyhow::Result<response::AckModule> {
+    let bytes =
+        std::fs::read(&path).with_context(|| format!("failed to read module @ path: {path:?}"))?;
+    let runtime = WasmRuntime::new(1, None)?;
+    let description = runtime
+        .describe(WasmRuntime::content_cid(&bytes), &bytes)
+        .await?;
+    Ok(response::AckModule::new(description))
+}
+
 impl RpcArgs {
     async fn client(&self) -> Result<Client, Error> {
         let addr = SocketAddr::new(self.host, self.port);
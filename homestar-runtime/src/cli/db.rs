@@ -0,0 +1,136 @@
+//! Commands working on the database directly, without a running node.
+
+use super::{DbArgs, DbCommand, Error};
+use crate::{
+    db::{car, prune, Database},
+    Db, Settings,
+};
+use chrono::Utc;
+use miette::miette;
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{stdout, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Handle `db` subcommands, exporting and importing workflows and their
+/// receipts as CARv1 archives, and pruning them according to the node's
+/// retention policy.
+pub fn handle_db_command(command: DbCommand) -> Result<(), Error> {
+    let mut out = stdout().lock();
+    match command {
+        DbCommand::Export {
+            args,
+            workflow,
+            output,
+        } => {
+            let db = args.db()?;
+            let mut conn = db.conn()?;
+            let write_err =
+                |err| Error::new(miette!("failed to write {}: {err}", output.display()));
+
+            // Streamed to a partial file, only replacing `output` once the
+            // whole archive is written.
+            let partial = partial_path(&output);
+            let mut writer = BufWriter::new(File::create(&partial).map_err(write_err)?);
+            let exported = car::export_workflow::<Db, _>(workflow, &mut writer, &mut conn)
+                .and_then(|receipts| {
+                    writer.flush()?;
+                    Ok(receipts)
+                });
+            drop(writer);
+
+            let receipts = match exported {
+                Ok(receipts) => receipts,
+                Err(err) => {
+                    let _ = fs::remove_file(&partial);
+                    return Err(err.into());
+                }
+            };
+            fs::rename(&partial, &output).map_err(write_err)?;
+            writeln!(
+                out,
+                "exported workflow {workflow} with {receipts} receipts to {}",
+                output.display()
+            )?;
+        }
+        DbCommand::Import { args, input } => {
+            let (settings, db) = args.settings_and_db()?;
+            let file = File::open(&input)
+                .map_err(|err| Error::new(miette!("failed to read {}: {err}", input.display())))?;
+            let imported = car::import::<Db, _>(
+                BufReader::new(file),
+                settings.node().network().receipt_policy(),
+                &mut db.conn()?,
+            )?;
+            writeln!(
+                out,
+                "imported workflow {} ({}) with {} receipts, {} new, and {} blocks",
+                imported.workflow_cid,
+                imported.status,
+                imported.receipts,
+                imported.stored,
+                imported.blocks
+            )?;
+        }
+        DbCommand::Prune { args, dry_run } => {
+            let (settings, db) = args.settings_and_db()?;
+            let pruned = prune::prune::<Db>(
+                &settings.node().db.retention,
+                Utc::now().naive_utc(),
+                dry_run,
+                &mut db.conn()?,
+            )?;
+
+            for cid in &pruned.receipts {
+                writeln!(out, "receipt {cid}")?;
+            }
+            for cid in &pruned.workflows {
+                writeln!(out, "workflow {cid}")?;
+            }
+            for cid in &pruned.blocks {
+                writeln!(out, "block {cid}")?;
+            }
+            writeln!(
+                out,
+                "{} {} receipts, {} workflows and {} blocks",
+                if dry_run { "would prune" } else { "pruned" },
+                pruned.receipts.len(),
+                pruned.workflows.len(),
+                pruned.blocks.len()
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Path an archive is written to before it's renamed to `output`, next to
+/// it so the rename doesn't cross filesystems.
+fn partial_path(output: &Path) -> PathBuf {
+    let mut name = output
+        .file_name()
+        .map(OsString::from)
+        .unwrap_or_else(|| OsString::from("export"));
+    name.push(".part");
+    output.with_file_name(name)
+}
+
+impl DbArgs {
+    fn db(self) -> Result<Db, Error> {
+        self.settings_and_db().map(|(_settings, db)| db)
+    }
+
+    fn settings_and_db(self) -> Result<(Settings, Db), Error> {
+        let settings = if let Some(file) = self.runtime_config {
+            Settings::load_from_file(file)
+        } else {
+            Settings::load()
+        }
+        .map_err(|err| Error::new(miette!("failed to load runtime settings: {err}")))?;
+
+        let db = Db::setup_connection_pool(settings.node(), self.database_url)?;
+        Ok((settings, db))
+    }
+}
@@ -0,0 +1,61 @@
+//! Offline validation of workflow files.
+
+use super::{Error, GraphFormat};
+use crate::{runner::file::ReadWorkflow, workflow};
+use miette::miette;
+use std::io::{stdout, Write};
+
+/// Handle the `validate` command, which parses and validates a workflow file
+/// without a running node, printing issues found and, optionally, the
+/// workflow's batched schedule as a graph.
+///
+/// Returns an [Error] if the workflow cannot be run.
+pub fn handle_validate_command(
+    workflow_file: ReadWorkflow,
+    graph: Option<GraphFormat>,
+) -> Result<(), Error> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let (workflow, _settings, _ucans) =
+        rt.block_on(workflow_file.validate_and_parse())
+            .map_err(|err| {
+                Error::new(miette!(
+                    "failed to parse workflow @ path: {workflow_file}: {err}"
+                ))
+            })?;
+    let validation = workflow::Builder::new(workflow).validate()?;
+
+    let mut out = stdout().lock();
+    for issue in &validation.issues {
+        let severity = if issue.is_error() { "error" } else { "warning" };
+        writeln!(out, "{severity}: {issue}")?;
+    }
+
+    if let Some(graph) = &validation.graph {
+        writeln!(
+            out,
+            "workflow {} is valid: {} tasks in {} batches",
+            validation.cid,
+            validation.num_tasks,
+            graph.schedule.len()
+        )?;
+    }
+
+    match graph {
+        Some(GraphFormat::Dot) => validation.dot()?,
+        Some(GraphFormat::Mermaid) => validation.mermaid(),
+        None => None,
+    }
+    .map_or(Ok(()), |rendered| write!(out, "{rendered}"))?;
+
+    if validation.is_valid() {
+        Ok(())
+    } else {
+        Err(Error::new(miette!(
+            "workflow {} is invalid",
+            validation.cid
+        )))
+    }
+}
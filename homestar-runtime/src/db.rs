@@ -8,23 +8,32 @@ use crate::{
 };
 use anyhow::Result;
 use byte_unit::{AdjustedByte, Byte, ByteUnit};
+use chrono::NaiveDateTime;
 use diesel::{
-    dsl::now,
+    dsl::{count_star, now},
     r2d2::{self, CustomizeConnection, ManageConnection},
-    BelongingToDsl, Connection as SingleConnection, ExpressionMethods, OptionalExtension, QueryDsl,
-    RunQueryDsl, SelectableHelper, SqliteConnection,
+    sql_types::{BigInt, Text},
+    BelongingToDsl, BoolExpressionMethods, Connection as SingleConnection, ExpressionMethods,
+    OptionalExtension, QueryDsl, QueryableByName, RunQueryDsl, SelectableHelper, SqliteConnection,
 };
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use dotenvy::dotenv;
 use homestar_invocation::Pointer;
 use libipld::Cid;
-use std::{env, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::fs;
 use tracing::info;
 
 #[allow(missing_docs, unused_imports)]
 #[rustfmt::skip]
 pub mod schema;
+pub(crate) mod car;
+pub(crate) mod prune;
 pub(crate) mod utils;
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/");
@@ -39,6 +48,10 @@ PRAGMA foreign_keys = ON;           -- enforce foreign keys
 /// Database environment variable.
 pub(crate) const ENV: &str = "DATABASE_URL";
 
+/// Most [Pointer]s bound in a single query, staying under the limit on
+/// host parameters of SQLite versions before 3.32.0, which is 999.
+const MAX_BOUND_POINTERS: usize = 900;
+
 /// A Sqlite connection [pool].
 ///
 /// [pool]: r2d2::Pool
@@ -50,6 +63,26 @@ pub(crate) type Pool = r2d2::Pool<r2d2::ConnectionManager<SqliteConnection>>;
 pub(crate) type Connection =
     r2d2::PooledConnection<r2d2::ConnectionManager<diesel::SqliteConnection>>;
 
+/// DAG-CBOR encoded [Workflow], as stored in the database.
+///
+/// [Workflow]: homestar_workflow::Workflow
+pub(crate) type WorkflowData = Vec<u8>;
+
+/// Workflow left pending or running, along with its [WorkflowData] and
+/// DAG-CBOR encoded requested settings, if stored.
+pub(crate) type InterruptedWorkflow = (workflow::Stored, Option<WorkflowData>, Option<Vec<u8>>);
+
+/// [Receipt] and [Instruction] [Pointer]s selected by a raw SQL query.
+///
+/// [Instruction]: homestar_invocation::task::Instruction
+#[derive(Debug, QueryableByName)]
+struct ReceiptInstruction {
+    #[diesel(sql_type = Text)]
+    cid: Pointer,
+    #[diesel(sql_type = Text)]
+    instruction: Pointer,
+}
+
 /// The database object, which wraps an inner [Arc] to the connection pool.
 #[derive(Debug)]
 pub struct Db {
@@ -159,6 +192,7 @@ pub trait Database: Send + Sync + Clone {
             .values(&receipt)
             .on_conflict(schema::receipts::cid)
             .do_nothing()
+            .returning(Receipt::as_returning())
             .get_result(conn)
             .optional()
     }
@@ -192,6 +226,7 @@ pub trait Database: Send + Sync + Clone {
     ) -> Result<Vec<Receipt>, diesel::result::Error> {
         schema::receipts::dsl::receipts
             .filter(schema::receipts::instruction.eq_any(pointers))
+            .select(Receipt::as_select())
             .load(conn)
     }
 
@@ -204,6 +239,7 @@ pub trait Database: Send + Sync + Clone {
     ) -> Result<Receipt, diesel::result::Error> {
         schema::receipts::dsl::receipts
             .filter(schema::receipts::instruction.eq(Pointer::new(cid)))
+            .select(Receipt::as_select())
             .first(conn)
     }
 
@@ -225,9 +261,80 @@ pub trait Database: Send + Sync + Clone {
     ) -> Result<Vec<Receipt>, diesel::result::Error> {
         schema::receipts::dsl::receipts
             .filter(schema::receipts::cid.eq_any(pointers))
+            .select(Receipt::as_select())
+            .load(conn)
+    }
+
+    /// Select the Cid and [Instruction] [Pointer] of [Receipt]s stored
+    /// before `cutoff`.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn select_receipts_created_before(
+        cutoff: NaiveDateTime,
+        conn: &mut Connection,
+    ) -> Result<Vec<(Pointer, Pointer)>, diesel::result::Error> {
+        schema::receipts::dsl::receipts
+            .filter(schema::receipts::created_at.lt(cutoff))
+            .select((schema::receipts::cid, schema::receipts::instruction))
             .load(conn)
     }
 
+    /// Select the Cid and [Instruction] [Pointer] of [Receipt]s beyond the
+    /// `max` most recently stored receipts of every workflow they belong to.
+    ///
+    /// Receipts shared by workflows are kept as long as any of them still
+    /// counts the receipt among its `max` most recent.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn select_receipts_beyond_count(
+        max: u32,
+        conn: &mut Connection,
+    ) -> Result<Vec<(Pointer, Pointer)>, diesel::result::Error> {
+        diesel::sql_query(
+            "SELECT DISTINCT cid, instruction FROM (
+                SELECT receipts.cid, receipts.instruction, ROW_NUMBER() OVER (
+                    PARTITION BY workflows_receipts.workflow_cid
+                    ORDER BY receipts.created_at DESC, receipts.cid
+                ) AS position
+                FROM receipts
+                INNER JOIN workflows_receipts
+                    ON workflows_receipts.receipt_cid = receipts.cid
+            ) GROUP BY cid, instruction
+            HAVING MIN(position) > ?",
+        )
+        .bind::<BigInt, _>(i64::from(max))
+        .load::<ReceiptInstruction>(conn)
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| (row.cid, row.instruction))
+                .collect()
+        })
+    }
+
+    /// Delete [Receipt]s given their [Pointer]s, along with their ties to
+    /// workflows, returning the number of receipts deleted.
+    fn delete_receipts(
+        pointers: &[Pointer],
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            pointers
+                .chunks(MAX_BOUND_POINTERS)
+                .try_fold(0, |acc, pointers| {
+                    diesel::delete(
+                        schema::workflows_receipts::table
+                            .filter(schema::workflows_receipts::receipt_cid.eq_any(pointers)),
+                    )
+                    .execute(conn)?;
+                    let deleted = diesel::delete(
+                        schema::receipts::table.filter(schema::receipts::cid.eq_any(pointers)),
+                    )
+                    .execute(conn)?;
+                    Ok(acc + deleted)
+                })
+        })
+    }
+
     /// Store localized workflow cid and information, e.g. number of tasks.
     ///
     /// On conflicts, do nothing.
@@ -240,6 +347,7 @@ pub trait Database: Send + Sync + Clone {
             .values(&workflow)
             .on_conflict(schema::workflows::cid)
             .do_nothing()
+            .returning(workflow::Stored::as_returning())
             .get_result(conn)
             .optional()?
         {
@@ -301,6 +409,291 @@ pub trait Database: Send + Sync + Clone {
         })
     }
 
+    /// Select the workflow, [Receipt] and [Instruction] [Pointer]s of the
+    /// receipts belonging to `workflows`.
+    ///
+    /// [Instruction]: homestar_invocation::task::Instruction
+    fn select_workflows_receipts(
+        workflows: &[Pointer],
+        conn: &mut Connection,
+    ) -> Result<Vec<(Pointer, Pointer, Pointer)>, diesel::result::Error> {
+        workflows
+            .chunks(MAX_BOUND_POINTERS)
+            .try_fold(vec![], |mut acc, workflows| {
+                let selected: Vec<(Pointer, Pointer, Pointer)> = schema::workflows_receipts::table
+                    .inner_join(schema::receipts::table)
+                    .filter(schema::workflows_receipts::workflow_cid.eq_any(workflows))
+                    .select((
+                        schema::workflows_receipts::workflow_cid,
+                        schema::receipts::cid,
+                        schema::receipts::instruction,
+                    ))
+                    .load(conn)?;
+                acc.extend(selected);
+                Ok(acc)
+            })
+    }
+
+    /// Select workflows with one of the given `statuses`.
+    fn select_workflows_with_status(
+        statuses: &[workflow::Status],
+        conn: &mut Connection,
+    ) -> Result<Vec<workflow::Stored>, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(schema::workflows::status.eq_any(statuses))
+            .select(workflow::Stored::as_select())
+            .load(conn)
+    }
+
+    /// Select the [Pointer]s of workflows with one of the given `statuses`
+    /// that completed, or if never marked completed were created, before
+    /// `cutoff`.
+    fn select_workflows_finished_before(
+        statuses: &[workflow::Status],
+        cutoff: NaiveDateTime,
+        conn: &mut Connection,
+    ) -> Result<Vec<Pointer>, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(schema::workflows::status.eq_any(statuses))
+            .filter(
+                schema::workflows::completed_at
+                    .lt(cutoff)
+                    .or(schema::workflows::completed_at
+                        .is_null()
+                        .and(schema::workflows::created_at.lt(cutoff))),
+            )
+            .select(schema::workflows::cid)
+            .load(conn)
+    }
+
+    /// Select the [Pointer]s of workflows with one of the given `statuses`,
+    /// stored along with their tasks, that have at least `min_receipts`
+    /// [Receipt]s.
+    fn select_workflows_with_receipts(
+        statuses: &[workflow::Status],
+        min_receipts: i64,
+        conn: &mut Connection,
+    ) -> Result<Vec<Pointer>, diesel::result::Error> {
+        schema::workflows_receipts::table
+            .inner_join(schema::workflows::table)
+            .filter(schema::workflows::status.eq_any(statuses))
+            .filter(schema::workflows::workflow.is_not_null())
+            .group_by(schema::workflows_receipts::workflow_cid)
+            .having(count_star().ge(min_receipts))
+            .select(schema::workflows_receipts::workflow_cid)
+            .load(conn)
+    }
+
+    /// Delete workflows given their [Pointer]s, along with their ties to
+    /// [Receipt]s and the blocks put by their tasks, returning the number of
+    /// workflows deleted.
+    fn delete_workflows(
+        pointers: &[Pointer],
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            pointers
+                .chunks(MAX_BOUND_POINTERS)
+                .try_fold(0, |acc, pointers| {
+                    diesel::delete(
+                        schema::workflows_receipts::table
+                            .filter(schema::workflows_receipts::workflow_cid.eq_any(pointers)),
+                    )
+                    .execute(conn)?;
+                    diesel::delete(
+                        schema::blocks::table.filter(schema::blocks::workflow_cid.eq_any(pointers)),
+                    )
+                    .execute(conn)?;
+                    let deleted = diesel::delete(
+                        schema::workflows::table.filter(schema::workflows::cid.eq_any(pointers)),
+                    )
+                    .execute(conn)?;
+                    Ok(acc + deleted)
+                })
+        })
+    }
+
+    /// Store encoded UCAN tokens, given their Cids.
+    ///
+    /// On conflicts, do nothing.
+    fn store_ucans(
+        ucans: &[(Cid, String)],
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        let rows: Vec<_> = ucans
+            .iter()
+            .map(|(cid, token)| {
+                (
+                    schema::ucans::cid.eq(cid.to_string()),
+                    schema::ucans::token.eq(token),
+                )
+            })
+            .collect();
+
+        diesel::insert_or_ignore_into(schema::ucans::table)
+            .values(&rows)
+            .execute(conn)
+    }
+
+    /// Find an encoded UCAN token given its Cid.
+    fn find_ucan(cid: Cid, conn: &mut Connection) -> Result<Option<String>, diesel::result::Error> {
+        schema::ucans::dsl::ucans
+            .filter(schema::ucans::cid.eq(cid.to_string()))
+            .select(schema::ucans::token)
+            .first(conn)
+            .optional()
+    }
+
+    /// Store a block put by a task of a workflow, given its Cid.
+    ///
+    /// On conflicts, do nothing.
+    fn store_block(
+        workflow_cid: Cid,
+        cid: Cid,
+        data: &[u8],
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        diesel::insert_or_ignore_into(schema::blocks::table)
+            .values((
+                schema::blocks::workflow_cid.eq(Pointer::new(workflow_cid)),
+                schema::blocks::cid.eq(Pointer::new(cid)),
+                schema::blocks::data.eq(data),
+            ))
+            .execute(conn)
+    }
+
+    /// Find a block put by a task of any workflow, given its Cid.
+    fn find_block(
+        cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Option<Vec<u8>>, diesel::result::Error> {
+        schema::blocks::dsl::blocks
+            .filter(schema::blocks::cid.eq(Pointer::new(cid)))
+            .select(schema::blocks::data)
+            .first(conn)
+            .optional()
+    }
+
+    /// Select the workflow and block [Pointer]s of blocks put by tasks of
+    /// workflows other than `protected` ones, which either belong to one of
+    /// `workflows` or were put before `cutoff`.
+    fn select_prunable_blocks(
+        workflows: &[Pointer],
+        cutoff: Option<NaiveDateTime>,
+        protected: &[Pointer],
+        conn: &mut Connection,
+    ) -> Result<Vec<(Pointer, Pointer)>, diesel::result::Error> {
+        let mut blocks: Vec<(Pointer, Pointer)> = match cutoff {
+            Some(cutoff) => schema::blocks::table
+                .filter(schema::blocks::created_at.lt(cutoff))
+                .select((schema::blocks::workflow_cid, schema::blocks::cid))
+                .load(conn)?,
+            None => vec![],
+        };
+
+        for workflows in workflows.chunks(MAX_BOUND_POINTERS) {
+            let query = schema::blocks::table
+                .filter(schema::blocks::workflow_cid.eq_any(workflows))
+                .select((schema::blocks::workflow_cid, schema::blocks::cid))
+                .into_boxed();
+            // Blocks put before the cutoff were selected above.
+            let selected: Vec<(Pointer, Pointer)> = match cutoff {
+                Some(cutoff) => query
+                    .filter(schema::blocks::created_at.ge(cutoff))
+                    .load(conn)?,
+                None => query.load(conn)?,
+            };
+            blocks.extend(selected);
+        }
+
+        // Filtered here rather than in the query, as there may be more
+        // protected workflows than can be bound at once.
+        let protected: HashSet<&Pointer> = protected.iter().collect();
+        blocks.retain(|(workflow_cid, _)| !protected.contains(workflow_cid));
+        Ok(blocks)
+    }
+
+    /// Delete blocks given their workflow and block [Pointer]s, returning
+    /// the number of blocks deleted.
+    fn delete_blocks(
+        blocks: &[(Pointer, Pointer)],
+        conn: &mut Connection,
+    ) -> Result<usize, diesel::result::Error> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            blocks.iter().try_fold(0, |acc, (workflow_cid, cid)| {
+                let deleted = diesel::delete(
+                    schema::blocks::table
+                        .filter(schema::blocks::workflow_cid.eq(workflow_cid))
+                        .filter(schema::blocks::cid.eq(cid)),
+                )
+                .execute(conn)?;
+                Ok(acc + deleted)
+            })
+        })
+    }
+
+    /// Store the DAG-CBOR encoded workflow for a workflow Cid, if it hasn't
+    /// been stored already.
+    fn store_workflow_data(
+        workflow_cid: Cid,
+        data: &[u8],
+        conn: &mut Connection,
+    ) -> Result<(), diesel::result::Error> {
+        diesel::update(schema::workflows::dsl::workflows)
+            .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+            .filter(schema::workflows::workflow.is_null())
+            .set(schema::workflows::workflow.eq(data))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Store the DAG-CBOR encoded [workflow::RequestedSettings] a workflow
+    /// was first run with, if they haven't been stored already.
+    fn store_workflow_settings(
+        workflow_cid: Cid,
+        settings: &[u8],
+        conn: &mut Connection,
+    ) -> Result<(), diesel::result::Error> {
+        diesel::update(schema::workflows::dsl::workflows)
+            .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+            .filter(schema::workflows::settings.is_null())
+            .set(schema::workflows::settings.eq(settings))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Select the DAG-CBOR encoded workflow stored for a workflow Cid.
+    fn select_workflow_data(
+        workflow_cid: Cid,
+        conn: &mut Connection,
+    ) -> Result<Option<WorkflowData>, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(schema::workflows::cid.eq(Pointer::new(workflow_cid)))
+            .select(schema::workflows::workflow)
+            .get_result(conn)
+    }
+
+    /// Select workflows left pending or running, oldest first, along with
+    /// their DAG-CBOR encoded workflow and requested settings if stored.
+    fn select_interrupted_workflows(
+        conn: &mut Connection,
+    ) -> Result<Vec<InterruptedWorkflow>, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(
+                schema::workflows::status
+                    .eq_any([workflow::Status::Pending, workflow::Status::Running]),
+            )
+            .order(schema::workflows::created_at.asc())
+            .select((
+                workflow::Stored::as_select(),
+                schema::workflows::workflow,
+                schema::workflows::settings,
+            ))
+            .load(conn)
+    }
+
     /// Select workflow given a Cid to the workflow.
     fn select_workflow(
         cid: Cid,
@@ -312,6 +705,50 @@ pub trait Database: Send + Sync + Clone {
             .get_result(conn)
     }
 
+    /// Select up to `limit` workflows, most recently created first, filtered
+    /// by an optional status, local name, and creation-time window.
+    fn select_workflows(
+        status: Option<workflow::Status>,
+        name: Option<&str>,
+        since: Option<NaiveDateTime>,
+        until: Option<NaiveDateTime>,
+        limit: u32,
+        conn: &mut Connection,
+    ) -> Result<Vec<workflow::Stored>, diesel::result::Error> {
+        let mut query = schema::workflows::dsl::workflows
+            .select(workflow::Stored::as_select())
+            .order(schema::workflows::created_at.desc())
+            .limit(limit.into())
+            .into_boxed();
+
+        if let Some(status) = status {
+            query = query.filter(schema::workflows::status.eq(status));
+        }
+        if let Some(name) = name {
+            query = query.filter(schema::workflows::name.eq(name));
+        }
+        if let Some(since) = since {
+            query = query.filter(schema::workflows::created_at.ge(since));
+        }
+        if let Some(until) = until {
+            query = query.filter(schema::workflows::created_at.lt(until));
+        }
+
+        query.load(conn)
+    }
+
+    /// Select the most recently created workflow with the given local name.
+    fn select_workflow_by_name(
+        name: &str,
+        conn: &mut Connection,
+    ) -> Result<workflow::Stored, diesel::result::Error> {
+        schema::workflows::dsl::workflows
+            .filter(schema::workflows::name.eq(name))
+            .order(schema::workflows::created_at.desc())
+            .select(workflow::Stored::as_select())
+            .first(conn)
+    }
+
     /// Return workflow information with number of receipts emitted.
     fn get_workflow_info(
         workflow_cid: Cid,
@@ -333,6 +770,31 @@ pub trait Database: Send + Sync + Clone {
         Ok((name, info))
     }
 
+    /// Count the [Receipt]s of each of the given workflows, keyed by
+    /// workflow Cid, in a grouped query per chunk of workflows.
+    ///
+    /// Workflows without receipts are left out.
+    fn count_workflows_receipts(
+        workflows: &[Pointer],
+        conn: &mut Connection,
+    ) -> Result<HashMap<Cid, u32>, diesel::result::Error> {
+        workflows
+            .chunks(MAX_BOUND_POINTERS)
+            .try_fold(HashMap::new(), |mut acc, workflows| {
+                let counts: Vec<(Pointer, i64)> = schema::workflows_receipts::table
+                    .filter(schema::workflows_receipts::workflow_cid.eq_any(workflows))
+                    .group_by(schema::workflows_receipts::workflow_cid)
+                    .select((schema::workflows_receipts::workflow_cid, count_star()))
+                    .load(conn)?;
+                acc.extend(
+                    counts
+                        .into_iter()
+                        .map(|(workflow, count)| (workflow.cid(), count as u32)),
+                );
+                Ok(acc)
+            })
+    }
+
     /// Update the local (view) name of a workflow.
     fn update_local_name(name: &str, conn: &mut Connection) -> Result<(), diesel::result::Error> {
         diesel::update(schema::workflows::dsl::workflows)
@@ -404,7 +866,7 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::test_utils::db::MemoryDb;
+    use crate::test_utils::{self, db::MemoryDb};
 
     #[homestar_runtime_proc_macro::db_async_test]
     fn check_pragmas_memory_db() {
@@ -431,4 +893,176 @@ mod test {
 
         assert_eq!(busy_timeout, vec!["1000".to_string()]);
     }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn select_workflows_with_filters() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let day = |d| {
+            chrono::NaiveDate::from_ymd_opt(2024, 1, d)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+        };
+        let mut rng = rand::thread_rng();
+        let cid1 = homestar_invocation::test_utils::cid::generate_cid(&mut rng);
+        let cid2 = homestar_invocation::test_utils::cid::generate_cid(&mut rng);
+
+        let workflow1 = workflow::Stored::new(
+            Pointer::new(cid1),
+            Some("first".into()),
+            1,
+            workflow::IndexedResources::default(),
+            day(1),
+        );
+        let workflow2 = workflow::Stored::new(
+            Pointer::new(cid2),
+            Some("second".into()),
+            1,
+            workflow::IndexedResources::default(),
+            day(2),
+        );
+        MemoryDb::store_workflow(workflow1.clone(), &mut conn).unwrap();
+        MemoryDb::store_workflow(workflow2.clone(), &mut conn).unwrap();
+        MemoryDb::set_workflow_status(cid2, workflow::Status::Failed, &mut conn).unwrap();
+
+        // Most recent first.
+        let all = MemoryDb::select_workflows(None, None, None, None, 100, &mut conn).unwrap();
+        assert_eq!(
+            all.iter().map(|w| w.cid.cid()).collect::<Vec<_>>(),
+            vec![cid2, cid1]
+        );
+
+        let limited = MemoryDb::select_workflows(None, None, None, None, 1, &mut conn).unwrap();
+        assert_eq!(limited, vec![all[0].clone()]);
+
+        let failed = MemoryDb::select_workflows(
+            Some(workflow::Status::Failed),
+            None,
+            None,
+            None,
+            100,
+            &mut conn,
+        )
+        .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].cid.cid(), cid2);
+        assert_eq!(failed[0].status, workflow::Status::Failed);
+
+        let named =
+            MemoryDb::select_workflows(None, Some("first"), None, None, 100, &mut conn).unwrap();
+        assert_eq!(named, vec![workflow1.clone()]);
+
+        let windowed =
+            MemoryDb::select_workflows(None, None, Some(day(1)), Some(day(2)), 100, &mut conn)
+                .unwrap();
+        assert_eq!(windowed, vec![workflow1]);
+
+        let by_name = MemoryDb::select_workflow_by_name("second", &mut conn).unwrap();
+        assert_eq!(by_name.cid.cid(), cid2);
+        assert!(MemoryDb::select_workflow_by_name("third", &mut conn).is_err());
+
+        let (_, receipt) = test_utils::receipt::receipts();
+        MemoryDb::commit_receipt(cid1, receipt, &mut conn).unwrap();
+        let counts = MemoryDb::count_workflows_receipts(
+            &[Pointer::new(cid1), Pointer::new(cid2)],
+            &mut conn,
+        )
+        .unwrap();
+        assert_eq!(counts, HashMap::from([(cid1, 1)]));
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn select_interrupted_workflows_with_data() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let mut rng = rand::thread_rng();
+        let cid1 = homestar_invocation::test_utils::cid::generate_cid(&mut rng);
+        let cid2 = homestar_invocation::test_utils::cid::generate_cid(&mut rng);
+        let cid3 = homestar_invocation::test_utils::cid::generate_cid(&mut rng);
+
+        for cid in [cid1, cid2, cid3] {
+            MemoryDb::store_workflow(workflow::Stored::default(Pointer::new(cid), 1), &mut conn)
+                .unwrap();
+        }
+        MemoryDb::set_workflow_status(cid2, workflow::Status::Running, &mut conn).unwrap();
+        MemoryDb::set_workflow_status(cid3, workflow::Status::Completed, &mut conn).unwrap();
+
+        MemoryDb::store_workflow_data(cid1, &[1, 2, 3], &mut conn).unwrap();
+        // Already stored data is not overwritten.
+        MemoryDb::store_workflow_data(cid1, &[4, 5, 6], &mut conn).unwrap();
+        MemoryDb::store_workflow_settings(cid1, &[7, 8], &mut conn).unwrap();
+        // Already stored settings are not overwritten either.
+        MemoryDb::store_workflow_settings(cid1, &[9], &mut conn).unwrap();
+
+        let interrupted = MemoryDb::select_interrupted_workflows(&mut conn).unwrap();
+        assert_eq!(interrupted.len(), 2);
+
+        let (stored1, data1, settings1) = interrupted
+            .iter()
+            .find(|(stored, ..)| stored.cid.cid() == cid1)
+            .unwrap();
+        assert_eq!(stored1.status, workflow::Status::Pending);
+        assert_eq!(data1, &Some(vec![1, 2, 3]));
+        assert_eq!(settings1, &Some(vec![7, 8]));
+
+        let (stored2, data2, settings2) = interrupted
+            .iter()
+            .find(|(stored, ..)| stored.cid.cid() == cid2)
+            .unwrap();
+        assert_eq!(stored2.status, workflow::Status::Running);
+        assert_eq!(data2, &None);
+        assert_eq!(settings2, &None);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn helpers_bind_pointers_in_chunks() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let mut rng = rand::thread_rng();
+        let pointers: Vec<Pointer> = (0..=MAX_BOUND_POINTERS)
+            .map(|_| Pointer::new(homestar_invocation::test_utils::cid::generate_cid(&mut rng)))
+            .collect();
+        for pointer in &pointers {
+            MemoryDb::store_workflow(workflow::Stored::default(pointer.clone(), 1), &mut conn)
+                .unwrap();
+        }
+
+        // Tie a receipt and a block to the workflow past the first chunk.
+        let last = pointers.last().unwrap().cid();
+        let block_cid = homestar_invocation::test_utils::cid::generate_cid(&mut rng);
+        MemoryDb::store_block(last, block_cid, b"block", &mut conn).unwrap();
+        let (_, receipt) = test_utils::receipt::receipts();
+        MemoryDb::commit_receipt(last, receipt, &mut conn).unwrap();
+
+        assert_eq!(
+            MemoryDb::select_workflows_receipts(&pointers, &mut conn)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            MemoryDb::count_workflows_receipts(&pointers, &mut conn).unwrap(),
+            HashMap::from([(last, 1)])
+        );
+        assert_eq!(
+            MemoryDb::select_prunable_blocks(&pointers, None, &pointers[..1], &mut conn).unwrap(),
+            vec![(Pointer::new(last), Pointer::new(block_cid))]
+        );
+        assert!(
+            MemoryDb::select_prunable_blocks(&pointers, None, &pointers, &mut conn)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            MemoryDb::delete_workflows(&pointers, &mut conn).unwrap(),
+            pointers.len()
+        );
+    }
 }
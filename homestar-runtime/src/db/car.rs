@@ -0,0 +1,687 @@
+//! Export and import of a [Workflow] and its [Receipt]s as [CARv1] archives.
+//!
+//! An archive's first root is the [Workflow] Cid. Its first block is the
+//! DAG-CBOR encoded [Workflow], followed by the DAG-CBOR encoded
+//! [UCAN Invocation Receipt] of each task run, whose outputs are embedded
+//! in the receipt itself.
+//!
+//! If any [Receipt] is signed, the archive's second root is a DAG-CBOR map
+//! of [Receipt] Cids to their detached signatures, written after the
+//! [Receipt]s. Blocks linked from [Receipt] outputs, e.g. put by tasks,
+//! come last.
+//!
+//! [CARv1]: <https://ipld.io/specs/transport/car/carv1/>
+//! [Workflow]: homestar_workflow::Workflow
+//! [UCAN Invocation Receipt]: homestar_invocation::Receipt
+
+use crate::{
+    db::{Connection, Database},
+    receipt::verifier,
+    settings::ReceiptPolicy,
+    workflow, Receipt,
+};
+use anyhow::{anyhow, bail, Result};
+use diesel::{Connection as SingleConnection, OptionalExtension};
+use homestar_invocation::{
+    consts::DAG_CBOR, ipld::DagCbor, task, Pointer, Receipt as InvocationReceipt,
+};
+use homestar_wasm::io::Arg;
+use homestar_workflow::Workflow;
+use libipld::{
+    cbor::DagCborCodec,
+    multihash::{Code, MultihashDigest},
+    prelude::Codec,
+    Cid, Ipld,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{self, Cursor, Read, Write},
+};
+
+const VERSION: u64 = 1;
+const VERSION_KEY: &str = "version";
+const ROOTS_KEY: &str = "roots";
+
+/// Maximum size of a single section, i.e. the header or a block along with
+/// its Cid, read from an archive.
+const MAX_SECTION_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Summary of a [Workflow] archive imported into the database.
+///
+/// [Workflow]: homestar_workflow::Workflow
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Imported {
+    /// Cid of the imported [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) workflow_cid: Cid,
+    /// Number of [Receipt]s found in the archive.
+    pub(crate) receipts: usize,
+    /// Number of [Receipt]s not already in the database.
+    pub(crate) stored: usize,
+    /// Number of blocks linked from [Receipt] outputs found in the archive.
+    pub(crate) blocks: usize,
+    /// Status of the [Workflow] after import.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) status: workflow::Status,
+}
+
+/// Write a stored [Workflow] and its [Receipt]s to `writer` as a [CARv1]
+/// archive, returning the number of [Receipt]s written.
+///
+/// Blocks linked from [Receipt] outputs are written along, if stored, and so
+/// are the blocks they link to in turn.
+///
+/// [CARv1]: <https://ipld.io/specs/transport/car/carv1/>
+/// [Workflow]: homestar_workflow::Workflow
+pub(crate) fn export_workflow<DB: Database, W: Write>(
+    workflow_cid: Cid,
+    writer: W,
+    conn: &mut Connection,
+) -> Result<usize> {
+    let data = DB::select_workflow_data(workflow_cid, conn)
+        .optional()?
+        .ok_or_else(|| anyhow!("workflow {workflow_cid} not found"))?
+        .ok_or_else(|| anyhow!("workflow {workflow_cid} was stored without its tasks"))?;
+    let (_, info) = DB::get_workflow_info(workflow_cid, conn)?;
+    let pointers = info.progress().iter().copied().map(Pointer::new).collect();
+    let receipts = DB::find_receipt_pointers(&pointers, conn)?;
+
+    let signatures: BTreeMap<String, Ipld> = receipts
+        .iter()
+        .filter_map(|receipt| {
+            receipt
+                .signature()
+                .map(|signature| (receipt.cid().to_string(), Ipld::Bytes(signature.to_vec())))
+        })
+        .collect();
+    let signatures = if signatures.is_empty() {
+        None
+    } else {
+        let data = DagCborCodec.encode(&Ipld::Map(signatures))?;
+        Some((Cid::new_v1(DAG_CBOR, Code::Sha3_256.digest(&data)), data))
+    };
+
+    let mut seen: HashSet<Cid> = receipts.iter().map(Receipt::cid).collect();
+    seen.insert(workflow_cid);
+    let mut pending: Vec<Cid> = receipts
+        .iter()
+        .flat_map(|receipt| output_links(receipt.output()))
+        .collect();
+    let mut linked = vec![];
+    while let Some(cid) = pending.pop() {
+        if !seen.insert(cid) {
+            continue;
+        }
+        // Links to blocks not stored, e.g. fetched resources, are left out.
+        let Some(data) = DB::find_block(cid, conn)? else {
+            continue;
+        };
+        pending.extend(block_links(cid, &data));
+        linked.push((cid, data));
+    }
+
+    let mut roots = vec![workflow_cid];
+    roots.extend(signatures.iter().map(|(cid, _)| *cid));
+    let mut car = CarWriter::new(writer, &roots)?;
+    car.write_block(workflow_cid, &data)?;
+    for receipt in &receipts {
+        car.write_block(receipt.cid(), &receipt.block()?)?;
+    }
+    for (cid, data) in signatures.iter().chain(linked.iter()) {
+        car.write_block(*cid, data)?;
+    }
+    car.finish()?;
+
+    Ok(receipts.len())
+}
+
+/// Read a [CARv1] archive written by [export_workflow], verifying each
+/// block against its Cid and each [Receipt] against the node's
+/// [ReceiptPolicy], and store the [Workflow], its [Receipt]s and linked
+/// blocks within a single transaction.
+///
+/// Once imported, running the [Workflow] replays it from the stored
+/// [Receipt]s instead of recomputing its tasks.
+///
+/// [CARv1]: <https://ipld.io/specs/transport/car/carv1/>
+/// [Workflow]: homestar_workflow::Workflow
+pub(crate) fn import<DB: Database, R: Read>(
+    reader: R,
+    policy: &ReceiptPolicy,
+    conn: &mut Connection,
+) -> Result<Imported> {
+    let mut car = CarReader::new(reader)?;
+    let (workflow_cid, signatures_cid) = match car.roots() {
+        [root] => (*root, None),
+        [root, signatures] => (*root, Some(*signatures)),
+        roots => bail!(
+            "expected a workflow root and optional signatures, found {} roots",
+            roots.len()
+        ),
+    };
+
+    let (cid, workflow_data) = car
+        .next_block()?
+        .ok_or_else(|| anyhow!("archive is missing workflow {workflow_cid}"))?;
+    if cid != workflow_cid {
+        bail!("expected workflow {workflow_cid} as the first block, found {cid}");
+    }
+
+    let workflow = Workflow::<Arg>::from_cbor(&workflow_data)?;
+    let num_tasks = workflow.len();
+    let graph = workflow::Builder::new(workflow).graph()?;
+    let instructions = graph
+        .schedule
+        .iter()
+        .flatten()
+        .map(|node| Ok((node.inner().invocation.cid(), node.name().parse::<Cid>()?)))
+        .collect::<Result<HashMap<Cid, Cid>>>()?;
+
+    let mut receipts = vec![];
+    let mut signatures = HashMap::new();
+    let mut linked = HashSet::new();
+    let mut blocks = vec![];
+    while let Some((cid, data)) = car.next_block()? {
+        if Some(cid) == signatures_cid {
+            signatures = read_signatures(&data)?;
+            continue;
+        }
+
+        match read_receipt(cid, &data, &instructions) {
+            Ok(receipt) => {
+                linked.extend(output_links(receipt.output()));
+                receipts.push(receipt);
+            }
+            Err(_) if linked.contains(&cid) => {
+                linked.extend(block_links(cid, &data));
+                blocks.push((cid, data));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    for receipt in receipts.iter_mut() {
+        if let Some(signature) = signatures.remove(&receipt.cid()) {
+            receipt.set_signature(signature);
+        }
+        verifier::verify(receipt, policy)
+            .map_err(|rejection| anyhow!("receipt {} rejected: {rejection}", receipt.cid()))?;
+    }
+
+    let receipt_cids: Vec<Cid> = receipts.iter().map(Receipt::cid).collect();
+    let num_receipts = receipts.len();
+    // As with a worker, a workflow with any failed task has failed.
+    let failed = receipts
+        .iter()
+        .any(|receipt| matches!(receipt.output(), task::Result::Error(_)));
+    let num_blocks = blocks.len();
+    let stored_workflow = workflow::Stored::new_with_resources(
+        Pointer::new(workflow_cid),
+        Some(workflow_cid.to_string()),
+        num_tasks as i32,
+        graph.indexed_resources,
+    );
+
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        DB::store_workflow(stored_workflow, conn)?;
+        DB::store_workflow_data(workflow_cid, &workflow_data, conn)?;
+        let stored = DB::store_receipts(receipts, conn)?;
+        DB::store_workflow_receipts(workflow_cid, &receipt_cids, conn)?;
+        for (cid, data) in &blocks {
+            DB::store_block(workflow_cid, *cid, data, conn)?;
+        }
+
+        let (_, info) = DB::get_workflow_info(workflow_cid, conn)?;
+        let status = if failed {
+            DB::set_workflow_status(workflow_cid, workflow::Status::Failed, conn)?;
+            workflow::Status::Failed
+        } else if info.progress_count() >= info.num_tasks() {
+            DB::set_workflow_status(workflow_cid, workflow::Status::Completed, conn)?;
+            workflow::Status::Completed
+        } else {
+            DB::select_workflow(workflow_cid, conn)?.status
+        };
+
+        Ok(Imported {
+            workflow_cid,
+            receipts: num_receipts,
+            stored,
+            blocks: num_blocks,
+            status,
+        })
+    })
+}
+
+/// Decode a [Receipt] block for one of the workflow's `instructions`,
+/// keyed by the Cid of their invocation.
+fn read_receipt(cid: Cid, data: &[u8], instructions: &HashMap<Cid, Cid>) -> Result<Receipt> {
+    let ipld: Ipld = DagCborCodec.decode(data)?;
+    let invocation_receipt = InvocationReceipt::<Ipld>::try_from(ipld)?;
+    let ran = invocation_receipt.ran().cid();
+    let instruction = instructions
+        .get(&ran)
+        .ok_or_else(|| anyhow!("receipt {cid} ran {ran}, which is not part of the workflow"))?;
+    let receipt = Receipt::try_with(Pointer::new(*instruction), &invocation_receipt)?;
+    if receipt.cid() != cid {
+        bail!("receipt {cid} does not match its encoding");
+    }
+    Ok(receipt)
+}
+
+/// Decode the map of [Receipt] Cids to their detached signatures.
+fn read_signatures(data: &[u8]) -> Result<HashMap<Cid, Vec<u8>>> {
+    let Ipld::Map(signatures) = DagCborCodec.decode(data)? else {
+        bail!("archive signatures are not a map");
+    };
+
+    signatures
+        .into_iter()
+        .map(|(cid, signature)| match signature {
+            Ipld::Bytes(signature) => Ok((Cid::try_from(cid.as_str())?, signature)),
+            other => Err(anyhow!(
+                "signature of receipt {cid} is not bytes: {other:?}"
+            )),
+        })
+        .collect()
+}
+
+/// Cids linked from a [Receipt]'s output.
+fn output_links(output: &task::Result<Ipld>) -> Vec<Cid> {
+    let mut cids = vec![];
+    match output {
+        task::Result::Ok(ipld) | task::Result::Error(ipld) | task::Result::Just(ipld) => {
+            links(ipld, &mut cids)
+        }
+    }
+    cids
+}
+
+/// Cids linked from a block, if DAG-CBOR encoded.
+fn block_links(cid: Cid, data: &[u8]) -> Vec<Cid> {
+    let mut cids = vec![];
+    if cid.codec() == DAG_CBOR {
+        if let Ok(ipld) = DagCborCodec.decode::<Ipld>(data) {
+            links(&ipld, &mut cids);
+        }
+    }
+    cids
+}
+
+fn links(ipld: &Ipld, cids: &mut Vec<Cid>) {
+    match ipld {
+        Ipld::Link(cid) => cids.push(*cid),
+        Ipld::List(list) => list.iter().for_each(|ipld| links(ipld, cids)),
+        Ipld::Map(map) => map.values().for_each(|ipld| links(ipld, cids)),
+        _ => {}
+    }
+}
+
+/// Writer of [CARv1] archives, one block at a time.
+///
+/// [CARv1]: <https://ipld.io/specs/transport/car/carv1/>
+#[derive(Debug)]
+pub(crate) struct CarWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> CarWriter<W> {
+    /// Create a new [CarWriter], writing the archive's header for the given
+    /// roots.
+    pub(crate) fn new(mut writer: W, roots: &[Cid]) -> Result<Self> {
+        let header = Ipld::Map(BTreeMap::from([
+            (
+                ROOTS_KEY.into(),
+                Ipld::List(roots.iter().copied().map(Ipld::Link).collect()),
+            ),
+            (VERSION_KEY.into(), Ipld::Integer(VERSION.into())),
+        ]));
+        write_section(&mut writer, &DagCborCodec.encode(&header)?)?;
+        Ok(Self { writer })
+    }
+
+    /// Write a block of `data`, addressed by `cid`.
+    pub(crate) fn write_block(&mut self, cid: Cid, data: &[u8]) -> Result<()> {
+        let mut section = cid.to_bytes();
+        section.extend_from_slice(data);
+        write_section(&mut self.writer, &section)
+    }
+
+    /// Flush the archive, returning the inner writer.
+    pub(crate) fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reader of [CARv1] archives, verifying each block read against its Cid.
+///
+/// [CARv1]: <https://ipld.io/specs/transport/car/carv1/>
+#[derive(Debug)]
+pub(crate) struct CarReader<R> {
+    reader: R,
+    roots: Vec<Cid>,
+}
+
+impl<R: Read> CarReader<R> {
+    /// Create a new [CarReader], reading the archive's header.
+    pub(crate) fn new(mut reader: R) -> Result<Self> {
+        let header = read_section(&mut reader)?.ok_or_else(|| anyhow!("archive is empty"))?;
+        let Ipld::Map(mut header) = DagCborCodec.decode(&header)? else {
+            bail!("archive header is not a map");
+        };
+
+        match header.remove(VERSION_KEY) {
+            Some(Ipld::Integer(version)) if version == VERSION.into() => {}
+            version => bail!("unsupported archive version: {version:?}"),
+        }
+
+        let Some(Ipld::List(roots)) = header.remove(ROOTS_KEY) else {
+            bail!("archive header is missing its roots");
+        };
+        let roots = roots
+            .into_iter()
+            .map(|root| match root {
+                Ipld::Link(cid) => Ok(cid),
+                other => Err(anyhow!("archive root is not a link: {other:?}")),
+            })
+            .collect::<Result<Vec<Cid>>>()?;
+
+        Ok(Self { reader, roots })
+    }
+
+    /// Roots of the archive.
+    pub(crate) fn roots(&self) -> &[Cid] {
+        &self.roots
+    }
+
+    /// Read the next block, if any, erroring if its data does not hash to
+    /// its Cid.
+    pub(crate) fn next_block(&mut self) -> Result<Option<(Cid, Vec<u8>)>> {
+        let Some(section) = read_section(&mut self.reader)? else {
+            return Ok(None);
+        };
+
+        let mut cursor = Cursor::new(section);
+        let cid = Cid::read_bytes(&mut cursor)?;
+        let offset = cursor.position() as usize;
+        let data = cursor.into_inner().split_off(offset);
+
+        let code = Code::try_from(cid.hash().code())?;
+        if code.digest(&data) != *cid.hash() {
+            bail!("block {cid} does not match its data");
+        }
+
+        Ok(Some((cid, data)))
+    }
+}
+
+fn write_section<W: Write>(writer: &mut W, section: &[u8]) -> Result<()> {
+    let mut len = section.len() as u64;
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            writer.write_all(&[byte])?;
+            break;
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+    writer.write_all(section)?;
+    Ok(())
+}
+
+/// Read a varint length-prefixed section, or [None] at the end of the
+/// archive.
+fn read_section<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+    let mut len: u64 = 0;
+    let mut byte = [0u8; 1];
+    for shift in (0..64).step_by(7) {
+        match reader.read_exact(&mut byte) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof && shift == 0 => {
+                return Ok(None)
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        len |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            if len > MAX_SECTION_SIZE {
+                bail!("archive section of {len} bytes exceeds the maximum of {MAX_SECTION_SIZE}");
+            }
+            let mut section = vec![0; usize::try_from(len)?];
+            reader.read_exact(&mut section)?;
+            return Ok(Some(section));
+        }
+    }
+
+    bail!("archive section length overflows")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        receipt::signer::ReceiptSigner, test_utils::db::MemoryDb, workflow::IndexedResources,
+    };
+    use homestar_invocation::{
+        authority::UcanPrf,
+        task::{self, instruction::RunInstruction, Resources},
+        test_utils, Invocation, Task,
+    };
+    use libp2p::identity::Keypair;
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn export_and_import_workflow() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2.clone()),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![task1.clone(), task2.clone()]);
+        let workflow_cid = workflow.clone().to_cid().unwrap();
+        let workflow_data = workflow.to_cbor().unwrap();
+
+        // The first task's output links to a block it put.
+        let block = Ipld::List(vec![Ipld::Integer(4)]);
+        let block_data = DagCborCodec.encode(&block).unwrap();
+        let block_cid = Cid::new_v1(DAG_CBOR, Code::Sha3_256.digest(&block_data));
+
+        let signer = ReceiptSigner::new(Keypair::generate_ed25519()).unwrap();
+        let receipts = [
+            (task1, instruction1, Ipld::Link(block_cid), Some(&signer)),
+            (task2, instruction2, Ipld::Integer(44), None),
+        ]
+        .map(|(task, instruction, out, signer)| {
+            let invocation_receipt = InvocationReceipt::new(
+                Invocation::new(task).try_into().unwrap(),
+                task::Result::Ok(out),
+                Ipld::Null,
+                signer.map(|signer| signer.issuer().to_owned()),
+                UcanPrf::default(),
+            );
+            let mut receipt =
+                Receipt::try_with(instruction.try_into().unwrap(), &invocation_receipt).unwrap();
+            if let Some(signer) = signer {
+                let signature = signer
+                    .sign(receipt.instruction(), &invocation_receipt)
+                    .unwrap();
+                receipt.set_signature(signature);
+            }
+            receipt
+        });
+        let receipt_cids: Vec<Cid> = receipts.iter().map(Receipt::cid).collect();
+
+        MemoryDb::store_workflow(
+            workflow::Stored::new_with_resources(
+                Pointer::new(workflow_cid),
+                None,
+                2,
+                IndexedResources::default(),
+            ),
+            &mut conn,
+        )
+        .unwrap();
+        MemoryDb::store_workflow_data(workflow_cid, &workflow_data, &mut conn).unwrap();
+        MemoryDb::store_receipts(receipts.to_vec(), &mut conn).unwrap();
+        MemoryDb::store_workflow_receipts(workflow_cid, &receipt_cids, &mut conn).unwrap();
+        MemoryDb::store_block(workflow_cid, block_cid, &block_data, &mut conn).unwrap();
+
+        let mut archive = vec![];
+        let exported = export_workflow::<MemoryDb, _>(workflow_cid, &mut archive, &mut conn);
+        assert_eq!(exported.unwrap(), 2);
+
+        let reader = CarReader::new(archive.as_slice()).unwrap();
+        assert_eq!(reader.roots().len(), 2);
+        assert_eq!(reader.roots()[0], workflow_cid);
+
+        let mut import_settings = settings.clone();
+        import_settings.node.db.url = Some("export_and_import_workflow_import.db".to_string());
+        let import_db = MemoryDb::setup_connection_pool(import_settings.node(), None).unwrap();
+        let mut import_conn = import_db.conn().unwrap();
+
+        // The second receipt is unsigned.
+        assert!(import::<MemoryDb, _>(
+            archive.as_slice(),
+            &ReceiptPolicy::Signed,
+            &mut import_conn
+        )
+        .is_err());
+        assert!(MemoryDb::select_workflow_data(workflow_cid, &mut import_conn).is_err());
+
+        let imported =
+            import::<MemoryDb, _>(archive.as_slice(), &ReceiptPolicy::All, &mut import_conn)
+                .unwrap();
+        assert_eq!(
+            imported,
+            Imported {
+                workflow_cid,
+                receipts: 2,
+                stored: 2,
+                blocks: 1,
+                status: workflow::Status::Completed,
+            }
+        );
+        assert_eq!(
+            MemoryDb::find_block(block_cid, &mut import_conn).unwrap(),
+            Some(block_data)
+        );
+
+        let (_, info) = MemoryDb::get_workflow_info(workflow_cid, &mut import_conn).unwrap();
+        assert_eq!(info.progress_count(), 2);
+        assert_eq!(info.resources.len(), 2);
+        for receipt in &receipts {
+            let stored = MemoryDb::find_receipt_by_cid(receipt.cid(), &mut import_conn).unwrap();
+            assert_eq!(&stored, receipt);
+        }
+        assert_eq!(
+            MemoryDb::select_workflow_data(workflow_cid, &mut import_conn).unwrap(),
+            Some(workflow_data)
+        );
+
+        // Importing again stores nothing new.
+        let reimported =
+            import::<MemoryDb, _>(archive.as_slice(), &ReceiptPolicy::All, &mut import_conn)
+                .unwrap();
+        assert_eq!(reimported.stored, 0);
+
+        // Tampering with a block fails verification.
+        let last = archive.len() - 1;
+        archive[last] ^= 0xff;
+        assert!(
+            import::<MemoryDb, _>(archive.as_slice(), &ReceiptPolicy::All, &mut import_conn)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn read_section_over_maximum_size() {
+        let mut archive = vec![];
+        write_section(&mut archive, &[0; 8]).unwrap();
+        assert_eq!(
+            read_section(&mut archive.as_slice()).unwrap(),
+            Some(vec![0; 8])
+        );
+
+        // Only the length prefix is needed for the section to be rejected.
+        let mut len = vec![];
+        let mut remaining = MAX_SECTION_SIZE + 1;
+        while remaining >= 0x80 {
+            len.push((remaining & 0x7f) as u8 | 0x80);
+            remaining >>= 7;
+        }
+        len.push(remaining as u8);
+        assert!(read_section(&mut len.as_slice()).is_err());
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn import_workflow_with_failed_task() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let (instruction, _, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction.clone()),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![task.clone()]);
+        let workflow_cid = workflow.clone().to_cid().unwrap();
+        let workflow_data = workflow.to_cbor().unwrap();
+
+        let invocation_receipt = InvocationReceipt::new(
+            Invocation::new(task).try_into().unwrap(),
+            task::Result::Error(Ipld::String("out of fuel".to_string())),
+            Ipld::Null,
+            None,
+            UcanPrf::default(),
+        );
+        let receipt =
+            Receipt::try_with(instruction.try_into().unwrap(), &invocation_receipt).unwrap();
+
+        MemoryDb::store_workflow(
+            workflow::Stored::new_with_resources(
+                Pointer::new(workflow_cid),
+                None,
+                1,
+                IndexedResources::default(),
+            ),
+            &mut conn,
+        )
+        .unwrap();
+        MemoryDb::store_workflow_data(workflow_cid, &workflow_data, &mut conn).unwrap();
+        MemoryDb::commit_receipt(workflow_cid, receipt, &mut conn).unwrap();
+
+        let mut archive = vec![];
+        export_workflow::<MemoryDb, _>(workflow_cid, &mut archive, &mut conn).unwrap();
+
+        let mut import_settings = settings.clone();
+        import_settings.node.db.url = Some("import_workflow_with_failed_task.db".to_string());
+        let import_db = MemoryDb::setup_connection_pool(import_settings.node(), None).unwrap();
+        let mut import_conn = import_db.conn().unwrap();
+
+        let imported =
+            import::<MemoryDb, _>(archive.as_slice(), &ReceiptPolicy::All, &mut import_conn)
+                .unwrap();
+        assert_eq!(imported.status, workflow::Status::Failed);
+        assert_eq!(
+            MemoryDb::select_workflow(workflow_cid, &mut import_conn)
+                .unwrap()
+                .status,
+            workflow::Status::Failed
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Pruning of stored [Receipt]s, workflows and the blocks put by their
+//! tasks, following the node's [Retention] policy.
+//!
+//! [Receipt]: crate::Receipt
+//! [Retention]: crate::settings::Retention
+
+use crate::{
+    db::{Connection, Database},
+    settings::Retention,
+    workflow,
+};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use diesel::Connection as SingleConnection;
+use homestar_invocation::{
+    ipld::DagCbor,
+    task::instruction::{Parse, RunInstruction},
+    Pointer,
+};
+use homestar_wasm::io::Arg;
+use homestar_workflow::Workflow;
+use libipld::Cid;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::warn;
+
+/// Statuses of workflows that may still run, whose receipts are protected.
+const ACTIVE: [workflow::Status; 3] = [
+    workflow::Status::Pending,
+    workflow::Status::Running,
+    workflow::Status::Stuck,
+];
+
+/// Statuses of workflows that are done running.
+const TERMINAL: [workflow::Status; 3] = [
+    workflow::Status::Completed,
+    workflow::Status::Failed,
+    workflow::Status::Cancelled,
+];
+
+/// [Receipt]s and workflows pruned, or to be pruned on a dry-run.
+///
+/// [Receipt]: crate::Receipt
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Pruned {
+    /// Cids of pruned [Receipt]s.
+    ///
+    /// [Receipt]: crate::Receipt
+    pub(crate) receipts: Vec<Cid>,
+    /// Cids of pruned workflows.
+    pub(crate) workflows: Vec<Cid>,
+    /// Cids of pruned blocks put by tasks.
+    pub(crate) blocks: Vec<Cid>,
+}
+
+/// Prune stored [Receipt]s and workflows according to the [Retention]
+/// policy, relative to `now`, skipping the deletion itself on a `dry_run`.
+///
+/// [Receipt]s belonging to, or awaited by, workflows that are pending,
+/// running or stuck are never pruned, nor are blocks put by their tasks.
+/// When such a workflow was stored without its tasks, receipts for any of
+/// its instructions are kept.
+/// Other blocks are pruned along with their workflow, or once older than
+/// the maximum age.
+///
+/// What to keep is read in the same transaction as the deletion, so
+/// workflows started meanwhile are protected.
+///
+/// [Receipt]: crate::Receipt
+pub(crate) fn prune<DB: Database>(
+    retention: &Retention,
+    now: NaiveDateTime,
+    dry_run: bool,
+    conn: &mut Connection,
+) -> Result<Pruned> {
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        let cutoff = retention.max_age.map(|max_age| now - max_age);
+        let mut protected_workflows = vec![];
+        let mut awaited_instructions = HashSet::new();
+
+        for stored in DB::select_workflows_with_status(&ACTIVE, conn)? {
+            let workflow_cid = stored.cid.cid();
+            protected_workflows.push(Pointer::new(workflow_cid));
+            match awaits::<DB>(workflow_cid, conn)? {
+                Some(awaits) => awaited_instructions.extend(awaits.into_values().flatten()),
+                // Without its tasks, what the workflow awaits is unknown, so
+                // conservatively treat all of its instructions as awaited.
+                None => awaited_instructions.extend(stored.resources.inner().keys().copied()),
+            }
+        }
+
+        let pruned_workflows: Vec<Pointer> = match cutoff {
+            Some(cutoff) => DB::select_workflows_finished_before(&TERMINAL, cutoff, conn)?,
+            None => vec![],
+        };
+
+        let mut intermediate_instructions: HashMap<Pointer, HashSet<Cid>> = HashMap::new();
+        if retention.terminal_only {
+            // Only workflows with more than one receipt left can still have
+            // an intermediate one.
+            for workflow in DB::select_workflows_with_receipts(&TERMINAL, 2, conn)? {
+                if let Some(awaits) = awaits::<DB>(workflow.cid(), conn)? {
+                    let intermediate: HashSet<Cid> = awaits
+                        .values()
+                        .flatten()
+                        .filter(|awaited| awaits.contains_key(awaited))
+                        .copied()
+                        .collect();
+                    intermediate_instructions.insert(workflow, intermediate);
+                }
+            }
+        }
+
+        let mut candidates: BTreeMap<Cid, Cid> = BTreeMap::new();
+
+        if !intermediate_instructions.is_empty() {
+            let workflows: Vec<Pointer> = intermediate_instructions.keys().cloned().collect();
+            for (workflow, receipt, instruction) in DB::select_workflows_receipts(&workflows, conn)?
+            {
+                if intermediate_instructions
+                    .get(&workflow)
+                    .is_some_and(|intermediate| intermediate.contains(&instruction.cid()))
+                {
+                    candidates.insert(receipt.cid(), instruction.cid());
+                }
+            }
+        }
+
+        if let Some(cutoff) = cutoff {
+            candidates.extend(
+                DB::select_receipts_created_before(cutoff, conn)?
+                    .into_iter()
+                    .map(|(receipt, instruction)| (receipt.cid(), instruction.cid())),
+            );
+        }
+
+        if let Some(max) = retention.max_receipts_per_workflow {
+            candidates.extend(
+                DB::select_receipts_beyond_count(max, conn)?
+                    .into_iter()
+                    .map(|(receipt, instruction)| (receipt.cid(), instruction.cid())),
+            );
+        }
+
+        let protected_receipts: HashSet<Cid> = if protected_workflows.is_empty() {
+            HashSet::new()
+        } else {
+            DB::select_workflows_receipts(&protected_workflows, conn)?
+                .into_iter()
+                .map(|(_, receipt, _)| receipt.cid())
+                .collect()
+        };
+
+        let pruned_blocks =
+            DB::select_prunable_blocks(&pruned_workflows, cutoff, &protected_workflows, conn)?;
+
+        let pruned = Pruned {
+            receipts: candidates
+                .into_iter()
+                .filter(|(receipt, instruction)| {
+                    !protected_receipts.contains(receipt)
+                        && !awaited_instructions.contains(instruction)
+                })
+                .map(|(receipt, _)| receipt)
+                .collect(),
+            workflows: pruned_workflows
+                .iter()
+                .map(|workflow| workflow.cid())
+                .collect(),
+            blocks: pruned_blocks.iter().map(|(_, cid)| cid.cid()).collect(),
+        };
+
+        if !dry_run {
+            let receipt_pointers: Vec<Pointer> =
+                pruned.receipts.iter().copied().map(Pointer::new).collect();
+            DB::delete_receipts(&receipt_pointers, conn)?;
+            DB::delete_blocks(&pruned_blocks, conn)?;
+            DB::delete_workflows(&pruned_workflows, conn)?;
+        }
+
+        Ok(pruned)
+    })
+}
+
+/// Map of each task's [Instruction] Cid to the [Instruction] Cids it awaits,
+/// if the workflow was stored along with its tasks.
+///
+/// [Instruction]: homestar_invocation::task::Instruction
+fn awaits<DB: Database>(
+    workflow_cid: Cid,
+    conn: &mut Connection,
+) -> Result<Option<HashMap<Cid, Vec<Cid>>>> {
+    let Some(data) = DB::select_workflow_data(workflow_cid, conn)? else {
+        return Ok(None);
+    };
+
+    let workflow = match Workflow::<Arg>::from_cbor(&data) {
+        Ok(workflow) => workflow,
+        Err(err) => {
+            warn!(
+                subject = "db.prune.err",
+                category = "db.prune",
+                workflow_cid = workflow_cid.to_string(),
+                err=?err,
+                "failed to decode stored workflow"
+            );
+            return Ok(None);
+        }
+    };
+
+    workflow
+        .tasks_ref()
+        .iter()
+        .map(|task| {
+            let awaits = match task.run() {
+                RunInstruction::Expanded(instruction) => {
+                    instruction.input().parse()?.args().deferreds().collect()
+                }
+                RunInstruction::Ptr(_) => vec![],
+            };
+            Ok((task.instruction_cid()?, awaits))
+        })
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        test_utils::{self, db::MemoryDb},
+        workflow::IndexedResources,
+        Receipt,
+    };
+    use chrono::Utc;
+    use homestar_invocation::{
+        authority::UcanPrf,
+        task::{self, Resources},
+        Invocation, Receipt as InvocationReceipt, Task,
+    };
+    use indexmap::IndexMap;
+    use libipld::{
+        multihash::{Code, MultihashDigest},
+        Ipld,
+    };
+    use std::time::Duration;
+
+    fn store_workflow(tasks: Vec<Task<'static, Arg>>, conn: &mut Connection) -> Cid {
+        let workflow = Workflow::new(tasks);
+        let workflow_cid = workflow.clone().to_cid().unwrap();
+        MemoryDb::store_workflow(
+            workflow::Stored::new_with_resources(
+                Pointer::new(workflow_cid),
+                None,
+                workflow.len() as i32,
+                IndexedResources::default(),
+            ),
+            conn,
+        )
+        .unwrap();
+        MemoryDb::store_workflow_data(workflow_cid, &workflow.to_cbor().unwrap(), conn).unwrap();
+        workflow_cid
+    }
+
+    fn store_receipt(workflow_cid: Cid, task: Task<'static, Arg>, conn: &mut Connection) -> Cid {
+        let invocation_receipt = InvocationReceipt::new(
+            Invocation::new(task.clone()).try_into().unwrap(),
+            task::Result::Ok(Ipld::Integer(1)),
+            Ipld::Null,
+            None,
+            UcanPrf::default(),
+        );
+        let instruction = Pointer::new(task.instruction_cid().unwrap());
+        let receipt = Receipt::try_with(instruction, &invocation_receipt).unwrap();
+        MemoryDb::commit_receipt(workflow_cid, receipt, conn)
+            .unwrap()
+            .cid()
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn prune_with_retention() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let (instruction, dep_instruction1, dep_instruction2) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let [task, dep_task1, dep_task2] =
+            [instruction, dep_instruction1, dep_instruction2].map(|instruction| {
+                Task::new(
+                    RunInstruction::Expanded(instruction),
+                    Resources::default().into(),
+                    UcanPrf::default(),
+                )
+            });
+
+        // Completed workflow, whose second task awaits its first.
+        let completed = store_workflow(vec![task.clone(), dep_task1.clone()], &mut conn);
+        let first = store_receipt(completed, task, &mut conn);
+        let second = store_receipt(completed, dep_task1, &mut conn);
+        MemoryDb::set_workflow_status(completed, workflow::Status::Completed, &mut conn).unwrap();
+
+        // Running workflow, awaiting both of the completed workflow's tasks.
+        let running = store_workflow(vec![dep_task2], &mut conn);
+        MemoryDb::set_workflow_status(running, workflow::Status::Running, &mut conn).unwrap();
+
+        // Blocks put by tasks of each workflow.
+        let block = Cid::new_v1(0x55, Code::Sha3_256.digest(b"block"));
+        MemoryDb::store_block(completed, block, b"block", &mut conn).unwrap();
+        MemoryDb::store_block(running, block, b"block", &mut conn).unwrap();
+
+        // Receipt not tied to any workflow, e.g. received over the network.
+        let (_, unrelated) = test_utils::receipt::receipts();
+        MemoryDb::store_receipt(unrelated.clone(), &mut conn).unwrap();
+
+        let now = Utc::now().naive_utc();
+        let later = now + Duration::from_secs(2 * 86400);
+        let max_age = Retention {
+            max_age: Some(Duration::from_secs(86400)),
+            ..Default::default()
+        };
+        let terminal_only = Retention {
+            terminal_only: true,
+            ..Default::default()
+        };
+
+        // Awaited receipts are kept while the awaiting workflow runs.
+        let pruned = prune::<MemoryDb>(&terminal_only, now, false, &mut conn).unwrap();
+        assert_eq!(pruned, Pruned::default());
+
+        let pruned = prune::<MemoryDb>(&max_age, later, true, &mut conn).unwrap();
+        assert_eq!(pruned.receipts, vec![unrelated.cid()]);
+        assert_eq!(pruned.workflows, vec![completed]);
+        assert_eq!(pruned.blocks, vec![block]);
+        assert!(MemoryDb::find_receipt_by_cid(unrelated.cid(), &mut conn).is_ok());
+
+        MemoryDb::set_workflow_status(running, workflow::Status::Completed, &mut conn).unwrap();
+
+        let max_count = Retention {
+            max_receipts_per_workflow: Some(1),
+            ..Default::default()
+        };
+        let pruned = prune::<MemoryDb>(&max_count, now, true, &mut conn).unwrap();
+        assert_eq!(pruned.receipts.len(), 1);
+        assert!([first, second].contains(&pruned.receipts[0]));
+
+        // Only the intermediate receipt is pruned.
+        let pruned = prune::<MemoryDb>(&terminal_only, now, false, &mut conn).unwrap();
+        assert_eq!(pruned.receipts, vec![first]);
+        assert!(MemoryDb::find_receipt_by_cid(first, &mut conn).is_err());
+        let (_, info) = MemoryDb::get_workflow_info(completed, &mut conn).unwrap();
+        assert_eq!(info.progress(), &vec![second]);
+
+        let pruned = prune::<MemoryDb>(&max_age, later, false, &mut conn).unwrap();
+        assert_eq!(
+            pruned.receipts.into_iter().collect::<HashSet<_>>(),
+            HashSet::from([second, unrelated.cid()])
+        );
+        assert_eq!(
+            pruned.workflows.into_iter().collect::<HashSet<_>>(),
+            HashSet::from([completed, running])
+        );
+        assert_eq!(pruned.blocks, vec![block, block]);
+        assert_eq!(MemoryDb::find_block(block, &mut conn).unwrap(), None);
+        assert!(
+            MemoryDb::select_workflows(None, None, None, None, 100, &mut conn)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn prune_by_count_keeps_receipts_shared_by_workflows() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let (instruction, dep_instruction1, dep_instruction2) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let [task, dep_task1, dep_task2] =
+            [instruction, dep_instruction1, dep_instruction2].map(|instruction| {
+                Task::new(
+                    RunInstruction::Expanded(instruction),
+                    Resources::default().into(),
+                    UcanPrf::default(),
+                )
+            });
+
+        let many = store_workflow(
+            vec![task.clone(), dep_task1.clone(), dep_task2.clone()],
+            &mut conn,
+        );
+        let shared = store_receipt(many, task.clone(), &mut conn);
+        let first = store_receipt(many, dep_task1, &mut conn);
+        let second = store_receipt(many, dep_task2, &mut conn);
+        MemoryDb::set_workflow_status(many, workflow::Status::Completed, &mut conn).unwrap();
+
+        // Workflow whose only receipt is shared with the other one.
+        let single = store_workflow(vec![task], &mut conn);
+        MemoryDb::store_workflow_receipt(single, shared, &mut conn).unwrap();
+        MemoryDb::set_workflow_status(single, workflow::Status::Completed, &mut conn).unwrap();
+
+        let max_count = Retention {
+            max_receipts_per_workflow: Some(1),
+            ..Default::default()
+        };
+        let pruned =
+            prune::<MemoryDb>(&max_count, Utc::now().naive_utc(), false, &mut conn).unwrap();
+        assert!(!pruned.receipts.is_empty());
+        assert!(pruned
+            .receipts
+            .iter()
+            .all(|receipt| [first, second].contains(receipt)));
+        assert!(MemoryDb::find_receipt_by_cid(shared, &mut conn).is_ok());
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn prune_keeps_receipts_of_workflows_stored_without_tasks() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let mut conn = db.conn().unwrap();
+
+        let (instruction, _, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+
+        let completed = store_workflow(vec![task.clone()], &mut conn);
+        let receipt = store_receipt(completed, task.clone(), &mut conn);
+        MemoryDb::set_workflow_status(completed, workflow::Status::Completed, &mut conn).unwrap();
+
+        // Running workflow sharing the task, stored without its data.
+        let running = Cid::new_v1(0x71, Code::Sha3_256.digest(b"running"));
+        MemoryDb::store_workflow(
+            workflow::Stored::new_with_resources(
+                Pointer::new(running),
+                None,
+                1,
+                IndexedResources::new(IndexMap::from([(task.instruction_cid().unwrap(), vec![])])),
+            ),
+            &mut conn,
+        )
+        .unwrap();
+        MemoryDb::set_workflow_status(running, workflow::Status::Running, &mut conn).unwrap();
+
+        let later = Utc::now().naive_utc() + Duration::from_secs(2 * 86400);
+        let max_age = Retention {
+            max_age: Some(Duration::from_secs(86400)),
+            ..Default::default()
+        };
+
+        let pruned = prune::<MemoryDb>(&max_age, later, false, &mut conn).unwrap();
+        assert!(pruned.receipts.is_empty());
+        assert_eq!(pruned.workflows, vec![completed]);
+        assert!(MemoryDb::find_receipt_by_cid(receipt, &mut conn).is_ok());
+    }
+}
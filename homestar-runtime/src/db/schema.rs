@@ -1,5 +1,14 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    blocks (workflow_cid, cid) {
+        workflow_cid -> Text,
+        cid -> Text,
+        data -> Binary,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     receipts (cid) {
         cid -> Text,
@@ -10,6 +19,15 @@ diesel::table! {
         issuer -> Nullable<Text>,
         prf -> Binary,
         version -> Text,
+        created_at -> Timestamp,
+        signature -> Nullable<Binary>,
+    }
+}
+
+diesel::table! {
+    ucans (cid) {
+        cid -> Text,
+        token -> Text,
     }
 }
 
@@ -23,6 +41,8 @@ diesel::table! {
         completed_at -> Nullable<Timestamp>,
         status -> crate::workflow::StatusMapping,
         retries -> Integer,
+        workflow -> Nullable<Binary>,
+        settings -> Nullable<Binary>,
     }
 }
 
@@ -37,7 +57,9 @@ diesel::joinable!(workflows_receipts -> receipts (receipt_cid));
 diesel::joinable!(workflows_receipts -> workflows (workflow_cid));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    blocks,
     receipts,
+    ucans,
     workflows,
     workflows_receipts,
 );
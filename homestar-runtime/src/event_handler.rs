@@ -94,6 +94,9 @@ pub(crate) struct EventHandler<DB: Database> {
     /// [tokio::sync::broadcast::Sender] for websocket workflow-related
     /// notification messages.
     ws_workflow_sender: webserver::Notifier<notifier::Message>,
+    /// [tokio::sync::broadcast::Sender] for websocket task log
+    /// notification messages.
+    ws_task_log_sender: webserver::Notifier<notifier::Message>,
     /// [libp2p::Multiaddr] addresses to dial.
     node_addresses: Vec<libp2p::Multiaddr>,
     /// [libp2p::Multiaddr] externally reachable addresses to announce to the network.
@@ -104,6 +107,8 @@ pub(crate) struct EventHandler<DB: Database> {
     poll_cache_interval: Duration,
     /// Bootstrap configuration.
     bootstrap: Bootstrap,
+    /// Policy for receipts received from the network.
+    receipt_policy: settings::ReceiptPolicy,
 }
 
 /// Event loop handler for libp2p network events and commands.
@@ -145,6 +150,8 @@ pub(crate) struct EventHandler<DB: Database> {
     poll_cache_interval: Duration,
     /// Bootstrap configuration.
     bootstrap: Bootstrap,
+    /// Policy for receipts received from the network.
+    receipt_policy: settings::ReceiptPolicy,
 }
 
 /// Rendezvous protocol configurations and state
@@ -184,6 +191,7 @@ where
         settings: &settings::Network,
         ws_evt_sender: webserver::Notifier<notifier::Message>,
         ws_workflow_sender: webserver::Notifier<notifier::Message>,
+        ws_task_log_sender: webserver::Notifier<notifier::Message>,
     ) -> Self {
         let (sender, receiver) = Self::setup_channel(settings);
         let sender = Arc::new(sender);
@@ -215,6 +223,7 @@ where
             pubsub_enabled: settings.libp2p.pubsub.enable,
             ws_evt_sender,
             ws_workflow_sender,
+            ws_task_log_sender,
             node_addresses: settings.libp2p.node_addresses.clone(),
             announce_addresses: settings.libp2p.announce_addresses.clone(),
             external_address_limit: settings.libp2p.max_announce_addresses,
@@ -222,6 +231,7 @@ where
             bootstrap: Bootstrap {
                 interval: settings.libp2p.bootstrap_interval,
             },
+            receipt_policy: settings.receipt_policy().clone(),
         }
     }
 
@@ -267,6 +277,7 @@ where
             bootstrap: Bootstrap {
                 interval: settings.libp2p.bootstrap_interval,
             },
+            receipt_policy: settings.receipt_policy().clone(),
         }
     }
 
@@ -287,6 +298,14 @@ where
         self.ws_workflow_sender.clone()
     }
 
+    /// [tokio::sync::broadcast::Sender] for sending guest log records of
+    /// tasks through the WebSocket server to subscribers.
+    #[cfg(feature = "websocket-notify")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "websocket-notify")))]
+    pub(crate) fn ws_task_log_sender(&self) -> webserver::Notifier<notifier::Message> {
+        self.ws_task_log_sender.clone()
+    }
+
     /// [tokio::sync::broadcast::Sender] for sending event-related messages
     /// through the WebSocket server to subscribers.
     #[cfg(feature = "websocket-notify")]
@@ -24,6 +24,8 @@ use anyhow::Result;
 #[cfg(feature = "websocket-notify")]
 use homestar_invocation::Pointer;
 use homestar_invocation::Receipt as InvocationReceipt;
+#[cfg(feature = "websocket-notify")]
+use homestar_wasm::wasmtime::logs::LogRecord;
 use libipld::{Cid, Ipld};
 use libp2p::{
     kad::{Quorum, Record, RecordKey},
@@ -68,6 +70,19 @@ pub(crate) struct Replay {
     pub(crate) metadata: Option<Ipld>,
 }
 
+/// A guest log record written while running a task, for notifications.
+#[cfg(feature = "websocket-notify")]
+#[cfg_attr(docsrs, doc(cfg(feature = "websocket-notify")))]
+#[derive(Debug, Clone)]
+pub(crate) struct TaskLog {
+    /// Cid of the workflow the task belongs to.
+    pub(crate) workflow: Cid,
+    /// Cid of the instruction being run.
+    pub(crate) instruction: Cid,
+    /// The log record written.
+    pub(crate) record: LogRecord,
+}
+
 /// A structured query for finding a [Record] in the DHT and
 /// returning to a [P2PSender].
 #[derive(Debug, Clone)]
@@ -102,6 +117,10 @@ pub(crate) enum Event {
     #[cfg(feature = "websocket-notify")]
     #[cfg_attr(docsrs, doc(cfg(feature = "websocket-notify")))]
     ReplayReceipts(Replay),
+    /// Guest log record written while running a task.
+    #[cfg(feature = "websocket-notify")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "websocket-notify")))]
+    TaskLog(TaskLog),
     /// General shutdown event.
     Shutdown(AsyncChannelSender<()>),
     /// Find a [Record] in the DHT, e.g. a [Receipt].
@@ -172,6 +191,18 @@ impl Event {
             Event::RemoveRecord(record) => record.remove(event_handler).await,
             #[cfg(feature = "websocket-notify")]
             #[cfg_attr(docsrs, doc(cfg(feature = "websocket-notify")))]
+            Event::TaskLog(TaskLog {
+                workflow,
+                instruction,
+                record,
+            }) => notification::emit_task_log(
+                event_handler.ws_task_log_sender(),
+                workflow,
+                instruction,
+                record,
+            ),
+            #[cfg(feature = "websocket-notify")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "websocket-notify")))]
             Event::StoredRecord(event) => match event {
                 FoundEvent::Receipt(ReceiptEvent { peer_id, receipt }) => {
                     notification::emit_network_event(
@@ -440,7 +471,9 @@ impl Captured {
             Quorum::One
         };
 
-        if let Ok(receipt_bytes) = Receipt::invocation_capsule(&invocation_receipt) {
+        if let Ok(receipt_bytes) =
+            Receipt::invocation_capsule(&invocation_receipt, receipt.signature())
+        {
             event_handler
                 .swarm
                 .behaviour_mut()
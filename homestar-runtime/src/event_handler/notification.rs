@@ -6,24 +6,28 @@ use crate::{
         SUBSCRIBE_NETWORK_EVENTS_ENDPOINT,
     },
     receipt::metadata::{WORKFLOW_KEY, WORKFLOW_NAME_KEY},
-    Receipt,
+    workflow, Receipt,
 };
 use homestar_invocation::{ipld::DagJson, Receipt as InvocationReceipt};
-use libipld::Ipld;
+use homestar_wasm::wasmtime::logs::LogRecord;
+use libipld::{json::DagJsonCodec, prelude::Codec, Cid, Ipld};
+use std::collections::BTreeMap;
 use tracing::{debug, warn};
 
 pub(crate) mod network;
 pub(crate) mod receipt;
+pub(crate) mod task_log;
 pub(crate) use network::{
     ConnectionClosed, ConnectionEstablished, DiscoverServedRendezvous, DiscoveredMdns,
     DiscoveredRendezvous, GotReceiptDht, GotWorkflowInfoDht, IncomingConnectionError,
     NetworkNotification, NewListenAddr, OutgoingConnectionError, PeerRegisteredRendezvous,
     PublishedReceiptPubsub, PutReceiptDht, PutWorkflowInfoDht, ReceiptQuorumFailureDht,
     ReceiptQuorumSuccessDht, ReceivedReceiptPubsub, ReceivedWorkflowInfo, RegisteredRendezvous,
-    SentWorkflowInfo, WorkflowInfoQuorumFailureDht, WorkflowInfoQuorumSuccessDht,
+    RejectedReceipt, SentWorkflowInfo, WorkflowInfoQuorumFailureDht, WorkflowInfoQuorumSuccessDht,
     WorkflowInfoSource,
 };
 pub(crate) use receipt::ReceiptNotification;
+pub(crate) use task_log::TaskLogNotification;
 
 /// Send receipt notification as bytes.
 pub(crate) fn emit_receipt(
@@ -67,6 +71,70 @@ pub(crate) fn emit_receipt(
     }
 }
 
+/// Send workflow status notification as bytes, e.g. on cancellation.
+pub(crate) fn emit_workflow_status(
+    notifier: Notifier<notifier::Message>,
+    workflow_cid: Cid,
+    status: workflow::Status,
+) {
+    let notification = Ipld::Map(BTreeMap::from([(
+        WORKFLOW_KEY.into(),
+        Ipld::Map(BTreeMap::from([
+            ("cid".into(), Ipld::Link(workflow_cid)),
+            ("status".into(), Ipld::String(status.to_string())),
+        ])),
+    )]));
+
+    if let Ok(json) = DagJsonCodec.encode(&notification) {
+        debug!(
+            subject = "notification.workflow",
+            category = "notification",
+            cid = workflow_cid.to_string(),
+            status = status.to_string(),
+            "emitting workflow status to WebSocket"
+        );
+        let header = Header::new(SubscriptionTyp::Cid(workflow_cid), None);
+        let _ = notifier.notify(Message::new(header, json));
+    } else {
+        warn!(
+            subject = "notification.err",
+            category = "notification",
+            cid = workflow_cid.to_string(),
+            "unable to serialize workflow notification as bytes"
+        );
+    }
+}
+
+/// Send guest log record notification as bytes, to subscribers of the
+/// task's logs.
+pub(crate) fn emit_task_log(
+    notifier: Notifier<notifier::Message>,
+    workflow_cid: Cid,
+    instruction_cid: Cid,
+    record: LogRecord,
+) {
+    let notification = TaskLogNotification::with(workflow_cid, instruction_cid, record);
+
+    if let Ok(json) = notification.to_json() {
+        let header = Header::new(
+            SubscriptionTyp::TaskLogs {
+                workflow: workflow_cid,
+                instruction: instruction_cid,
+            },
+            None,
+        );
+        let _ = notifier.notify(Message::new(header, json));
+    } else {
+        warn!(
+            subject = "notification.err",
+            category = "notification",
+            cid = workflow_cid.to_string(),
+            instruction_cid = instruction_cid.to_string(),
+            "unable to serialize task log notification as bytes"
+        );
+    }
+}
+
 /// Send network event notification as bytes.
 pub(crate) fn emit_network_event(
     notifier: Notifier<notifier::Message>,
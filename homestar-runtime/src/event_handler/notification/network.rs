@@ -13,6 +13,7 @@ pub(crate) mod connection;
 pub(crate) mod dht;
 pub(crate) mod mdns;
 pub(crate) mod pubsub;
+pub(crate) mod receipt;
 pub(crate) mod rendezvous;
 pub(crate) mod req_resp;
 pub(crate) use connection::{
@@ -25,6 +26,7 @@ pub(crate) use dht::{
 };
 pub(crate) use mdns::DiscoveredMdns;
 pub(crate) use pubsub::{PublishedReceiptPubsub, ReceivedReceiptPubsub};
+pub(crate) use receipt::RejectedReceipt;
 pub(crate) use rendezvous::{
     DiscoverServedRendezvous, DiscoveredRendezvous, PeerRegisteredRendezvous, RegisteredRendezvous,
 };
@@ -100,6 +102,9 @@ pub enum NetworkNotification {
     /// Received workflow info notification.
     #[schemars(rename = "received_workflow_info")]
     ReceivedWorkflowInfo(ReceivedWorkflowInfo),
+    /// Rejected receipt notification.
+    #[schemars(rename = "rejected_receipt")]
+    RejectedReceipt(RejectedReceipt),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -153,6 +158,7 @@ impl fmt::Display for NetworkNotification {
             NetworkNotification::ReceivedWorkflowInfo(_) => {
                 write!(f, "received_workflow_info")
             }
+            NetworkNotification::RejectedReceipt(_) => write!(f, "rejected_receipt"),
         }
     }
 }
@@ -240,6 +246,9 @@ impl From<NetworkNotification> for Ipld {
                 "received_workflow_info".into(),
                 n.into(),
             )])),
+            NetworkNotification::RejectedReceipt(n) => {
+                Ipld::Map(BTreeMap::from([("rejected_receipt".into(), n.into())]))
+            }
         }
     }
 }
@@ -322,6 +331,9 @@ impl TryFrom<Ipld> for NetworkNotification {
                 "received_workflow_info" => Ok(NetworkNotification::ReceivedWorkflowInfo(
                     ReceivedWorkflowInfo::try_from(val.to_owned())?,
                 )),
+                "rejected_receipt" => Ok(NetworkNotification::RejectedReceipt(
+                    RejectedReceipt::try_from(val.to_owned())?,
+                )),
                 _ => Err(anyhow!("Unknown network notification tag type")),
             }
         } else {
@@ -343,6 +355,8 @@ mod test {
     use rand::thread_rng;
     use std::str::FromStr;
 
+    const ISSUER: &str = "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp";
+
     #[derive(Clone, Debug)]
     struct Fixtures {
         address: Multiaddr,
@@ -484,6 +498,14 @@ mod test {
             progress,
             progress_count,
         );
+        let rejected_receipt = RejectedReceipt::new(
+            "pubsub",
+            Some(peer_id),
+            cid,
+            ran.to_string(),
+            Some(ISSUER.to_string()),
+            "receipt is not signed".to_string(),
+        );
 
         vec![
             (
@@ -574,6 +596,10 @@ mod test {
                 received_workflow_info.timestamp().to_owned(),
                 NetworkNotification::ReceivedWorkflowInfo(received_workflow_info),
             ),
+            (
+                rejected_receipt.timestamp().to_owned(),
+                NetworkNotification::RejectedReceipt(rejected_receipt),
+            ),
         ]
     }
 
@@ -796,6 +822,20 @@ mod test {
                 );
                 assert_eq!(n.progress_count(), &progress_count);
             }
+            NetworkNotification::RejectedReceipt(n) => {
+                assert_eq!(n.timestamp(), timestamp);
+                assert_eq!(n.source(), "pubsub");
+                assert_eq!(
+                    n.publisher()
+                        .as_ref()
+                        .map(|p| PeerId::from_str(&p).unwrap()),
+                    Some(peer_id)
+                );
+                assert_eq!(Cid::from_str(n.cid()).unwrap(), cid);
+                assert_eq!(Cid::from_str(n.ran()).unwrap(), ran);
+                assert_eq!(n.issuer().as_deref(), Some(ISSUER));
+                assert_eq!(n.reason(), "receipt is not signed");
+            }
         }
     }
 
@@ -0,0 +1,152 @@
+//! Notification types for receipts received from the network and rejected
+//! by the node's receipt policy.
+
+use anyhow::anyhow;
+use chrono::prelude::Utc;
+use derive_getters::Getters;
+use homestar_invocation::ipld::DagJson;
+use libipld::{serde::from_ipld, Cid, Ipld};
+use libp2p::PeerId;
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+
+const CID_KEY: &str = "cid";
+const ISSUER_KEY: &str = "issuer";
+const PUBLISHER_KEY: &str = "publisher";
+const RAN_KEY: &str = "ran";
+const REASON_KEY: &str = "reason";
+const SOURCE_KEY: &str = "source";
+const TIMESTAMP_KEY: &str = "timestamp";
+
+#[derive(Debug, Clone, Getters, JsonSchema)]
+#[schemars(rename = "rejected_receipt")]
+pub struct RejectedReceipt {
+    timestamp: i64,
+    #[schemars(description = "Source of the receipt, pubsub or dht")]
+    source: String,
+    #[schemars(description = "Receipt publisher peer ID")]
+    publisher: Option<String>,
+    #[schemars(description = "Receipt CID")]
+    cid: String,
+    #[schemars(description = "Ran receipt CID")]
+    ran: String,
+    #[schemars(description = "Receipt issuer DID")]
+    issuer: Option<String>,
+    #[schemars(description = "Reason the receipt was rejected")]
+    reason: String,
+}
+
+impl RejectedReceipt {
+    pub(crate) fn new(
+        source: &str,
+        publisher: Option<PeerId>,
+        cid: Cid,
+        ran: String,
+        issuer: Option<String>,
+        reason: String,
+    ) -> RejectedReceipt {
+        RejectedReceipt {
+            timestamp: Utc::now().timestamp_millis(),
+            source: source.to_string(),
+            publisher: publisher.map(|p| p.to_string()),
+            cid: cid.to_string(),
+            ran,
+            issuer,
+            reason,
+        }
+    }
+}
+
+impl DagJson for RejectedReceipt {}
+
+impl From<RejectedReceipt> for Ipld {
+    fn from(notification: RejectedReceipt) -> Self {
+        let map: BTreeMap<String, Ipld> = BTreeMap::from([
+            (TIMESTAMP_KEY.into(), notification.timestamp.into()),
+            (SOURCE_KEY.into(), notification.source.into()),
+            (
+                PUBLISHER_KEY.into(),
+                notification
+                    .publisher
+                    .map(|peer_id| peer_id.into())
+                    .unwrap_or(Ipld::Null),
+            ),
+            (CID_KEY.into(), notification.cid.into()),
+            (RAN_KEY.into(), notification.ran.into()),
+            (
+                ISSUER_KEY.into(),
+                notification
+                    .issuer
+                    .map(|issuer| issuer.into())
+                    .unwrap_or(Ipld::Null),
+            ),
+            (REASON_KEY.into(), notification.reason.into()),
+        ]);
+
+        Ipld::Map(map)
+    }
+}
+
+impl TryFrom<Ipld> for RejectedReceipt {
+    type Error = anyhow::Error;
+
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        let map = from_ipld::<BTreeMap<String, Ipld>>(ipld)?;
+
+        let timestamp = from_ipld(
+            map.get(TIMESTAMP_KEY)
+                .ok_or_else(|| anyhow!("missing {TIMESTAMP_KEY}"))?
+                .to_owned(),
+        )?;
+
+        let source = from_ipld(
+            map.get(SOURCE_KEY)
+                .ok_or_else(|| anyhow!("missing {SOURCE_KEY}"))?
+                .to_owned(),
+        )?;
+
+        let publisher = map
+            .get(PUBLISHER_KEY)
+            .and_then(|ipld| match ipld {
+                Ipld::Null => None,
+                ipld => Some(ipld),
+            })
+            .and_then(|ipld| from_ipld(ipld.to_owned()).ok());
+
+        let cid = from_ipld(
+            map.get(CID_KEY)
+                .ok_or_else(|| anyhow!("missing {CID_KEY}"))?
+                .to_owned(),
+        )?;
+
+        let ran = from_ipld(
+            map.get(RAN_KEY)
+                .ok_or_else(|| anyhow!("missing {RAN_KEY}"))?
+                .to_owned(),
+        )?;
+
+        let issuer = map
+            .get(ISSUER_KEY)
+            .and_then(|ipld| match ipld {
+                Ipld::Null => None,
+                ipld => Some(ipld),
+            })
+            .and_then(|ipld| from_ipld(ipld.to_owned()).ok());
+
+        let reason = from_ipld(
+            map.get(REASON_KEY)
+                .ok_or_else(|| anyhow!("missing {REASON_KEY}"))?
+                .to_owned(),
+        )?;
+
+        Ok(RejectedReceipt {
+            timestamp,
+            source,
+            publisher,
+            cid,
+            ran,
+            issuer,
+            reason,
+        })
+    }
+}
@@ -0,0 +1,66 @@
+//! Notification of guest log records written while running a task.
+
+use homestar_wasm::wasmtime::logs::LogRecord;
+use libipld::Cid;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A guest log record, written while running a task, that is sent out for
+/// websocket notifications.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "task_log_notification")]
+pub struct TaskLogNotification {
+    #[schemars(description = "Cid of the workflow")]
+    pub(crate) workflow: String,
+    #[schemars(description = "Cid of the instruction run")]
+    pub(crate) instruction: String,
+    #[schemars(description = "Log level: trace, debug, info, warn, error or critical")]
+    pub(crate) level: String,
+    #[schemars(description = "Context given by the guest, or print")]
+    pub(crate) context: String,
+    #[schemars(description = "Log message")]
+    pub(crate) message: String,
+}
+
+impl TaskLogNotification {
+    /// Create a new [TaskLogNotification].
+    pub(crate) fn with(workflow: Cid, instruction: Cid, record: LogRecord) -> Self {
+        Self {
+            workflow: workflow.to_string(),
+            instruction: instruction.to_string(),
+            level: record.level.to_string(),
+            context: record.context,
+            message: record.message,
+        }
+    }
+
+    /// Serialize the notification as JSON bytes.
+    pub(crate) fn to_json(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homestar_wasm::wasmtime::logs::LogLevel;
+
+    #[test]
+    fn task_log_to_json() {
+        let notification = TaskLogNotification::with(
+            Cid::default(),
+            Cid::default(),
+            LogRecord {
+                level: LogLevel::Warn,
+                context: "guest".to_string(),
+                message: "careful".to_string(),
+            },
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&notification.to_json().unwrap()).unwrap();
+        assert_eq!(json["level"], "warn");
+        assert_eq!(json["message"], "careful");
+        assert_eq!(json["workflow"], Cid::default().to_string());
+    }
+}
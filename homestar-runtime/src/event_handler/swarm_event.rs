@@ -12,12 +12,14 @@ use crate::{
         Event, Handler, RequestResponseError,
     },
     libp2p::multiaddr::MultiaddrExt,
+    metrics,
     network::{
         pubsub,
         swarm::{
             CapsuleTag, ComposedEvent, PeerDiscoveryInfo, RequestResponseKey, HOMESTAR_PROTOCOL_VER,
         },
     },
+    receipt::verifier,
     workflow,
     workflow::WORKFLOW_TAG,
     Db, Receipt,
@@ -522,6 +524,15 @@ async fn handle_swarm_event<DB: Database>(
                             receipt.cid()
                         );
 
+                        if !verify_receipt(
+                            event_handler,
+                            &receipt,
+                            "pubsub",
+                            Some(propagation_source),
+                        ) {
+                            return;
+                        }
+
                         // Store gossiped receipt.
                         let _ = event_handler
                             .db
@@ -631,6 +642,17 @@ async fn handle_swarm_event<DB: Database>(
 
                             match decoded_record {
                                 DecodedRecord::Receipt(ReceiptRecord { peer_id, receipt }) => {
+                                    if !verify_receipt(event_handler, &receipt, "dht", peer_id) {
+                                        if let Some(sender) = sender {
+                                            let _ = sender
+                                                .send_async(ResponseEvent::Found(Err(anyhow!(
+                                                    "found receipt record rejected by receipt policy"
+                                                ))))
+                                                .await;
+                                        }
+                                        return;
+                                    }
+
                                     let response_event = ResponseEvent::Found(Ok(
                                         FoundEvent::Receipt(ReceiptEvent {
                                             peer_id,
@@ -1395,3 +1417,44 @@ async fn handle_swarm_event<DB: Database>(
                     "uncaught event"),
     }
 }
+
+/// Verify a receipt received from the network, over `source`, against the
+/// node's receipt policy, counting and notifying of rejections.
+fn verify_receipt<DB: Database>(
+    event_handler: &EventHandler<DB>,
+    receipt: &Receipt,
+    source: &'static str,
+    publisher: Option<PeerId>,
+) -> bool {
+    match verifier::verify(receipt, &event_handler.receipt_policy) {
+        Ok(()) => true,
+        Err(rejection) => {
+            warn!(subject = "libp2p.receipt.rejected",
+                  category = "handle_swarm_event",
+                  cid = receipt.cid().to_string(),
+                  source = source,
+                  err=?rejection,
+                  "rejected receipt received from the network");
+
+            metrics::receipt::record_rejection(source, &rejection);
+
+            #[cfg(feature = "websocket-notify")]
+            notification::emit_network_event(
+                event_handler.ws_evt_sender(),
+                NetworkNotification::RejectedReceipt(notification::RejectedReceipt::new(
+                    source,
+                    publisher,
+                    receipt.cid(),
+                    receipt.ran(),
+                    receipt.issuer().map(|issuer| issuer.to_string()),
+                    rejection.to_string(),
+                )),
+            );
+
+            #[cfg(not(feature = "websocket-notify"))]
+            let _ = publisher;
+
+            false
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     event_handler::RequestResponseError,
-    receipt::{RECEIPT_TAG, VERSION_KEY},
+    receipt::{RECEIPT_TAG, SIGNATURE_KEY, VERSION_KEY},
     workflow,
     workflow::WORKFLOW_TAG,
     Receipt,
@@ -68,8 +68,13 @@ pub(crate) fn decode_capsule(
                 if rest.remove(VERSION_KEY)
                     == Some(Ipld::String(consts::INVOCATION_VERSION.to_string()))
                 {
+                    let signature = rest.remove(SIGNATURE_KEY);
                     let invocation_receipt = InvocationReceipt::try_from(Ipld::Map(rest))?;
-                    let receipt = Receipt::try_with(Pointer::new(key_cid), &invocation_receipt)?;
+                    let mut receipt =
+                        Receipt::try_with(Pointer::new(key_cid), &invocation_receipt)?;
+                    if let Some(Ipld::Bytes(signature)) = signature {
+                        receipt.set_signature(signature);
+                    }
 
                     Ok(DecodedRecord::Receipt(ReceiptRecord { peer_id, receipt }))
                 } else {
@@ -116,7 +121,7 @@ mod test {
     fn found_receipt_record() {
         let (invocation_receipt, receipt) = test_utils::receipt::receipts();
         let instruction_bytes = receipt.instruction_cid_as_bytes();
-        let bytes = Receipt::invocation_capsule(&invocation_receipt).unwrap();
+        let bytes = Receipt::invocation_capsule(&invocation_receipt, None).unwrap();
         let record = Record::new(instruction_bytes, bytes);
         let peer_record = PeerRecord {
             record,
@@ -129,6 +134,25 @@ mod test {
         }
     }
 
+    #[test]
+    fn found_signed_receipt_record() {
+        let (invocation_receipt, mut receipt) = test_utils::receipt::receipts();
+        receipt.set_signature(vec![1, 2, 3]);
+        let instruction_bytes = receipt.instruction_cid_as_bytes();
+        let bytes = Receipt::invocation_capsule(&invocation_receipt, receipt.signature()).unwrap();
+        let record = Record::new(instruction_bytes, bytes);
+        let peer_record = PeerRecord {
+            record,
+            peer: Some(PeerId::random()),
+        };
+        if let DecodedRecord::Receipt(decoded) = peer_record.found_record().unwrap() {
+            assert_eq!(decoded.receipt, receipt);
+            assert_eq!(decoded.receipt.signature(), Some([1, 2, 3].as_slice()));
+        } else {
+            panic!("Incorrect record type")
+        }
+    }
+
     #[test]
     fn found_workflow_record() {
         let config = Resources::default();
@@ -74,7 +74,9 @@ pub(crate) mod libp2p;
 pub use logger::*;
 pub(crate) mod metrics;
 #[cfg(feature = "websocket-notify")]
-pub use event_handler::notification::{network::NetworkNotification, receipt::ReceiptNotification};
+pub use event_handler::notification::{
+    network::NetworkNotification, receipt::ReceiptNotification, task_log::TaskLogNotification,
+};
 #[allow(unused_imports)]
 pub(crate) use event_handler::EventHandler;
 pub use network::webserver::PrometheusData;
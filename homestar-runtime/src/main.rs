@@ -1,6 +1,8 @@
 use clap::Parser;
 use homestar_runtime::{
-    cli::{handle_init_command, Cli, Command, ConsoleTable},
+    cli::{
+        handle_db_command, handle_init_command, handle_validate_command, Cli, Command, ConsoleTable,
+    },
     daemon,
     db::Database,
     runner::response,
@@ -57,6 +59,8 @@ fn main() -> Result<()> {
             info!("starting Homestar runtime...");
             Runner::start(settings, db).expect("Failed to start runtime")
         }
+        Command::Db { command } => handle_db_command(command)?,
+        Command::Validate { graph, workflow } => handle_validate_command(workflow, graph)?,
         Command::Info => {
             let response = response::Info::default();
             response
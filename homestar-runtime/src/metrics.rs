@@ -10,6 +10,14 @@ mod exporter;
 #[cfg(feature = "monitoring")]
 #[cfg_attr(docsrs, doc(cfg(feature = "monitoring")))]
 mod node;
+pub(crate) mod receipt;
+pub(crate) mod wasm;
+
+/// Denials and rejections counted by reason, e.g. [receipt::REJECTED].
+pub(crate) trait Reason {
+    /// Short label of the reason, used as a metric label.
+    fn reason(&self) -> &'static str;
+}
 
 /// Start metrics collection and setup scrape endpoint.
 /// Also, spawn a task to collect process metrics at a regular interval.
@@ -2,7 +2,10 @@
 
 #[cfg(feature = "monitoring")]
 use crate::metrics::node;
-use crate::settings;
+use crate::{
+    metrics::{receipt, wasm},
+    settings,
+};
 use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 use metrics_util::layers::{PrefixLayer, Stack};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
@@ -30,6 +33,19 @@ fn setup_metrics_recorder_inner(port: u16) -> anyhow::Result<PrometheusHandle> {
         0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
     ];
 
+    const EXPONENTIAL_FUEL: &[f64] = &[1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12];
+    const EXPONENTIAL_BYTES: &[f64] = &[
+        65536.0,
+        262144.0,
+        1048576.0,
+        4194304.0,
+        16777216.0,
+        67108864.0,
+        268435456.0,
+        1073741824.0,
+        4294967296.0,
+    ];
+
     let socket = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
 
     let (recorder, exporter) = PrometheusBuilder::new()
@@ -37,6 +53,14 @@ fn setup_metrics_recorder_inner(port: u16) -> anyhow::Result<PrometheusHandle> {
             Matcher::Suffix("_duration_seconds".to_string()),
             EXPONENTIAL_SECONDS,
         )?
+        .set_buckets_for_metric(
+            Matcher::Full(format!("homestar_{}", wasm::FUEL_USED)),
+            EXPONENTIAL_FUEL,
+        )?
+        .set_buckets_for_metric(
+            Matcher::Full(format!("homestar_{}", wasm::PEAK_MEMORY)),
+            EXPONENTIAL_BYTES,
+        )?
         .with_http_listener(socket)
         .build()
         .expect("failed to install recorder/exporter");
@@ -51,6 +75,8 @@ fn setup_metrics_recorder_inner(port: u16) -> anyhow::Result<PrometheusHandle> {
 
     #[cfg(feature = "monitoring")]
     node::describe();
+    receipt::describe();
+    wasm::describe();
 
     Ok(hdl)
 }
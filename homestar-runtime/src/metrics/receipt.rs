@@ -0,0 +1,27 @@
+//! Receipt metrics, i.e. receipts from the network rejected by the node's
+//! receipt policy.
+
+use super::Reason;
+use crate::receipt::verifier::Rejection;
+use metrics::{describe_counter, increment_counter, Unit};
+
+/// Receipts received from the network and rejected.
+pub(crate) const REJECTED: &str = "receipts_rejected";
+
+/// Describe counters for receipt metrics.
+pub(crate) fn describe() {
+    describe_counter!(
+        REJECTED,
+        Unit::Count,
+        "Receipts received from the network and rejected, by source and reason."
+    );
+}
+
+/// Count a [Rejection] of a receipt received over `source`, e.g. `pubsub`.
+pub(crate) fn record_rejection(source: &'static str, rejection: &Rejection) {
+    increment_counter!(
+        REJECTED,
+        "source" => source,
+        "reason" => rejection.reason()
+    );
+}
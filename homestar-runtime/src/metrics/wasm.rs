@@ -0,0 +1,59 @@
+//! Wasm task metrics, i.e. resources used per function and module, and
+//! modules denied by the node's module policy.
+
+use super::Reason;
+use crate::tasks::{ModuleDenial, Usage};
+use libipld::Cid;
+use metrics::{describe_counter, describe_histogram, histogram, increment_counter, Unit};
+
+/// Fuel consumed by a Wasm task.
+pub(crate) const FUEL_USED: &str = "wasm_fuel_used";
+
+/// Peak memory used by a Wasm task.
+pub(crate) const PEAK_MEMORY: &str = "wasm_peak_memory_bytes";
+
+/// Wall-clock duration of a Wasm task.
+pub(crate) const DURATION: &str = "wasm_execution_duration_seconds";
+
+/// Wasm modules denied by the node's module policy.
+pub(crate) const MODULES_DENIED: &str = "wasm_modules_denied";
+
+/// Describe histograms and counters for Wasm task metrics.
+pub(crate) fn describe() {
+    describe_histogram!(
+        FUEL_USED,
+        Unit::Count,
+        "Fuel consumed by Wasm tasks, by function and module."
+    );
+    describe_histogram!(
+        PEAK_MEMORY,
+        Unit::Bytes,
+        "Peak memory used by Wasm tasks, by function and module."
+    );
+    describe_histogram!(
+        DURATION,
+        Unit::Seconds,
+        "Execution time of Wasm tasks, by function and module."
+    );
+    describe_counter!(
+        MODULES_DENIED,
+        Unit::Count,
+        "Wasm modules denied by the node's module policy, by reason."
+    );
+}
+
+/// Record [Usage] of a Wasm task, labelled by function and module Cid.
+pub(crate) fn record_usage(usage: &Usage, fun: &str, module: Cid) {
+    let labels = [
+        ("function", fun.to_string()),
+        ("module", module.to_string()),
+    ];
+    histogram!(FUEL_USED, usage.fuel_used as f64, &labels);
+    histogram!(PEAK_MEMORY, usage.peak_memory as f64, &labels);
+    histogram!(DURATION, usage.duration.as_secs_f64(), &labels);
+}
+
+/// Count a [ModuleDenial] of a Wasm module.
+pub(crate) fn record_module_denial(denial: &ModuleDenial) {
+    increment_counter!(MODULES_DENIED, "reason" => denial.reason());
+}
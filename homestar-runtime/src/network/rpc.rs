@@ -3,10 +3,11 @@
 use crate::{
     channel::{AsyncChannel, AsyncChannelReceiver, AsyncChannelSender},
     runner::{self, file::ReadWorkflow, response, RpcSender},
-    settings,
+    settings, workflow,
 };
 use faststr::FastStr;
 use futures::{future, StreamExt};
+use libipld::Cid;
 use std::{io, net::SocketAddr, sync::Arc, time::Duration};
 use stream_cancel::Valved;
 use tarpc::{
@@ -53,6 +54,40 @@ pub(crate) enum ServerMessage {
     NodeInfo,
     /// Acknowledgement of the node's identity/info.
     NodeInfoAck(response::AckNodeInfo),
+    /// Message sent to the [Runner] to list workflows matching a
+    /// [workflow::Filter].
+    ///
+    /// [Runner]: crate::Runner
+    ListWorkflows(workflow::Filter),
+    /// Acknowledgement of listed workflows.
+    ListWorkflowsAck(response::AckWorkflowList),
+    /// Message sent to the [Runner] to show a workflow, given its Cid or
+    /// local name.
+    ///
+    /// [Runner]: crate::Runner
+    ShowWorkflow(String),
+    /// Acknowledgement of a shown workflow.
+    ShowWorkflowAck(Box<response::AckWorkflowDetail>),
+    /// Message sent to the [Runner] to list the receipts of a workflow.
+    ///
+    /// [Runner]: crate::Runner
+    WorkflowReceipts(Cid),
+    /// Acknowledgement of a workflow's receipts.
+    WorkflowReceiptsAck(response::AckWorkflowReceipts),
+    /// Message sent to the [Runner] to cancel a running workflow, given its
+    /// Cid or local name.
+    ///
+    /// [Runner]: crate::Runner
+    Cancel(String),
+    /// Acknowledgement of a cancelled workflow.
+    CancelAck(response::AckCancel),
+    /// Message sent to the [Runner] to describe the exported functions of a
+    /// Wasm module, given its Cid.
+    ///
+    /// [Runner]: crate::Runner
+    DescribeModule(Cid),
+    /// Acknowledgement of a described module.
+    DescribeModuleAck(response::AckModule),
     /// For skipping server messages.
     Skip,
 }
@@ -71,6 +106,16 @@ pub(crate) trait Interface {
     async fn stop() -> Result<(), Error>;
     /// Identify the node.
     async fn node_info() -> Result<response::AckNodeInfo, Error>;
+    /// Cancel a running workflow, given its Cid or local name.
+    async fn cancel(workflow: String) -> Result<response::AckCancel, Error>;
+    /// List workflows matching a filter.
+    async fn list_workflows(filter: workflow::Filter) -> Result<response::AckWorkflowList, Error>;
+    /// Show a workflow, given its Cid or local name.
+    async fn show_workflow(workflow: String) -> Result<Box<response::AckWorkflowDetail>, Error>;
+    /// List the receipts of a workflow.
+    async fn workflow_receipts(cid: Cid) -> Result<response::AckWorkflowReceipts, Error>;
+    /// Describe the exported functions of a Wasm module, given its Cid.
+    async fn describe_module(cid: Cid) -> Result<response::AckModule, Error>;
 }
 
 /// RPC server state information.
@@ -117,6 +162,35 @@ impl ServerHandler {
             timeout,
         }
     }
+
+    /// Send a message to the [Runner] and await its reply, up to the
+    /// server timeout.
+    ///
+    /// [Runner]: crate::Runner
+    async fn request(&self, msg: ServerMessage) -> Result<ServerMessage, Error> {
+        let (tx, rx) = AsyncChannel::oneshot();
+        self.runner_sender
+            .send_async((msg, Some(tx)))
+            .await
+            .map_err(|e| Error::FailureToSendOnChannel(e.to_string()))?;
+
+        let now = time::Instant::now();
+        select! {
+            Ok(msg) = rx.recv_async() => {
+                match msg {
+                    ServerMessage::RunErr(err) => Err(Error::FromRunner(err.to_string())),
+                    msg => Ok(msg),
+                }
+            },
+            _ = time::sleep_until(now + self.timeout) => {
+                let s = format!("server timeout of {} ms reached", self.timeout.as_millis());
+                info!(subject = "rpc.timeout",
+                      category = "rpc",
+                      "{s}");
+                Err(Error::FailureToReceiveOnChannel(s))
+            }
+        }
+    }
 }
 
 #[tarpc::server]
@@ -190,6 +264,57 @@ impl Interface for ServerHandler {
             }
         }
     }
+
+    async fn cancel(
+        self,
+        _: context::Context,
+        workflow: String,
+    ) -> Result<response::AckCancel, Error> {
+        match self.request(ServerMessage::Cancel(workflow)).await? {
+            ServerMessage::CancelAck(response) => Ok(response),
+            _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+        }
+    }
+    async fn list_workflows(
+        self,
+        _: context::Context,
+        filter: workflow::Filter,
+    ) -> Result<response::AckWorkflowList, Error> {
+        match self.request(ServerMessage::ListWorkflows(filter)).await? {
+            ServerMessage::ListWorkflowsAck(response) => Ok(response),
+            _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+        }
+    }
+    async fn show_workflow(
+        self,
+        _: context::Context,
+        workflow: String,
+    ) -> Result<Box<response::AckWorkflowDetail>, Error> {
+        match self.request(ServerMessage::ShowWorkflow(workflow)).await? {
+            ServerMessage::ShowWorkflowAck(response) => Ok(response),
+            _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+        }
+    }
+    async fn workflow_receipts(
+        self,
+        _: context::Context,
+        cid: Cid,
+    ) -> Result<response::AckWorkflowReceipts, Error> {
+        match self.request(ServerMessage::WorkflowReceipts(cid)).await? {
+            ServerMessage::WorkflowReceiptsAck(response) => Ok(response),
+            _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+        }
+    }
+    async fn describe_module(
+        self,
+        _: context::Context,
+        cid: Cid,
+    ) -> Result<response::AckModule, Error> {
+        match self.request(ServerMessage::DescribeModule(cid)).await? {
+            ServerMessage::DescribeModuleAck(response) => Ok(response),
+            _ => Err(Error::FailureToSendOnChannel("unexpected message".into())),
+        }
+    }
 }
 
 impl Server {
@@ -297,6 +422,48 @@ impl Client {
         self.cli.node_info(self.ctx).await
     }
 
+    /// Cancel a running [Workflow], given its Cid or local name.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub async fn cancel(
+        &self,
+        workflow: String,
+    ) -> Result<Result<response::AckCancel, Error>, RpcError> {
+        self.cli.cancel(self.ctx, workflow).await
+    }
+
+    /// List workflows matching a [workflow::Filter].
+    pub async fn list_workflows(
+        &self,
+        filter: workflow::Filter,
+    ) -> Result<Result<response::AckWorkflowList, Error>, RpcError> {
+        self.cli.list_workflows(self.ctx, filter).await
+    }
+
+    /// Show a workflow, given its Cid or local name.
+    pub async fn show_workflow(
+        &self,
+        workflow: String,
+    ) -> Result<Result<Box<response::AckWorkflowDetail>, Error>, RpcError> {
+        self.cli.show_workflow(self.ctx, workflow).await
+    }
+
+    /// List the receipts of a workflow.
+    pub async fn workflow_receipts(
+        &self,
+        cid: Cid,
+    ) -> Result<Result<response::AckWorkflowReceipts, Error>, RpcError> {
+        self.cli.workflow_receipts(self.ctx, cid).await
+    }
+
+    /// Describe the exported functions of a Wasm module, given its Cid.
+    pub async fn describe_module(
+        &self,
+        cid: Cid,
+    ) -> Result<Result<response::AckModule, Error>, RpcError> {
+        self.cli.describe_module(self.ctx, cid).await
+    }
+
     /// Run a [Workflow].
     ///
     /// [Workflow]: homestar_workflow::Workflow
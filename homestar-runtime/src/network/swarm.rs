@@ -7,7 +7,7 @@ use crate::{
     network::{error::PubSubError, pubsub},
     settings, Receipt, RECEIPT_TAG, WORKFLOW_TAG,
 };
-use anyhow::{Context, Result};
+use anyhow::Result;
 use const_format::formatcp;
 use enum_assoc::Assoc;
 use faststr::FastStr;
@@ -43,12 +43,10 @@ pub(crate) const HOMESTAR_PROTOCOL_VER: &str = formatcp!("homestar/{VERSION}");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Build a new [Swarm] with a given transport and a tokio executor.
-pub(crate) async fn new(settings: &settings::Network) -> Result<Swarm<ComposedBehaviour>> {
-    let keypair = settings
-        .keypair_config
-        .keypair()
-        .with_context(|| "failed to generate/import keypair for libp2p".to_string())?;
-
+pub(crate) async fn new(
+    settings: &settings::Network,
+    keypair: Keypair,
+) -> Result<Swarm<ComposedBehaviour>> {
     let peer_id = keypair.public().to_peer_id();
     info!(
         subject = "swarm.init",
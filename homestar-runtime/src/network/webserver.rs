@@ -3,8 +3,8 @@
 use crate::{
     db::Database,
     ip, runner,
-    runner::{DynamicNodeInfo, StaticNodeInfo, WsSender},
-    settings,
+    runner::{response, DynamicNodeInfo, StaticNodeInfo, WsSender},
+    settings, workflow,
 };
 use anyhow::{anyhow, Result};
 use faststr::FastStr;
@@ -57,10 +57,26 @@ use rpc::{Context, JsonRpc};
 #[derive(Debug)]
 pub(crate) enum Message {
     RunErr(runner::Error),
-    /// Run a workflow, given a tuple of name, and [Workflow].
-    RunWorkflow((FastStr, Workflow<'static, Arg>)),
+    /// Run a workflow, given a tuple of name, [Workflow], requested
+    /// settings, and encoded UCANs authorizing its tasks.
+    RunWorkflow(
+        (
+            FastStr,
+            Workflow<'static, Arg>,
+            workflow::RequestedSettings,
+            Vec<String>,
+        ),
+    ),
     /// Acknowledgement of a [Workflow] run.
     AckWorkflow((Cid, FastStr)),
+    /// Cancel a running workflow, given its Cid or local name.
+    CancelWorkflow(FastStr),
+    /// Acknowledgement of a cancelled [Workflow].
+    AckCancelWorkflow(response::AckCancel),
+    /// Describe the exported functions of a Wasm module, given its Cid.
+    DescribeModule(Cid),
+    /// Acknowledgement of a described module.
+    AckDescribeModule(response::AckModule),
     /// Message sent to the [Runner] to gather node information from the [EventHandler].
     ///
     /// [Runner]: crate::Runner
@@ -87,6 +103,10 @@ pub(crate) struct Server {
     /// Message sender for broadcasting workflow-related events to clients
     /// connected to to the server.
     workflow_msg_notifier: Notifier<notifier::Message>,
+    /// Message sender for broadcasting guest log records of tasks to
+    /// clients connected to the server, apart from workflow-related events
+    /// so logs can't crowd them out.
+    task_log_notifier: Notifier<notifier::Message>,
     /// Sender timeout for the [Sink] messages.
     ///
     /// [Sink]: jsonrpsee::SubscriptionSink
@@ -132,6 +152,7 @@ impl Server {
     pub(crate) fn new(settings: &settings::Webserver) -> Result<Self> {
         let (evt_sender, _receiver) = Self::setup_channel(settings.websocket_capacity);
         let (msg_sender, _receiver) = Self::setup_channel(settings.websocket_capacity);
+        let (log_sender, _receiver) = Self::setup_channel(settings.websocket_capacity);
         let v4_host = IpAddr::from_str(&settings.v4_host.to_string())?;
         let v6_host = ip::parse_ip_from_uri_host(&settings.v6_host.to_string())
             .ok_or_else(|| anyhow!("unable to parse URI"))?;
@@ -158,6 +179,7 @@ impl Server {
             capacity: settings.websocket_capacity,
             evt_notifier: Notifier::new(evt_sender),
             workflow_msg_notifier: Notifier::new(msg_sender),
+            task_log_notifier: Notifier::new(log_sender),
             sender_timeout: settings.websocket_sender_timeout,
             webserver_timeout: settings.timeout,
         })
@@ -207,6 +229,7 @@ impl Server {
             metrics_hdl,
             self.evt_notifier.clone(),
             self.workflow_msg_notifier.clone(),
+            self.task_log_notifier.clone(),
             runner_sender,
             db,
             self.sender_timeout,
@@ -248,6 +271,13 @@ impl Server {
         self.workflow_msg_notifier.clone()
     }
 
+    /// Get WebSocket message sender for broadcasting guest log records of
+    /// tasks to connected clients.
+    #[cfg(feature = "websocket-notify")]
+    pub(crate) fn task_log_notifier(&self) -> Notifier<notifier::Message> {
+        self.task_log_notifier.clone()
+    }
+
     /// Shared start logic for both WebSocket and HTTP servers.
     async fn start_inner<DB: Database + 'static>(
         &self,
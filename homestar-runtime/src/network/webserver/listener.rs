@@ -1,18 +1,48 @@
 //! Listener for incoming requests types.
 
+use crate::workflow::{self, settings::SETTINGS_KEY};
 use anyhow::anyhow;
 use faststr::FastStr;
 use homestar_invocation::ipld::{DagCbor, DagJson};
 use homestar_wasm::io::Arg;
 use homestar_workflow::Workflow;
-use libipld::{serde::from_ipld, Ipld};
+use libipld::{serde::from_ipld, Cid, Ipld};
 use names::{Generator, Name};
 use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::value::RawValue;
+use serde_with::{serde_as, DisplayFromStr};
 use std::collections::BTreeMap;
 
 const NAME_KEY: &str = "name";
 const WORKFLOW_KEY: &str = "workflow";
+const UCANS_KEY: &str = "ucans";
+
+/// A [Workflow] cancel command via a WebSocket channel, given the
+/// workflow's Cid or local name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CancelWorkflow {
+    pub(crate) workflow: FastStr,
+}
+
+/// A module description request via a WebSocket channel, given the Cid of
+/// the Wasm module.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DescribeModule {
+    #[serde_as(as = "DisplayFromStr")]
+    pub(crate) cid: Cid,
+}
+
+/// A subscription to a task's logs via a WebSocket channel, given the Cids
+/// of its workflow and instruction.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TaskLogs {
+    #[serde_as(as = "DisplayFromStr")]
+    pub(crate) workflow: Cid,
+    #[serde_as(as = "DisplayFromStr")]
+    pub(crate) instruction: Cid,
+}
 
 /// A [Workflow] run command via a WebSocket channel.
 ///
@@ -24,6 +54,11 @@ pub(crate) struct JsonRun<'a> {
     pub(crate) name: FastStr,
     #[serde(deserialize_with = "from_raw_value")]
     pub(crate) workflow: Workflow<'a, Arg>,
+    #[serde(default)]
+    pub(crate) settings: workflow::RequestedSettings,
+    /// Encoded UCANs linked from the proofs of the workflow's tasks.
+    #[serde(default)]
+    pub(crate) ucans: Vec<String>,
 }
 
 fn default_name() -> FastStr {
@@ -46,14 +81,26 @@ where
 pub(crate) struct CborRun<'a> {
     pub(crate) name: FastStr,
     pub(crate) workflow: Workflow<'a, Arg>,
+    pub(crate) settings: workflow::RequestedSettings,
+    pub(crate) ucans: Vec<String>,
 }
 
 impl<'a> From<CborRun<'a>> for Ipld {
     fn from(run: CborRun<'a>) -> Self {
-        Ipld::Map(BTreeMap::from([
+        let mut map = BTreeMap::from([
             ("name".into(), Ipld::String(run.name.as_str().to_string())),
             ("workflow".into(), run.workflow.into()),
-        ]))
+        ]);
+        if run.settings != workflow::RequestedSettings::default() {
+            map.insert(SETTINGS_KEY.into(), run.settings.into());
+        }
+        if !run.ucans.is_empty() {
+            map.insert(
+                UCANS_KEY.into(),
+                Ipld::List(run.ucans.into_iter().map(Ipld::String).collect()),
+            );
+        }
+        Ipld::Map(map)
     }
 }
 
@@ -72,9 +119,17 @@ impl<'a> TryFrom<Ipld> for CborRun<'a> {
                 .ok_or_else(|| anyhow!("missing {WORKFLOW_KEY}"))?
                 .to_owned(),
         )?;
+        let settings = map
+            .get(SETTINGS_KEY)
+            .map_or_else(|| Ok(Default::default()), |ipld| ipld.to_owned().try_into())?;
+        let ucans = map
+            .get(UCANS_KEY)
+            .map_or_else(|| Ok(vec![]), |ipld| from_ipld(ipld.to_owned()))?;
         Ok(CborRun {
             name: FastStr::from(name),
             workflow,
+            settings,
+            ucans,
         })
     }
 }
@@ -110,6 +165,19 @@ mod test {
     };
     use std::{fs, path::PathBuf};
 
+    #[test]
+    fn task_logs_json() {
+        let workflow = "bafyrmic7gy3muoxiyepl44iwrkxxrnkl5oa77scqq3ptuigrm5faiqfgka";
+        let instruction = "bafybeiejevluvtoevgk66plh5t6xiy3ikyuuxg3vgofuvpeckb6eadresm";
+        let params = format!(r#"{{"workflow": "{workflow}", "instruction": "{instruction}"}}"#);
+
+        let task_logs: TaskLogs = serde_json::from_str(&params).unwrap();
+        assert_eq!(task_logs.workflow.to_string(), workflow);
+        assert_eq!(task_logs.instruction.to_string(), instruction);
+
+        assert!(serde_json::from_str::<TaskLogs>(r#"{"workflow": "not-a-cid"}"#).is_err());
+    }
+
     #[test]
     fn run_json() {
         let config = Resources::default();
@@ -131,6 +199,8 @@ mod test {
         let run = JsonRun {
             name: "test".into(),
             workflow: workflow.clone(),
+            settings: Default::default(),
+            ucans: vec![],
         };
 
         let run_str = format!(
@@ -140,6 +210,24 @@ mod test {
 
         let post_run = serde_json::from_str(&run_str).unwrap();
         assert_eq!(run, post_run);
+
+        let run_str = format!(
+            r#"{{"name": "test","workflow": {},"settings": {{"retries": 1,"timeout": 60}}}}"#,
+            workflow.to_json_string().unwrap()
+        );
+        let post_run: JsonRun<'_> = serde_json::from_str(&run_str).unwrap();
+        assert_eq!(post_run.settings.retries, Some(1));
+        assert_eq!(
+            post_run.settings.timeout,
+            Some(std::time::Duration::from_secs(60))
+        );
+
+        let run_str = format!(
+            r#"{{"name": "test","workflow": {},"ucans": ["eyJhbGciOiJFZERTQSJ9.e30.c2ln"]}}"#,
+            workflow.to_json_string().unwrap()
+        );
+        let post_run: JsonRun<'_> = serde_json::from_str(&run_str).unwrap();
+        assert_eq!(post_run.ucans, vec!["eyJhbGciOiJFZERTQSJ9.e30.c2ln"]);
     }
 
     #[test]
@@ -148,8 +236,12 @@ mod test {
             fs::read_to_string("tests/fixtures/test-workflow-image-pipeline.json").unwrap();
         let json: serde_json::Value = serde_json::from_str(&workflow_str).unwrap();
         let json_string = serde_json::to_string(&json).unwrap();
-        let run_str = format!(r#"{{"name": "test","workflow": {}}}"#, json_string);
+        let run_str = format!(
+            r#"{{"name": "test","workflow": {},"settings": {{"retries": 1}}}}"#,
+            json_string
+        );
         let run1: CborRun<'_> = DagJson::from_json_string(run_str).unwrap();
+        assert_eq!(run1.settings.retries, Some(1));
 
         let path = PathBuf::from("./fixtures/test.cbor");
         assert!(run1
@@ -10,6 +10,7 @@ use crate::{
     db::Database,
     runner::{NodeInfo, WsSender},
 };
+use crate::{runner, workflow};
 #[cfg(feature = "websocket-notify")]
 use anyhow::anyhow;
 use anyhow::Result;
@@ -62,6 +63,10 @@ pub(crate) const HEALTH_ENDPOINT: &str = "health";
 pub(crate) const METRICS_ENDPOINT: &str = "metrics";
 /// Node information endpoint.
 pub(crate) const NODE_INFO_ENDPOINT: &str = "node";
+/// Cancel a running workflow.
+pub(crate) const CANCEL_WORKFLOW_ENDPOINT: &str = "cancel_workflow";
+/// Describe the exported functions of a Wasm module.
+pub(crate) const DESCRIBE_MODULE_ENDPOINT: &str = "describe_module";
 /// Run a workflow and subscribe to that workflow's events.
 #[cfg(feature = "websocket-notify")]
 pub(crate) const SUBSCRIBE_RUN_WORKFLOW_ENDPOINT: &str = "subscribe_run_workflow";
@@ -74,6 +79,20 @@ pub(crate) const SUBSCRIBE_NETWORK_EVENTS_ENDPOINT: &str = "subscribe_network_ev
 /// Unsubscribe from network events.
 #[cfg(feature = "websocket-notify")]
 pub(crate) const UNSUBSCRIBE_NETWORK_EVENTS_ENDPOINT: &str = "unsubscribe_network_events";
+/// Subscribe to a task's guest logs.
+#[cfg(feature = "websocket-notify")]
+pub(crate) const SUBSCRIBE_TASK_LOGS_ENDPOINT: &str = "subscribe_task_logs";
+/// Unsubscribe from a task's guest logs.
+#[cfg(feature = "websocket-notify")]
+pub(crate) const UNSUBSCRIBE_TASK_LOGS_ENDPOINT: &str = "unsubscribe_task_logs";
+
+/// Server error code for a workflow whose tasks are not authorized by their
+/// UCAN proofs.
+pub(crate) const UNAUTHORIZED_CODE: i32 = -32020;
+
+/// Server error code for a workflow whose tasks run Wasm modules the node's
+/// module policy doesn't allow, or for describing such a module.
+pub(crate) const MODULE_NOT_ALLOWED_CODE: i32 = -32021;
 
 /// Context for RPC methods.
 #[cfg(feature = "websocket-notify")]
@@ -82,6 +101,7 @@ pub(crate) struct Context<DB: Database> {
     metrics_hdl: PrometheusHandle,
     evt_notifier: Notifier<notifier::Message>,
     workflow_msg_notifier: Notifier<notifier::Message>,
+    task_log_notifier: Notifier<notifier::Message>,
     runner_sender: WsSender,
     sender_timeout: Duration,
     workflow_listeners: Arc<DashMap<SubscriptionId<'static>, (Cid, FastStr)>>,
@@ -108,6 +128,7 @@ where
         metrics_hdl: PrometheusHandle,
         evt_notifier: Notifier<notifier::Message>,
         workflow_msg_notifier: Notifier<notifier::Message>,
+        task_log_notifier: Notifier<notifier::Message>,
         runner_sender: WsSender,
         db: DB,
         sender_timeout: Duration,
@@ -117,6 +138,7 @@ where
             metrics_hdl,
             evt_notifier,
             workflow_msg_notifier,
+            task_log_notifier,
             runner_sender,
             sender_timeout,
             workflow_listeners: DashMap::new().into(),
@@ -219,6 +241,58 @@ where
             }
         })?;
 
+        module.register_async_method(CANCEL_WORKFLOW_ENDPOINT, |params, ctx| async move {
+            let listener::CancelWorkflow { workflow } = params
+                .one::<listener::CancelWorkflow>()
+                .map_err(|err| invalid_params_err(err.to_string()))?;
+
+            let (tx, rx) = crate::channel::AsyncChannel::oneshot();
+            ctx.runner_sender
+                .send_async((Message::CancelWorkflow(workflow), Some(tx)))
+                .await
+                .map_err(|err| internal_err(err.to_string()))?;
+
+            match rx.recv_async().await {
+                Ok(Message::AckCancelWorkflow(ack)) => Ok(serde_json::json!(ack)),
+                Ok(Message::RunErr(err)) => Err(run_err(err)),
+                _ => {
+                    error!(
+                        subject = "call.cancel_workflow",
+                        category = "jsonrpc.call",
+                        sub = CANCEL_WORKFLOW_ENDPOINT,
+                        "did not acknowledge message in time"
+                    );
+                    Err(internal_err("failed to cancel workflow".to_string()))
+                }
+            }
+        })?;
+
+        module.register_async_method(DESCRIBE_MODULE_ENDPOINT, |params, ctx| async move {
+            let listener::DescribeModule { cid } = params
+                .one::<listener::DescribeModule>()
+                .map_err(|err| invalid_params_err(err.to_string()))?;
+
+            let (tx, rx) = crate::channel::AsyncChannel::oneshot();
+            ctx.runner_sender
+                .send_async((Message::DescribeModule(cid), Some(tx)))
+                .await
+                .map_err(|err| internal_err(err.to_string()))?;
+
+            match rx.recv_async().await {
+                Ok(Message::AckDescribeModule(ack)) => Ok(serde_json::json!(ack)),
+                Ok(Message::RunErr(err)) => Err(run_err(err)),
+                _ => {
+                    error!(
+                        subject = "call.describe_module",
+                        category = "jsonrpc.call",
+                        sub = DESCRIBE_MODULE_ENDPOINT,
+                        "did not acknowledge message in time"
+                    );
+                    Err(internal_err("failed to describe module".to_string()))
+                }
+            }
+        })?;
+
         #[cfg(feature = "websocket-notify")]
         module.register_subscription(
             SUBSCRIBE_NETWORK_EVENTS_ENDPOINT,
@@ -239,6 +313,41 @@ where
             },
         )?;
 
+        #[cfg(feature = "websocket-notify")]
+        module.register_subscription(
+            SUBSCRIBE_TASK_LOGS_ENDPOINT,
+            SUBSCRIBE_TASK_LOGS_ENDPOINT,
+            UNSUBSCRIBE_TASK_LOGS_ENDPOINT,
+            |params, pending, ctx| async move {
+                match params.one::<listener::TaskLogs>() {
+                    Ok(listener::TaskLogs {
+                        workflow,
+                        instruction,
+                    }) => {
+                        let sink = pending.accept().await?;
+                        let rx = ctx.task_log_notifier.inner().subscribe();
+                        let stream = BroadcastStream::new(rx);
+                        Self::handle_task_logs_subscription(
+                            sink,
+                            stream,
+                            ctx,
+                            workflow,
+                            instruction,
+                        )
+                        .await?;
+                    }
+                    Err(err) => {
+                        warn!(subject = "subscription.task_logs.err",
+                              category = "jsonrpc.subscription",
+                              err=?err,
+                              "failed to parse task logs params");
+                        let _ = pending.reject(err).await;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
         #[cfg(feature = "websocket-notify")]
         module.register_subscription(
             SUBSCRIBE_RUN_WORKFLOW_ENDPOINT,
@@ -246,11 +355,21 @@ where
             UNSUBSCRIBE_RUN_WORKFLOW_ENDPOINT,
             |params, pending, ctx| async move {
                 match params.one::<listener::JsonRun<'_>>() {
-                    Ok(listener::JsonRun { name, workflow }) => {
+                    Ok(listener::JsonRun {
+                        name,
+                        workflow,
+                        settings,
+                        ucans,
+                    }) => {
                         let (tx, rx) = AsyncChannel::oneshot();
                         ctx.runner_sender
                             .send_async((
-                                Message::RunWorkflow((name.clone(), workflow.clone())),
+                                Message::RunWorkflow((
+                                    name.clone(),
+                                    workflow.clone(),
+                                    settings,
+                                    ucans,
+                                )),
                                 Some(tx),
                             ))
                             .await?;
@@ -259,11 +378,21 @@ where
                     }
 
                     Err(_err) => match params.one::<listener::CborRun<'_>>() {
-                        Ok(listener::CborRun { name, workflow }) => {
+                        Ok(listener::CborRun {
+                            name,
+                            workflow,
+                            settings,
+                            ucans,
+                        }) => {
                             let (tx, rx) = AsyncChannel::oneshot();
                             ctx.runner_sender
                                 .send_async((
-                                    Message::RunWorkflow((name.clone(), workflow.clone())),
+                                    Message::RunWorkflow((
+                                        name.clone(),
+                                        workflow.clone(),
+                                        settings,
+                                        ucans,
+                                    )),
                                     Some(tx),
                                 ))
                                 .await?;
@@ -294,27 +423,41 @@ where
         ctx: Arc<Context<DB>>,
         pending: PendingSubscriptionSink,
     ) -> Result<()> {
-        if let Ok(Message::AckWorkflow((cid, name))) = rx.recv_async().await {
-            let sink = pending.accept().await?;
-            ctx.workflow_listeners
-                .insert(sink.subscription_id(), (cid, name));
-            let rx = ctx.workflow_msg_notifier.inner().subscribe();
-            let stream = BroadcastStream::new(rx);
-            Self::handle_workflow_subscription(sink, stream, ctx).await?;
-        } else {
-            error!(
-                subject = "subscription.workflow.err",
-                category = "jsonrpc.subscription",
-                sub = SUBSCRIBE_RUN_WORKFLOW_ENDPOINT,
-                workflow_name = name.to_string(),
-                "did not acknowledge message in time"
-            );
-            let _ = pending
-                .reject(busy_err(format!(
-                    "not able to run workflow {}",
-                    workflow.to_cid()?
-                )))
-                .await;
+        match rx.recv_async().await {
+            Ok(Message::AckWorkflow((cid, name))) => {
+                let sink = pending.accept().await?;
+                ctx.workflow_listeners
+                    .insert(sink.subscription_id(), (cid, name));
+                let rx = ctx.workflow_msg_notifier.inner().subscribe();
+                let stream = BroadcastStream::new(rx);
+                Self::handle_workflow_subscription(sink, stream, ctx).await?;
+            }
+            Ok(Message::RunErr(err)) => {
+                warn!(
+                    subject = "subscription.workflow.err",
+                    category = "jsonrpc.subscription",
+                    sub = SUBSCRIBE_RUN_WORKFLOW_ENDPOINT,
+                    workflow_name = name.to_string(),
+                    err=?err,
+                    "workflow was not accepted"
+                );
+                let _ = pending.reject(run_err(err)).await;
+            }
+            _ => {
+                error!(
+                    subject = "subscription.workflow.err",
+                    category = "jsonrpc.subscription",
+                    sub = SUBSCRIBE_RUN_WORKFLOW_ENDPOINT,
+                    workflow_name = name.to_string(),
+                    "did not acknowledge message in time"
+                );
+                let _ = pending
+                    .reject(busy_err(format!(
+                        "not able to run workflow {}",
+                        workflow.to_cid()?
+                    )))
+                    .await;
+            }
         }
 
         Ok(())
@@ -373,6 +516,60 @@ where
         Ok(())
     }
 
+    #[cfg(feature = "websocket-notify")]
+    async fn handle_task_logs_subscription(
+        sink: SubscriptionSink,
+        mut stream: BroadcastStream<notifier::Message>,
+        ctx: Arc<Context<DB>>,
+        workflow_cid: Cid,
+        instruction_cid: Cid,
+    ) -> Result<()> {
+        let rt_hdl = Handle::current();
+        rt_hdl.spawn(async move {
+            loop {
+                select! {
+                    _ = sink.closed() => {
+                        break Ok(());
+                    }
+                    next_msg = stream.next() => {
+                        let msg = match next_msg {
+                            Some(Ok(notifier::Message {
+                                header: Header {
+                                    subscription: SubscriptionTyp::TaskLogs { workflow, instruction },
+                                    ..
+                                },
+                                payload,
+                            })) if workflow == workflow_cid && instruction == instruction_cid => payload,
+                            Some(Ok(_)) => continue,
+                            Some(Err(err)) => {
+                                error!(subject = "subscription.task_logs.err",
+                                       category = "jsonrpc.subscription",
+                                       err=?err,
+                                       "subscription stream error");
+                                break Err(err.into());
+                            }
+                            None => break Ok(()),
+                        };
+                        let sub_msg = SubscriptionMessage::from_json(&msg)?;
+                        match sink.send_timeout(sub_msg, ctx.sender_timeout).await {
+                            Ok(()) => (),
+                            Err(SendTimeoutError::Closed(_)) => {
+                                break Err(anyhow!("subscription sink closed"));
+                            }
+                            Err(SendTimeoutError::Timeout(_)) => {
+                                error!(subject = "subscription.task_logs.err",
+                                      category = "jsonrpc.subscription",
+                                      "subscription sink timed out");
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
     #[cfg(feature = "websocket-notify")]
     async fn handle_workflow_subscription(
         sink: SubscriptionSink,
@@ -452,6 +649,36 @@ fn internal_err<'a, T: ToString>(msg: T) -> ErrorObject<'a> {
     ErrorObject::owned(ErrorCode::InternalError.code(), msg.to_string(), None::<()>)
 }
 
+fn invalid_params_err<'a, T: ToString>(msg: T) -> ErrorObject<'a> {
+    ErrorObject::owned(ErrorCode::InvalidParams.code(), msg.to_string(), None::<()>)
+}
+
+/// Map an error from the [Runner] to a JSON-RPC error, distinguishing tasks
+/// that are not authorized by their UCAN proofs, modules the node doesn't
+/// allow, and requests for workflows that can't be cancelled.
+///
+/// [Runner]: crate::Runner
+fn run_err<'a>(err: runner::Error) -> ErrorObject<'a> {
+    match err {
+        runner::Error::ModuleNotAllowed { .. } => {
+            ErrorObject::owned(MODULE_NOT_ALLOWED_CODE, err.to_string(), None::<()>)
+        }
+        runner::Error::WorkflowNotFound(_) | runner::Error::WorkflowNotRunning(_) => {
+            invalid_params_err(err)
+        }
+        runner::Error::Runtime(err) => match err.downcast_ref::<workflow::Error>() {
+            Some(unauthorized @ workflow::Error::Unauthorized { .. }) => {
+                ErrorObject::owned(UNAUTHORIZED_CODE, unauthorized.to_string(), None::<()>)
+            }
+            Some(denied @ workflow::Error::ModuleNotAllowed { .. }) => {
+                ErrorObject::owned(MODULE_NOT_ALLOWED_CODE, denied.to_string(), None::<()>)
+            }
+            _ => internal_err(err),
+        },
+        err => internal_err(err),
+    }
+}
+
 #[allow(dead_code)]
 fn busy_err<'a, T: ToString>(msg: T) -> ErrorObject<'a> {
     ErrorObject::owned(ErrorCode::ServerIsBusy.code(), msg.to_string(), None::<()>)
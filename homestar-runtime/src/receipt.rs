@@ -16,6 +16,7 @@ use homestar_invocation::{
     authority::{Issuer, UcanPrf},
     consts,
     ipld::{DagCborRef, DagJson},
+    receipt::metadata::CACHEABLE_KEY,
     task, Pointer, Receipt as InvocationReceipt,
 };
 use homestar_wasm::io::Arg;
@@ -25,6 +26,8 @@ use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt};
 
 pub(crate) mod metadata;
+pub(crate) mod signer;
+pub(crate) mod verifier;
 
 /// General version key for receipts.
 pub const VERSION_KEY: &str = "version";
@@ -32,12 +35,15 @@ pub const VERSION_KEY: &str = "version";
 pub const RECEIPT_TAG: &str = "ipvm/receipt";
 
 const CID_KEY: &str = "cid";
-const INSTRUCTION_KEY: &str = "instruction";
+pub(crate) const INSTRUCTION_KEY: &str = "instruction";
 const RAN_KEY: &str = "ran";
 const OUT_KEY: &str = "out";
 const ISSUER_KEY: &str = "iss";
 const METADATA_KEY: &str = "meta";
 const PROOF_KEY: &str = "prf";
+/// Key of a receipt's detached signature, over the DAG-CBOR form of its
+/// [InvocationReceipt].
+pub(crate) const SIGNATURE_KEY: &str = "sig";
 
 /// Receipt for [Invocation], including it's own Cid and a Cid for an [Instruction].
 ///
@@ -57,6 +63,7 @@ pub struct Receipt {
     issuer: Option<Issuer>,
     prf: UcanPrf,
     version: String,
+    signature: Option<Vec<u8>>,
 }
 
 impl fmt::Display for Receipt {
@@ -85,6 +92,7 @@ impl Receipt {
             issuer: invocation_receipt.issuer().to_owned(),
             prf: invocation_receipt.prf().to_owned(),
             version: consts::INVOCATION_VERSION.to_string(),
+            signature: None,
         }
     }
 
@@ -102,13 +110,17 @@ impl Receipt {
     }
 
     /// Capsule-wrapper for [InvocationReceipt] to to be shared over libp2p as
-    /// DagCbor encoded bytes.
+    /// DagCbor encoded bytes, along with its detached signature, if signed.
     pub fn invocation_capsule(
         invocation_receipt: &InvocationReceipt<Ipld>,
+        signature: Option<&[u8]>,
     ) -> anyhow::Result<Vec<u8>> {
         let receipt_ipld = Ipld::from(invocation_receipt);
         let capsule = if let Ipld::Map(mut map) = receipt_ipld {
             map.insert(VERSION_KEY.into(), consts::INVOCATION_VERSION.into());
+            if let Some(signature) = signature {
+                map.insert(SIGNATURE_KEY.into(), Ipld::Bytes(signature.to_vec()));
+            }
             Ok(Ipld::Map(BTreeMap::from([(
                 RECEIPT_TAG.into(),
                 Ipld::Map(map),
@@ -125,6 +137,35 @@ impl Receipt {
         self.meta.inner()
     }
 
+    /// Whether the [Receipt] may be reused in place of running its
+    /// instruction again, i.e. it isn't an error and isn't marked as not
+    /// cacheable in its metadata.
+    pub fn is_cacheable(&self) -> bool {
+        if matches!(self.out, task::Result::Error(_)) {
+            return false;
+        }
+
+        match self.meta() {
+            Ipld::Map(meta) => !matches!(meta.get(CACHEABLE_KEY), Some(Ipld::Bool(false))),
+            _ => true,
+        }
+    }
+
+    /// Get the [Issuer] of a [Receipt], if any.
+    pub fn issuer(&self) -> Option<&Issuer> {
+        self.issuer.as_ref()
+    }
+
+    /// Get the detached signature of a [Receipt], if signed.
+    pub fn signature(&self) -> Option<&[u8]> {
+        self.signature.as_deref()
+    }
+
+    /// Set the detached signature of a [Receipt].
+    pub fn set_signature(&mut self, signature: Vec<u8>) {
+        self.signature = Some(signature)
+    }
+
     /// Set Ipld metadata on a [Receipt].
     pub fn set_meta(&mut self, meta: Ipld) {
         self.meta = LocalIpld(meta)
@@ -182,6 +223,13 @@ impl Receipt {
         DagCborCodec.encode(&ipld)
     }
 
+    /// Get the [Receipt] as the DAG-CBOR encoded [InvocationReceipt] block
+    /// its Cid addresses.
+    pub(crate) fn block(&self) -> anyhow::Result<Vec<u8>> {
+        let invocation_receipt = InvocationReceipt::<Ipld>::from(self);
+        DagCborCodec.encode(&Ipld::from(&invocation_receipt))
+    }
+
     /// Return semver [Version] of [Receipt].
     pub fn version(&self) -> Result<Version, semver::Error> {
         Version::parse(&self.version)
@@ -232,7 +280,7 @@ impl From<&Receipt> for InvocationReceipt<Ipld> {
 
 impl From<Receipt> for Ipld {
     fn from(receipt: Receipt) -> Self {
-        Ipld::Map(BTreeMap::from([
+        let mut map = BTreeMap::from([
             (CID_KEY.into(), receipt.cid.into()),
             (RAN_KEY.into(), receipt.ran.into()),
             (INSTRUCTION_KEY.into(), receipt.instruction.into()),
@@ -247,7 +295,12 @@ impl From<Receipt> for Ipld {
             ),
             (PROOF_KEY.into(), receipt.prf.into()),
             (VERSION_KEY.into(), receipt.version.into()),
-        ]))
+        ]);
+        if let Some(signature) = receipt.signature {
+            map.insert(SIGNATURE_KEY.into(), Ipld::Bytes(signature));
+        }
+
+        Ipld::Map(map)
     }
 }
 
@@ -291,6 +344,10 @@ impl TryFrom<Ipld> for Receipt {
                 .ok_or_else(|| anyhow!("missing {VERSION_KEY}"))?
                 .to_owned(),
         )?;
+        let signature = match map.get(SIGNATURE_KEY) {
+            Some(Ipld::Bytes(signature)) => Some(signature.to_owned()),
+            _ => None,
+        };
 
         Ok(Receipt {
             cid: Pointer::new(cid),
@@ -301,6 +358,7 @@ impl TryFrom<Ipld> for Receipt {
             issuer,
             prf: UcanPrf::try_from(prf)?,
             version,
+            signature,
         })
     }
 }
@@ -394,7 +452,10 @@ mod test {
             .unwrap();
 
         assert_eq!(1, rows_inserted);
-        let inserted_receipt = schema::receipts::table.load::<Receipt>(&mut conn).unwrap();
+        let inserted_receipt = schema::receipts::table
+            .select(Receipt::as_select())
+            .load::<Receipt>(&mut conn)
+            .unwrap();
         assert_eq!(vec![receipt.clone()], inserted_receipt);
     }
 
@@ -421,4 +482,37 @@ mod test {
 
         assert_eq!(receipt, from_bytes);
     }
+
+    #[test]
+    fn receipt_cacheable_unless_marked() {
+        let (_, mut receipt) = test_utils::receipt::receipts();
+        assert!(receipt.is_cacheable());
+
+        receipt.set_meta(Ipld::Map(BTreeMap::from([(
+            CACHEABLE_KEY.into(),
+            Ipld::Bool(false),
+        )])));
+        assert!(!receipt.is_cacheable());
+    }
+
+    #[test]
+    fn error_receipt_not_cacheable() {
+        let (local, _) = test_utils::receipt::receipts();
+        let failed = InvocationReceipt::new(
+            local.ran().clone(),
+            task::Result::Error(Ipld::String("trap".to_string())),
+            Ipld::Null,
+            None,
+            UcanPrf::default(),
+        );
+        let receipt = Receipt::try_with(
+            homestar_invocation::test_utils::instruction::<Ipld>()
+                .try_into()
+                .unwrap(),
+            &failed,
+        )
+        .unwrap();
+
+        assert!(!receipt.is_cacheable());
+    }
 }
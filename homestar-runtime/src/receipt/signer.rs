@@ -0,0 +1,154 @@
+//! Signing of [receipts] with the node's identity key.
+//!
+//! Receipts are issued by the `did:key` of the node's libp2p keypair and
+//! carry a detached signature over the DAG-CBOR form of their
+//! [InvocationReceipt], along with the [Pointer] to the instruction they're
+//! a receipt for.
+//!
+//! [receipts]: crate::Receipt
+
+use crate::receipt::INSTRUCTION_KEY;
+use anyhow::{anyhow, Result};
+use homestar_invocation::{authority::Issuer, Pointer, Receipt as InvocationReceipt};
+use libipld::{
+    cbor::DagCborCodec,
+    cid::multibase::{self, Base},
+    prelude::Codec,
+    Ipld,
+};
+use libp2p::identity::{ed25519, secp256k1, Keypair, PublicKey};
+use std::{collections::BTreeMap, fmt};
+
+/// Multicodec prefix of an ed25519 public key.
+const ED25519_PUB: [u8; 2] = [0xed, 0x01];
+/// Multicodec prefix of a (compressed) secp256k1 public key.
+const SECP256K1_PUB: [u8; 2] = [0xe7, 0x01];
+
+/// Key of the [InvocationReceipt] in the signed payload.
+const RECEIPT_KEY: &str = "receipt";
+
+/// Signs receipts on behalf of the node, as their [Issuer].
+#[derive(Clone)]
+pub(crate) struct ReceiptSigner {
+    keypair: Keypair,
+    issuer: Issuer,
+}
+
+impl fmt::Debug for ReceiptSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReceiptSigner")
+            .field("issuer", &self.issuer)
+            .finish()
+    }
+}
+
+impl ReceiptSigner {
+    /// Create a [ReceiptSigner] for the node's keypair.
+    pub(crate) fn new(keypair: Keypair) -> Result<Self> {
+        let did = did_key(&keypair.public())?;
+        let issuer = Issuer::try_from(Ipld::String(did.clone()))
+            .map_err(|err| anyhow!("invalid issuer {did}: {err}"))?;
+
+        Ok(Self { keypair, issuer })
+    }
+
+    /// [Issuer] of signed receipts, i.e. the node's `did:key`.
+    pub(crate) fn issuer(&self) -> &Issuer {
+        &self.issuer
+    }
+
+    /// Sign an [InvocationReceipt] for the instruction at `instruction`.
+    pub(crate) fn sign(
+        &self,
+        instruction: &Pointer,
+        invocation_receipt: &InvocationReceipt<Ipld>,
+    ) -> Result<Vec<u8>> {
+        Ok(self
+            .keypair
+            .sign(&signing_bytes(instruction, invocation_receipt)?)?)
+    }
+}
+
+/// Bytes a receipt's signature is over, i.e. the DAG-CBOR form of its
+/// [InvocationReceipt] and instruction [Pointer], so a signed receipt can't
+/// be passed off as the receipt of another instruction.
+pub(crate) fn signing_bytes(
+    instruction: &Pointer,
+    invocation_receipt: &InvocationReceipt<Ipld>,
+) -> Result<Vec<u8>> {
+    DagCborCodec.encode(&Ipld::Map(BTreeMap::from([
+        (INSTRUCTION_KEY.into(), Ipld::Link(instruction.cid())),
+        (RECEIPT_KEY.into(), Ipld::from(invocation_receipt)),
+    ])))
+}
+
+/// `did:key` of a public key.
+pub(crate) fn did_key(public: &PublicKey) -> Result<String> {
+    let bytes = if let Ok(key) = public.clone().try_into_ed25519() {
+        [ED25519_PUB.as_slice(), &key.to_bytes()].concat()
+    } else if let Ok(key) = public.clone().try_into_secp256k1() {
+        [SECP256K1_PUB.as_slice(), &key.to_bytes()].concat()
+    } else {
+        return Err(anyhow!("unsupported key type {:?}", public.key_type()));
+    };
+
+    Ok(format!(
+        "did:key:{}",
+        multibase::encode(Base::Base58Btc, bytes)
+    ))
+}
+
+/// Public key of a `did:key`.
+pub(crate) fn public_key(did: &str) -> Result<PublicKey> {
+    let encoded = did
+        .strip_prefix("did:key:")
+        .ok_or_else(|| anyhow!("{did} is not a did:key"))?;
+    let (base, bytes) = multibase::decode(encoded)?;
+    if base != Base::Base58Btc {
+        return Err(anyhow!("{did} is not base58btc encoded"));
+    }
+
+    if let Some(key) = bytes.strip_prefix(ED25519_PUB.as_slice()) {
+        Ok(ed25519::PublicKey::try_from_bytes(key)?.into())
+    } else if let Some(key) = bytes.strip_prefix(SECP256K1_PUB.as_slice()) {
+        Ok(secp256k1::PublicKey::try_from_bytes(key)?.into())
+    } else {
+        Err(anyhow!("unsupported key type for {did}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    #[test]
+    fn issuer_is_did_key_of_keypair() {
+        let keypair = Keypair::ed25519_from_bytes([0; 32]).unwrap();
+        let signer = ReceiptSigner::new(keypair.clone()).unwrap();
+
+        assert!(signer.issuer().to_string().starts_with("did:key:z6Mk"));
+
+        let (invocation_receipt, receipt) = test_utils::receipt::receipts();
+        let signature = signer
+            .sign(receipt.instruction(), &invocation_receipt)
+            .unwrap();
+        assert!(keypair.public().verify(
+            &signing_bytes(receipt.instruction(), &invocation_receipt).unwrap(),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn public_key_of_did_key() {
+        let ed25519 = Keypair::generate_ed25519().public();
+        let secp256k1 = Keypair::generate_secp256k1().public();
+
+        assert_eq!(public_key(&did_key(&ed25519).unwrap()).unwrap(), ed25519);
+        assert_eq!(
+            public_key(&did_key(&secp256k1).unwrap()).unwrap(),
+            secp256k1
+        );
+        assert!(public_key("did:web:example.com").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Verification of [receipts] received from the network against the node's
+//! [ReceiptPolicy].
+//!
+//! [receipts]: crate::Receipt
+
+use crate::{
+    metrics::Reason,
+    receipt::signer::{public_key, signing_bytes},
+    settings::ReceiptPolicy,
+    Receipt,
+};
+use homestar_invocation::{ipld::DagCbor, Receipt as InvocationReceipt};
+use libipld::Ipld;
+
+/// Reason a [Receipt] received from the network was rejected.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub(crate) enum Rejection {
+    /// Receipt Cid doesn't match its content.
+    #[error("receipt Cid {0} does not match its content")]
+    CidMismatch(String),
+    /// Receipt has no signature.
+    #[error("receipt is not signed")]
+    Unsigned,
+    /// Receipt is signed, but has no issuer.
+    #[error("receipt has no issuer")]
+    MissingIssuer,
+    /// Receipt issuer is not a supported `did:key`.
+    #[error("unsupported issuer {0}: {1}")]
+    UnsupportedIssuer(String, String),
+    /// Receipt signature is not valid for its issuer.
+    #[error("invalid signature for issuer {0}")]
+    InvalidSignature(String),
+    /// Receipt issuer is not in the allowlist of trusted issuers.
+    #[error("issuer {0} is not trusted")]
+    UntrustedIssuer(String),
+}
+
+impl Reason for Rejection {
+    fn reason(&self) -> &'static str {
+        match self {
+            Rejection::CidMismatch(_) => "cid_mismatch",
+            Rejection::Unsigned => "unsigned",
+            Rejection::MissingIssuer => "missing_issuer",
+            Rejection::UnsupportedIssuer(..) => "unsupported_issuer",
+            Rejection::InvalidSignature(_) => "invalid_signature",
+            Rejection::UntrustedIssuer(_) => "untrusted_issuer",
+        }
+    }
+}
+
+/// Verify a [Receipt] received from the network against a [ReceiptPolicy].
+///
+/// Receipts are accepted as-is under [ReceiptPolicy::All]. Otherwise, they
+/// must carry a valid signature by their issuer's `did:key`, over their
+/// instruction as well, and the issuer must be trusted under
+/// [ReceiptPolicy::Allowlist].
+pub(crate) fn verify(receipt: &Receipt, policy: &ReceiptPolicy) -> Result<(), Rejection> {
+    let trusted = match policy {
+        ReceiptPolicy::All => return Ok(()),
+        ReceiptPolicy::Signed => None,
+        ReceiptPolicy::Allowlist { issuers } => Some(issuers),
+    };
+
+    let invocation_receipt = InvocationReceipt::<Ipld>::from(receipt);
+    if invocation_receipt.clone().to_cid().ok() != Some(receipt.cid()) {
+        return Err(Rejection::CidMismatch(receipt.cid_as_string()));
+    }
+
+    let signature = receipt.signature().ok_or(Rejection::Unsigned)?;
+    let issuer = receipt
+        .issuer()
+        .ok_or(Rejection::MissingIssuer)?
+        .to_string();
+    let public = public_key(&issuer)
+        .map_err(|err| Rejection::UnsupportedIssuer(issuer.clone(), err.to_string()))?;
+
+    let signed = signing_bytes(receipt.instruction(), &invocation_receipt)
+        .map(|bytes| public.verify(&bytes, signature))
+        .unwrap_or(false);
+    if !signed {
+        return Err(Rejection::InvalidSignature(issuer));
+    }
+
+    match trusted {
+        Some(issuers) if !issuers.contains(&issuer) => Err(Rejection::UntrustedIssuer(issuer)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{receipt::signer::ReceiptSigner, test_utils};
+    use libp2p::identity::Keypair;
+
+    fn signed_receipt(signer: &ReceiptSigner) -> Receipt {
+        let (invocation_receipt, receipt) = test_utils::receipt::receipts();
+        let invocation_receipt = InvocationReceipt::new(
+            invocation_receipt.ran().to_owned(),
+            invocation_receipt.out().to_owned(),
+            invocation_receipt.meta().to_owned(),
+            Some(signer.issuer().to_owned()),
+            invocation_receipt.prf().to_owned(),
+        );
+        let mut receipt =
+            Receipt::try_with(receipt.instruction().to_owned(), &invocation_receipt).unwrap();
+        receipt.set_signature(
+            signer
+                .sign(receipt.instruction(), &invocation_receipt)
+                .unwrap(),
+        );
+        receipt
+    }
+
+    #[test]
+    fn accepts_receipts_per_policy() {
+        let signer = ReceiptSigner::new(Keypair::generate_ed25519()).unwrap();
+        let issuer = signer.issuer().to_string();
+        let signed = signed_receipt(&signer);
+        let (_, unsigned) = test_utils::receipt::receipts();
+
+        assert!(verify(&unsigned, &ReceiptPolicy::All).is_ok());
+        assert_eq!(
+            verify(&unsigned, &ReceiptPolicy::Signed),
+            Err(Rejection::Unsigned)
+        );
+        assert!(verify(&signed, &ReceiptPolicy::Signed).is_ok());
+        assert!(verify(
+            &signed,
+            &ReceiptPolicy::Allowlist {
+                issuers: vec![issuer.clone()]
+            }
+        )
+        .is_ok());
+        assert_eq!(
+            verify(
+                &signed,
+                &ReceiptPolicy::Allowlist {
+                    issuers: vec![
+                        "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp".to_string()
+                    ]
+                }
+            ),
+            Err(Rejection::UntrustedIssuer(issuer))
+        );
+    }
+
+    #[test]
+    fn rejects_forged_receipts() {
+        let signer = ReceiptSigner::new(Keypair::generate_ed25519()).unwrap();
+        let other = ReceiptSigner::new(Keypair::generate_ed25519()).unwrap();
+        let signed = signed_receipt(&signer);
+
+        let mut forged = signed.clone();
+        forged.set_signature(
+            other
+                .sign(signed.instruction(), &InvocationReceipt::from(&signed))
+                .unwrap(),
+        );
+        assert_eq!(
+            verify(&forged, &ReceiptPolicy::Signed),
+            Err(Rejection::InvalidSignature(signer.issuer().to_string()))
+        );
+
+        // Signed receipt, passed off as the receipt of another instruction.
+        let invocation_receipt = InvocationReceipt::from(&signed);
+        let mut forged =
+            Receipt::try_with(invocation_receipt.ran().to_owned(), &invocation_receipt).unwrap();
+        forged.set_signature(signed.signature().unwrap().to_vec());
+        assert_eq!(
+            verify(&forged, &ReceiptPolicy::Signed),
+            Err(Rejection::InvalidSignature(signer.issuer().to_string()))
+        );
+
+        let mut forged = signed;
+        forged.set_meta(Ipld::String("forged".to_string()));
+        assert_eq!(
+            verify(&forged, &ReceiptPolicy::Signed),
+            Err(Rejection::CidMismatch(forged.cid_as_string()))
+        );
+
+        let (_, mut forged) = test_utils::receipt::receipts();
+        forged.set_signature(vec![0; 64]);
+        assert_eq!(
+            verify(&forged, &ReceiptPolicy::Signed),
+            Err(Rejection::MissingIssuer)
+        );
+    }
+}
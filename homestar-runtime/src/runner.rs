@@ -1,35 +1,41 @@
 //! General [Runner] interface for working across multiple workers
 //! and executing workflows.
 
+#[cfg(feature = "websocket-notify")]
+use crate::event_handler::notification;
 #[cfg(feature = "ipfs")]
 use crate::network::IpfsCli;
 use crate::{
     channel::{AsyncChannel, AsyncChannelReceiver, AsyncChannelSender},
-    db::Database,
+    db::{prune, Connection, Database},
     event_handler::{Event, EventHandler},
     network::{rpc, swarm, webserver},
+    receipt::signer::ReceiptSigner,
     settings,
-    tasks::Fetch,
-    worker::WorkerMessage,
+    tasks::{Fetch, LoadedModulePolicy},
+    worker::{WorkerMessage, WorkerNode},
     workflow::{self, Resource},
     Db, Receipt, Settings, Worker,
 };
 use anyhow::{anyhow, Context, Result};
 use atomic_refcell::AtomicRefCell;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use dashmap::DashMap;
 use faststr::FastStr;
 use fnv::FnvHashSet;
-use futures::{future::poll_fn, FutureExt};
-use homestar_invocation::Pointer;
-use homestar_wasm::io::Arg;
+use futures::{
+    future::{poll_fn, Future},
+    FutureExt,
+};
+use homestar_invocation::{ipld::DagCbor, Pointer};
+use homestar_wasm::{io::Arg, wasmtime::Runtime as WasmRuntime};
 use homestar_workflow::Workflow;
 use jsonrpsee::server::ServerHandle;
 use libipld::Cid;
 use metrics_exporter_prometheus::PrometheusHandle;
 #[cfg(not(test))]
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::{collections::HashMap, ops::ControlFlow, rc::Rc, sync::Arc, task::Poll};
+use std::{collections::HashMap, ops::ControlFlow, rc::Rc, sync::Arc, task::Poll, time::Duration};
 #[cfg(not(windows))]
 use tokio::signal::unix::{signal, SignalKind};
 #[cfg(windows)]
@@ -41,6 +47,7 @@ use tokio::{
 };
 use tokio_util::time::{delay_queue, DelayQueue};
 use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
+use url::Url;
 
 mod error;
 pub(crate) mod file;
@@ -112,10 +119,13 @@ pub struct Runner {
     event_sender: Arc<AsyncChannelSender<Event>>,
     expiration_queue: Rc<AtomicRefCell<DelayQueue<Cid>>>,
     node_info: StaticNodeInfo,
+    prune_handle: AtomicRefCell<Option<JoinHandle<()>>>,
+    receipt_signer: Arc<ReceiptSigner>,
     running_tasks: Arc<RunningTaskSet>,
     running_workers: RunningWorkerSet,
     pub(crate) runtime: tokio::runtime::Runtime,
     pub(crate) settings: Arc<Settings>,
+    wasm_runtime: WasmRuntime,
     webserver: Arc<webserver::Server>,
 }
 
@@ -172,22 +182,41 @@ impl Runner {
         db: impl Database + 'static,
         runtime: tokio::runtime::Runtime,
     ) -> Result<Self> {
-        let swarm = runtime.block_on(swarm::new(settings.node().network()))?;
+        let keypair = settings
+            .node()
+            .network()
+            .keypair_config
+            .keypair()
+            .with_context(|| "failed to generate/import keypair for libp2p".to_string())?;
+        // Receipts are issued by, and signed with, the node's identity.
+        let receipt_signer = ReceiptSigner::new(keypair.clone())?;
+        let swarm = runtime.block_on(swarm::new(settings.node().network(), keypair))?;
         let peer_id = *swarm.local_peer_id();
 
         let webserver = webserver::Server::new(settings.node().network().webserver())?;
+        let wasm_runtime = WasmRuntime::new(
+            settings.node().wasm().component_cache_capacity,
+            settings.node().wasm().precompile_dir(),
+        )?;
 
         #[cfg(feature = "websocket-notify")]
-        let (ws_msg_tx, ws_evt_tx) = {
+        let (ws_msg_tx, ws_evt_tx, ws_log_tx) = {
             let ws_msg_tx = webserver.workflow_msg_notifier();
             let ws_evt_tx = webserver.evt_notifier();
+            let ws_log_tx = webserver.task_log_notifier();
 
-            (ws_msg_tx, ws_evt_tx)
+            (ws_msg_tx, ws_evt_tx, ws_log_tx)
         };
 
         #[cfg(feature = "websocket-notify")]
-        let event_handler =
-            EventHandler::new(swarm, db, settings.node().network(), ws_evt_tx, ws_msg_tx);
+        let event_handler = EventHandler::new(
+            swarm,
+            db,
+            settings.node().network(),
+            ws_evt_tx,
+            ws_msg_tx,
+            ws_log_tx,
+        );
         #[cfg(not(feature = "websocket-notify"))]
         let event_handler = EventHandler::new(swarm, db, settings.node().network());
 
@@ -206,10 +235,13 @@ impl Runner {
             event_sender,
             expiration_queue: Rc::new(AtomicRefCell::new(DelayQueue::new())),
             node_info: StaticNodeInfo::new(peer_id),
+            prune_handle: AtomicRefCell::new(None),
+            receipt_signer: receipt_signer.into(),
             running_tasks: DashMap::new().into(),
             running_workers: DashMap::new(),
             runtime,
             settings: settings.into(),
+            wasm_runtime,
             webserver: webserver.into(),
         })
     }
@@ -246,6 +278,26 @@ impl Runner {
         let rpc_sender = rpc_server.sender();
         self.runtime.block_on(rpc_server.spawn())?;
 
+        if self.settings.node.resume_workflows {
+            match self
+                .runtime
+                .block_on(self.resume_workflows(runner_worker_tx.clone(), db.clone()))
+            {
+                Ok(resumed) => info!(
+                    subject = "workflow.resume",
+                    category = "workflow",
+                    "resumed {} interrupted workflow(s)",
+                    resumed.len()
+                ),
+                Err(err) => error!(
+                    subject = "workflow.resume.err",
+                    category = "workflow",
+                    err=?err,
+                    "failed to resume interrupted workflows"
+                ),
+            }
+        }
+
         let shutdown_time_left = self.runtime.block_on(async {
             let mut gc_interval = tokio::time::interval(self.settings.node.gc_interval);
             loop {
@@ -253,17 +305,46 @@ impl Runner {
                     // Handle RPC messages.
                     Ok((rpc_message, Some(oneshot_tx))) = rpc_rx.recv_async() => {
                         let now = time::Instant::now();
-                        let handle = self.handle_command_message(
-                            rpc_message,
-                            Channels {
-                                rpc: rpc_sender.clone(),
-                                runner: runner_worker_tx.clone(),
-                            },
-                            ws_hdl.clone(),
-                            db.clone(),
-                            self.settings.node.network().libp2p().dht(),
-                            now
-                        ).await;
+                        let handle = match rpc_message {
+                            // Described on a spawned task, replying once done.
+                            rpc::ServerMessage::DescribeModule(cid) => {
+                                info!(
+                                    subject = "rpc.command",
+                                    category = "rpc",
+                                    "RPC describe module command received, describing module"
+                                );
+                                let describe = self.describe_module(cid, self.settings.node.network.rpc.server_timeout);
+                                self.runtime.spawn(async move {
+                                    match describe.await {
+                                        Ok(ack) => {
+                                            debug!(subject = "rpc.ack",
+                                                   category = "rpc",
+                                                   "sending describe_module message to rpc server");
+                                            let _ = oneshot_tx.send_async(rpc::ServerMessage::DescribeModuleAck(ack)).await;
+                                        }
+                                        Err(err) => {
+                                            error!(subject = "rpc.err",
+                                                   category = "rpc",
+                                                   err=?err,
+                                                   "error handling rpc message");
+                                            let _ = oneshot_tx.send_async(rpc::ServerMessage::RunErr(err)).await;
+                                        }
+                                    }
+                                });
+                                continue;
+                            }
+                            rpc_message => self.handle_command_message(
+                                rpc_message,
+                                Channels {
+                                    rpc: rpc_sender.clone(),
+                                    runner: runner_worker_tx.clone(),
+                                },
+                                ws_hdl.clone(),
+                                db.clone(),
+                                &self.settings.node,
+                                now
+                            ).await,
+                        };
 
 
                         match handle {
@@ -281,6 +362,20 @@ impl Runner {
                                        "sending workflow_run message to rpc server");
                                 let _ = oneshot_tx.send_async(msg).await;
                             },
+                            Ok(ControlFlow::Continue(msg @ rpc::ServerMessage::CancelAck(_))) => {
+                                debug!(subject = "rpc.ack",
+                                       category = "rpc",
+                                       "sending workflow_cancel message to rpc server");
+                                let _ = oneshot_tx.send_async(msg).await;
+                            },
+                            Ok(ControlFlow::Continue(msg @ (rpc::ServerMessage::ListWorkflowsAck(_)
+                                | rpc::ServerMessage::ShowWorkflowAck(_)
+                                | rpc::ServerMessage::WorkflowReceiptsAck(_)))) => {
+                                debug!(subject = "rpc.ack",
+                                       category = "rpc",
+                                       "sending workflow query message to rpc server");
+                                let _ = oneshot_tx.send_async(msg).await;
+                            },
                             Err(err) => {
                                 error!(subject = "rpc.err",
                                        category = "rpc",
@@ -293,16 +388,17 @@ impl Runner {
                     }
                     Ok(msg) = ws_receiver.recv_async() => {
                         match msg {
-                            (webserver::Message::RunWorkflow((name, workflow)), Some(oneshot_tx)) => {
+                            (webserver::Message::RunWorkflow((name, workflow, workflow_settings, ucans)), Some(oneshot_tx)) => {
                                 info!(subject = "workflow",
                                       category = "workflow.run",
                                       "running workflow: {}", name);
-                                // TODO: Parse this from the workflow data itself.
-                                let workflow_settings = workflow::Settings::default();
                                 match self.run_worker(
                                     workflow,
-                                    workflow_settings,
-                                    self.settings.node.network().libp2p().dht(),
+                                    RunSettings {
+                                        requested: workflow_settings,
+                                        ucans: &ucans,
+                                        node: &self.settings.node,
+                                    },
                                     Some(name),
                                     runner_worker_tx.clone(),
                                     db.clone(),
@@ -323,6 +419,50 @@ impl Runner {
                                 }
 
                             }
+                            (webserver::Message::CancelWorkflow(workflow), Some(oneshot_tx)) => {
+                                info!(subject = "workflow",
+                                      category = "workflow.cancel",
+                                      "cancelling workflow: {}", workflow);
+                                match self.cancel_workflow(workflow.as_str(), db.clone()) {
+                                    Ok(ack) => {
+                                        debug!(subject = "jsonrpc.ack",
+                                               category = "jsonrpc",
+                                               "sending message to jsonrpc server");
+                                        let _ = oneshot_tx.send_async(webserver::Message::AckCancelWorkflow(ack)).await;
+                                    }
+                                    Err(err) => {
+                                        error!(subject = "jsonrpc.err",
+                                               category = "jsonrpc",
+                                               err=?err,
+                                               "error handling ws message");
+                                        let _ = oneshot_tx.send_async(webserver::Message::RunErr(err)).await;
+                                    }
+                                }
+                            }
+                            (webserver::Message::DescribeModule(cid), Some(oneshot_tx)) => {
+                                info!(subject = "module",
+                                      category = "module.describe",
+                                      "describing module: {}", cid);
+                                // Described on a spawned task, replying once done.
+                                let describe = self.describe_module(cid, self.settings.node.network.webserver.timeout);
+                                self.runtime.spawn(async move {
+                                    match describe.await {
+                                        Ok(ack) => {
+                                            debug!(subject = "jsonrpc.ack",
+                                                   category = "jsonrpc",
+                                                   "sending message to jsonrpc server");
+                                            let _ = oneshot_tx.send_async(webserver::Message::AckDescribeModule(ack)).await;
+                                        }
+                                        Err(err) => {
+                                            error!(subject = "jsonrpc.err",
+                                                   category = "jsonrpc",
+                                                   err=?err,
+                                                   "error handling ws message");
+                                            let _ = oneshot_tx.send_async(webserver::Message::RunErr(err)).await;
+                                        }
+                                    }
+                                });
+                            }
                             (webserver::Message::GetNodeInfo, Some(oneshot_tx)) => {
                                 debug!(subject = "jsonrpc.nodeinfo",
                                        category = "jsonrpc",
@@ -351,6 +491,7 @@ impl Runner {
                     // Handle GC interval tick.
                     _ = gc_interval.tick() => {
                         let _ = self.gc();
+                        self.prune(db.clone());
                     },
                     // Handle expired workflows.
                     Some(expired) = poll_fn(
@@ -443,6 +584,55 @@ impl Runner {
         Ok(())
     }
 
+    /// Prune stored receipts and workflows according to the node's
+    /// [Retention] policy, off the runner's thread.
+    ///
+    /// Skipped while the previous prune is still running, so prunes of a
+    /// large database don't pile up on the database's write lock.
+    ///
+    /// [Retention]: settings::Retention
+    fn prune<DB: Database + 'static>(&self, db: DB) {
+        let retention = self.settings.node.db.retention.clone();
+        if !retention.is_enabled() {
+            return;
+        }
+
+        let mut prune_handle = self.prune_handle.borrow_mut();
+        if prune_handle
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+        {
+            debug!(
+                subject = "db.prune",
+                category = "db.prune",
+                "previous prune still running, skipping"
+            );
+            return;
+        }
+
+        *prune_handle = Some(self.runtime.spawn_blocking(move || {
+            let pruned = db.conn().and_then(|mut conn| {
+                prune::prune::<DB>(&retention, Utc::now().naive_utc(), false, &mut conn)
+            });
+            match pruned {
+                Ok(pruned) => info!(
+                    subject = "db.prune",
+                    category = "db.prune",
+                    "pruned {} receipt(s), {} workflow(s) and {} block(s)",
+                    pruned.receipts.len(),
+                    pruned.workflows.len(),
+                    pruned.blocks.len()
+                ),
+                Err(err) => error!(
+                    subject = "db.prune.err",
+                    category = "db.prune",
+                    err=?err,
+                    "failed to prune database"
+                ),
+            }
+        }));
+    }
+
     /// Abort and gc/cleanup all workers and tasks.
     #[allow(dead_code)]
     fn abort_and_cleanup_workers(&self) -> Result<()> {
@@ -509,6 +699,187 @@ impl Runner {
         Ok(())
     }
 
+    /// Cancel a running workflow, given its Cid or local name.
+    ///
+    /// Aborts the workflow's worker and in-flight tasks, marks the workflow
+    /// as cancelled, and notifies its subscribers.
+    fn cancel_workflow(
+        &self,
+        workflow: &str,
+        db: impl Database + 'static,
+    ) -> Result<response::AckCancel, Error> {
+        let conn = &mut db.conn()?;
+        let stored = find_workflow(workflow, conn)?;
+        let workflow_cid = stored.cid.cid();
+
+        // Finished workers are only removed on the next gc tick, so check the
+        // stored status and the handle itself too.
+        let running = matches!(
+            stored.status,
+            workflow::Status::Pending | workflow::Status::Running
+        ) && self
+            .running_workers
+            .get(&workflow_cid)
+            .is_some_and(|worker| !worker.0.is_finished());
+
+        if !running {
+            return Err(Error::WorkflowNotRunning(workflow_cid));
+        }
+
+        self.abort_worker(workflow_cid)?;
+        Db::set_workflow_status(workflow_cid, workflow::Status::Cancelled, conn)
+            .map_err(anyhow::Error::from)?;
+
+        #[cfg(feature = "websocket-notify")]
+        notification::emit_workflow_status(
+            self.webserver.workflow_msg_notifier(),
+            workflow_cid,
+            workflow::Status::Cancelled,
+        );
+
+        info!(
+            subject = "worker.cancel",
+            category = "worker",
+            workflow_cid = workflow_cid.to_string(),
+            "workflow cancelled"
+        );
+
+        Ok(response::AckCancel::new(workflow_cid, stored.name))
+    }
+
+    /// Describe the exported functions of a Wasm module, given its Cid, if
+    /// the node's module policy allows it.
+    ///
+    /// Fetching and compiling the module can take a while, so the returned
+    /// future is spawned off the runner's message loop, and gives up after
+    /// `timeout`.
+    fn describe_module(
+        &self,
+        cid: Cid,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<response::AckModule, Error>> + Send + 'static {
+        let settings = Arc::clone(&self.settings);
+        let wasm_runtime = self.wasm_runtime.clone();
+
+        async move {
+            time::timeout(timeout, async move {
+                let rsc = Url::parse(&format!("ipfs://{cid}")).map_err(anyhow::Error::from)?;
+                LoadedModulePolicy::load(&settings.node.wasm.module_policy)
+                    .await
+                    .check(&rsc)
+                    .map_err(|denial| Error::ModuleNotAllowed {
+                        module: cid,
+                        reason: denial.to_string(),
+                    })?;
+
+                let bytes = fetch_module(&settings.node, cid).await?;
+                let description = wasm_runtime
+                    .describe(cid, &bytes)
+                    .await
+                    .map_err(anyhow::Error::from)?;
+                Ok::<_, Error>(response::AckModule::new(description))
+            })
+            .await
+            .map_err(|_| anyhow!("timed out describing module {cid} after {timeout:?}"))?
+        }
+    }
+
+    /// Resume workflows left pending or running, e.g. after a crash or
+    /// an unclean shutdown, from their stored DAG-CBOR.
+    ///
+    /// Already-receipted instructions are skipped by the [TaskScheduler]
+    /// on initialization. Returns the Cids of the resumed workflows.
+    ///
+    /// [TaskScheduler]: crate::TaskScheduler
+    async fn resume_workflows(
+        &self,
+        runner_sender: AsyncChannelSender<WorkerMessage>,
+        db: impl Database + 'static,
+    ) -> Result<Vec<Cid>> {
+        let interrupted = Db::select_interrupted_workflows(&mut db.conn()?)?;
+        let mut resumed = Vec::with_capacity(interrupted.len());
+
+        for (stored, data, settings) in interrupted {
+            let workflow_cid = stored.cid.cid();
+            let resumable = data
+                .ok_or_else(|| anyhow!("workflow not stored"))
+                .and_then(|data| {
+                    Workflow::<Arg>::from_cbor(&data).context("failed to decode stored workflow")
+                })
+                // Settings requested on the first run, if any were stored.
+                .and_then(|workflow| {
+                    settings
+                        .map(|settings| workflow::RequestedSettings::from_cbor(&settings))
+                        .transpose()
+                        .context("failed to decode stored workflow settings")
+                        .map(|settings| (workflow, settings.unwrap_or_default()))
+                });
+
+            // Workflows that can't be resumed would otherwise be left
+            // running for good.
+            let (workflow, workflow_settings) = match resumable {
+                Ok(resumable) => resumable,
+                Err(err) => {
+                    warn!(
+                        subject = "workflow.resume",
+                        category = "workflow",
+                        cid = workflow_cid.to_string(),
+                        err=?err,
+                        "cannot resume workflow, marking it as failed"
+                    );
+                    Db::set_workflow_status(
+                        workflow_cid,
+                        workflow::Status::Failed,
+                        &mut db.conn()?,
+                    )?;
+                    continue;
+                }
+            };
+
+            info!(
+                subject = "workflow.resume",
+                category = "workflow",
+                cid = workflow_cid.to_string(),
+                "resuming interrupted workflow"
+            );
+
+            match self
+                .run_worker(
+                    workflow,
+                    RunSettings {
+                        requested: workflow_settings,
+                        ucans: &[],
+                        node: &self.settings.node,
+                    },
+                    stored.name,
+                    runner_sender.clone(),
+                    db.clone(),
+                )
+                .await
+            {
+                Ok(data) => resumed.push(data.info.cid),
+                // E.g. a UCAN proving a task has expired since the first run,
+                // which would otherwise fail again on every start.
+                Err(err) => {
+                    error!(
+                        subject = "workflow.resume.err",
+                        category = "workflow",
+                        cid = workflow_cid.to_string(),
+                        err=?err,
+                        "failed to resume workflow, marking it as failed"
+                    );
+                    Db::set_workflow_status(
+                        workflow_cid,
+                        workflow::Status::Failed,
+                        &mut db.conn()?,
+                    )?;
+                }
+            }
+        }
+
+        Ok(resumed)
+    }
+
     /// Abort a specific worker's tasks given a Cid.
     fn abort_worker_tasks(&self, cid: Cid) {
         if let Some((_cid, handles)) = self.running_tasks.remove(&cid) {
@@ -614,7 +985,7 @@ impl Runner {
         channels: Channels,
         ws_hdl: ServerHandle,
         db: impl Database + 'static,
-        network_settings: &settings::Dht,
+        node_settings: &settings::Node,
         now: time::Instant,
     ) -> Result<ControlFlow<(), rpc::ServerMessage>> {
         match msg {
@@ -665,7 +1036,7 @@ impl Runner {
                     category = "rpc",
                     "RPC run command received, running workflow"
                 );
-                let (workflow, workflow_settings) =
+                let (workflow, workflow_settings, ucans) =
                     workflow_file.validate_and_parse().await.with_context(|| {
                         format!("failed to validate/parse workflow @ path: {workflow_file}",)
                     })?;
@@ -673,8 +1044,11 @@ impl Runner {
                 let data = self
                     .run_worker(
                         workflow,
-                        workflow_settings,
-                        network_settings,
+                        RunSettings {
+                            requested: workflow_settings,
+                            ucans: &ucans,
+                            node: node_settings,
+                        },
                         name,
                         channels.runner,
                         db.clone(),
@@ -690,6 +1064,81 @@ impl Runner {
                     ),
                 ))))
             }
+            rpc::ServerMessage::Cancel(workflow) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC cancel command received, cancelling workflow"
+                );
+                let ack = self.cancel_workflow(&workflow, db)?;
+                Ok(ControlFlow::Continue(rpc::ServerMessage::CancelAck(ack)))
+            }
+            rpc::ServerMessage::ListWorkflows(filter) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC workflow list command received, listing workflows"
+                );
+                let conn = &mut db.conn()?;
+                let (since, until) = (filter.created_since(), filter.created_until());
+                let stored = Db::select_workflows(
+                    filter.status,
+                    filter.name.as_deref(),
+                    since,
+                    until,
+                    filter.limit,
+                    conn,
+                )?;
+                let pointers: Vec<Pointer> =
+                    stored.iter().map(|stored| stored.cid.clone()).collect();
+                let counts = Db::count_workflows_receipts(&pointers, conn)?;
+                let workflows = stored
+                    .iter()
+                    .map(|stored| {
+                        let count = counts.get(&stored.cid.cid()).copied().unwrap_or_default();
+                        response::WorkflowSummary::new(stored, count)
+                    })
+                    .collect();
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::ListWorkflowsAck(
+                    response::AckWorkflowList::new(workflows),
+                )))
+            }
+            rpc::ServerMessage::ShowWorkflow(workflow) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC workflow show command received, showing workflow"
+                );
+                let conn = &mut db.conn()?;
+                let stored = find_workflow(&workflow, conn)?;
+                let (_, info) = Db::get_workflow_info(stored.cid.cid(), conn)?;
+
+                Ok(ControlFlow::Continue(rpc::ServerMessage::ShowWorkflowAck(
+                    Box::new(response::AckWorkflowDetail::new(info, stored)),
+                )))
+            }
+            rpc::ServerMessage::WorkflowReceipts(cid) => {
+                info!(
+                    subject = "rpc.command",
+                    category = "rpc",
+                    "RPC workflow receipts command received, finding receipts"
+                );
+                let conn = &mut db.conn()?;
+                let (_, info) = Db::get_workflow_info(cid, conn)
+                    .with_context(|| format!("workflow {cid} not found"))?;
+                let pointers = info.progress.into_iter().map(Pointer::new).collect();
+                let receipts = Db::find_receipt_pointers(&pointers, conn)?
+                    .iter()
+                    .map(response::ReceiptSummary::from)
+                    .collect();
+
+                Ok(ControlFlow::Continue(
+                    rpc::ServerMessage::WorkflowReceiptsAck(response::AckWorkflowReceipts::new(
+                        cid, receipts,
+                    )),
+                ))
+            }
             msg => {
                 warn!(
                     subject = "rpc.command",
@@ -706,17 +1155,36 @@ impl Runner {
     async fn run_worker<S: Into<FastStr>>(
         &self,
         workflow: Workflow<'static, Arg>,
-        workflow_settings: workflow::Settings,
-        network_settings: &settings::Dht,
+        run_settings: RunSettings<'_>,
         name: Option<S>,
         runner_sender: AsyncChannelSender<WorkerMessage>,
         db: impl Database + 'static,
     ) -> Result<WorkflowData> {
+        let RunSettings {
+            requested: workflow_settings,
+            ucans,
+            node: node_settings,
+        } = run_settings;
+        workflow::authorization::authorize(
+            &workflow,
+            ucans,
+            node_settings.ucan(),
+            &self.receipt_signer.issuer().to_string(),
+            &db,
+        )
+        .await?;
+        let requested_settings = workflow_settings.clone().to_cbor()?;
+        let workflow_settings =
+            workflow::Settings::resolve(&workflow_settings, node_settings.workflow_limits())?;
         let worker = {
             Worker::new(
                 workflow,
                 workflow_settings,
-                network_settings.clone().to_owned(),
+                WorkerNode {
+                    settings: node_settings,
+                    wasm_runtime: self.wasm_runtime.clone(),
+                    receipt_signer: self.receipt_signer.clone(),
+                },
                 name,
                 self.event_sender(),
                 runner_sender,
@@ -725,6 +1193,14 @@ impl Runner {
             .await?
         };
 
+        // Keep the requested settings around, so the workflow is resumed
+        // with them if interrupted.
+        Db::store_workflow_settings(
+            worker.workflow_info.cid,
+            &requested_settings,
+            &mut db.conn()?,
+        )?;
+
         // Deliberate use of Arc::clone for readability, could just be
         // `clone`, as the underlying type is an `Arc`.
         let initial_info = Arc::clone(&worker.workflow_info);
@@ -816,6 +1292,37 @@ impl Runner {
     }
 }
 
+/// Fetch a Wasm module over IPFS, given its Cid.
+#[cfg(feature = "ipfs")]
+async fn fetch_module(settings: &settings::Node, cid: Cid) -> Result<Vec<u8>> {
+    IpfsCli::new(settings.network.ipfs())?
+        .get_cid(cid)
+        .await
+        .with_context(|| format!("failed to fetch module {cid}"))
+}
+
+/// Fetch a Wasm module, given its Cid, which requires IPFS.
+#[cfg(not(feature = "ipfs"))]
+async fn fetch_module(_settings: &settings::Node, cid: Cid) -> Result<Vec<u8>> {
+    Err(anyhow!(
+        "cannot fetch module {cid} without the ipfs feature enabled"
+    ))
+}
+
+/// Find a stored workflow given its Cid or local name.
+fn find_workflow(workflow: &str, conn: &mut Connection) -> Result<workflow::Stored, Error> {
+    match Cid::try_from(workflow) {
+        Ok(cid) => Db::select_workflow(cid, conn),
+        Err(_) => Db::select_workflow_by_name(workflow, conn),
+    }
+    .map_err(|err| match err {
+        diesel::result::Error::NotFound => Error::WorkflowNotFound(workflow.to_string()),
+        err => anyhow::Error::new(err)
+            .context(format!("failed to find workflow {workflow}"))
+            .into(),
+    })
+}
+
 /// Find receipts given a batch of [Receipt] [Pointer]s, and return them as [WorkflowReceiptInfo]s.
 fn find_receipt_info_by_pointers(
     pointers: &Vec<Pointer>,
@@ -848,6 +1355,15 @@ struct WorkflowData {
     replayed_receipt_info: Vec<WorkflowReceiptInfo>,
 }
 
+/// Settings a workflow is run with, as requested, and the UCANs and node
+/// settings its tasks are authorized and limited by.
+#[derive(Debug)]
+struct RunSettings<'a> {
+    requested: workflow::RequestedSettings,
+    ucans: &'a [String],
+    node: &'a settings::Node,
+}
+
 /// Channels for sending messages to/from the RPC server and the runner.
 #[derive(Debug)]
 struct Channels {
@@ -982,6 +1498,241 @@ mod test {
         });
     }
 
+    #[homestar_runtime_proc_macro::runner_test]
+    fn cancel_running_workflow() {
+        let TestRunner { runner, settings } = TestRunner::start();
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+
+        let workflow_cid = runner.runtime.block_on(async {
+            let fetch_fn = builder.fetch_fn();
+            let worker = builder.build().await;
+            let workflow_cid = worker.workflow_info.cid;
+            let workflow_timeout = worker.workflow_settings.timeout;
+            let handle = runner
+                .runtime
+                .spawn(worker.run(runner.running_tasks(), fetch_fn));
+            let delay_key = runner
+                .expiration_queue
+                .try_borrow_mut()
+                .unwrap()
+                .insert(workflow_cid, workflow_timeout);
+            runner
+                .running_workers
+                .insert(workflow_cid, (handle, delay_key));
+            workflow_cid
+        });
+
+        let ack = runner
+            .cancel_workflow(&workflow_cid.to_string(), db.clone())
+            .unwrap();
+        assert_eq!(ack.cid, workflow_cid);
+        assert_eq!(ack.status, workflow::Status::Cancelled);
+        assert!(runner.running_workers.is_empty());
+        assert!(!runner.running_tasks.contains_key(&workflow_cid));
+
+        let stored = MemoryDb::select_workflow(workflow_cid, &mut db.conn().unwrap()).unwrap();
+        assert_eq!(stored.status, workflow::Status::Cancelled);
+
+        // Cancelling a workflow that is no longer running fails.
+        assert!(matches!(
+            runner.cancel_workflow(&workflow_cid.to_string(), db.clone()),
+            Err(Error::WorkflowNotRunning(cid)) if cid == workflow_cid
+        ));
+        assert!(matches!(
+            runner.cancel_workflow("unknown", db),
+            Err(Error::WorkflowNotFound(workflow)) if workflow == "unknown"
+        ));
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn cancel_completed_workflow_before_gc() {
+        let TestRunner { runner, settings } = TestRunner::start();
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+
+        let workflow_cid = runner.runtime.block_on(async {
+            let worker = builder.build().await;
+            let workflow_cid = worker.workflow_info.cid;
+            let workflow_timeout = worker.workflow_settings.timeout;
+
+            // The worker has finished, but gc hasn't removed it yet.
+            let handle = runner.runtime.spawn(async { Ok(()) });
+            while !handle.is_finished() {
+                tokio::task::yield_now().await;
+            }
+            let delay_key = runner
+                .expiration_queue
+                .try_borrow_mut()
+                .unwrap()
+                .insert(workflow_cid, workflow_timeout);
+            runner
+                .running_workers
+                .insert(workflow_cid, (handle, delay_key));
+            workflow_cid
+        });
+
+        MemoryDb::set_workflow_status(
+            workflow_cid,
+            workflow::Status::Completed,
+            &mut db.conn().unwrap(),
+        )
+        .unwrap();
+
+        assert!(runner
+            .cancel_workflow(&workflow_cid.to_string(), db.clone())
+            .is_err());
+        assert_eq!(
+            MemoryDb::select_workflow(workflow_cid, &mut db.conn().unwrap())
+                .unwrap()
+                .status,
+            workflow::Status::Completed
+        );
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn prune_skipped_while_running() {
+        let TestRunner {
+            mut runner,
+            settings,
+        } = TestRunner::start();
+        let db = WorkerBuilder::new(settings.node).db();
+        let mut settings = runner.settings.as_ref().clone();
+        settings.node.db.retention.terminal_only = true;
+        runner.settings = Arc::new(settings);
+
+        // A previous prune is still running.
+        let running = runner.runtime.spawn(futures::future::pending());
+        *runner.prune_handle.borrow_mut() = Some(running);
+        runner.prune(db.clone());
+        let handle = runner.prune_handle.borrow_mut().take().unwrap();
+        handle.abort();
+        assert!(runner.runtime.block_on(handle).unwrap_err().is_cancelled());
+
+        // Once it has finished, the next prune runs.
+        runner.prune(db);
+        let handle = runner.prune_handle.borrow_mut().take().unwrap();
+        assert!(runner.runtime.block_on(handle).is_ok());
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn describe_disallowed_module() {
+        let TestRunner {
+            mut runner,
+            settings: _,
+        } = TestRunner::start();
+        let mut settings = runner.settings.as_ref().clone();
+        settings.node.wasm.module_policy = settings::ModulePolicy::Allowlist { modules: vec![] };
+        runner.settings = Arc::new(settings);
+
+        let cid =
+            Cid::try_from("bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q").unwrap();
+        let described = runner
+            .runtime
+            .block_on(runner.describe_module(cid, Duration::from_secs(5)));
+        assert!(matches!(
+            described,
+            Err(Error::ModuleNotAllowed { module, .. }) if module == cid
+        ));
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn resume_interrupted_workflows() {
+        let TestRunner { runner, settings } = TestRunner::start();
+        let (runner_tx, _runner_rx) = Runner::setup_worker_channel(1);
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+
+        // Worker is built (and its workflow stored), but never run.
+        let workflow_cid = runner.runtime.block_on(async {
+            let worker = builder.build().await;
+            worker.workflow_info.cid
+        });
+
+        // Settings requested on the first run are restored on resume.
+        let requested = workflow::RequestedSettings {
+            timeout: Some(Duration::from_secs(42)),
+            ..Default::default()
+        };
+        MemoryDb::store_workflow_settings(
+            workflow_cid,
+            &requested.to_cbor().unwrap(),
+            &mut db.conn().unwrap(),
+        )
+        .unwrap();
+
+        // Workflows stored without their DAG-CBOR can't be resumed, and are
+        // marked as failed.
+        let unstored_cid = homestar_invocation::test_utils::cid::generate_cid(&mut thread_rng());
+        MemoryDb::store_workflow(
+            workflow::Stored::default(Pointer::new(unstored_cid), 1),
+            &mut db.conn().unwrap(),
+        )
+        .unwrap();
+
+        let resumed = runner
+            .runtime
+            .block_on(runner.resume_workflows(runner_tx, db.clone()))
+            .unwrap();
+        assert_eq!(resumed, vec![workflow_cid]);
+        assert!(runner.running_workers.contains_key(&workflow_cid));
+        assert!(!runner.running_workers.contains_key(&unstored_cid));
+        assert_eq!(
+            MemoryDb::select_workflow(unstored_cid, &mut db.conn().unwrap())
+                .unwrap()
+                .status,
+            workflow::Status::Failed
+        );
+
+        let deadline = runner
+            .expiration_queue
+            .borrow_mut()
+            .try_remove(&runner.running_workers.get(&workflow_cid).unwrap().1)
+            .unwrap()
+            .deadline();
+        // Deadlines are rounded up to the timer's millisecond granularity.
+        assert!(deadline <= time::Instant::now() + Duration::from_millis(42_001));
+        assert!(deadline > time::Instant::now() + Duration::from_secs(30));
+
+        runner.abort_and_cleanup_workers().unwrap();
+    }
+
+    #[homestar_runtime_proc_macro::runner_test]
+    fn resume_unauthorized_workflow() {
+        let TestRunner {
+            mut runner,
+            settings,
+        } = TestRunner::start();
+        let (runner_tx, _runner_rx) = Runner::setup_worker_channel(1);
+        let builder = WorkerBuilder::new(settings.node);
+        let db = builder.db();
+
+        // Worker is built (and its workflow stored), but never run.
+        let workflow_cid = runner.runtime.block_on(async {
+            let worker = builder.build().await;
+            worker.workflow_info.cid
+        });
+
+        // Its tasks aren't authorized once UCANs are enforced, e.g. as a
+        // UCAN they were proved by has since expired.
+        let mut enforced = runner.settings.as_ref().clone();
+        enforced.node.ucan.enforce = true;
+        runner.settings = Arc::new(enforced);
+
+        let resumed = runner
+            .runtime
+            .block_on(runner.resume_workflows(runner_tx, db.clone()))
+            .unwrap();
+        assert!(resumed.is_empty());
+        assert!(!runner.running_workers.contains_key(&workflow_cid));
+        assert_eq!(
+            MemoryDb::select_workflow(workflow_cid, &mut db.conn().unwrap())
+                .unwrap()
+                .status,
+            workflow::Status::Failed
+        );
+    }
+
     #[homestar_runtime_proc_macro::runner_test]
     fn abort_and_cleanup_all_workers() {
         let TestRunner { runner, settings } = TestRunner::start();
@@ -2,6 +2,7 @@
 //!
 //! [Runner]: crate::Runner
 
+use libipld::Cid;
 use std::io;
 
 /// Error types related to running [Workflow]s and other runtime
@@ -13,6 +14,20 @@ pub(crate) enum Error {
     /// Unsupported workflow type.
     #[error("unsupported workflow file type: {0}")]
     UnsupportedWorkflow(String),
+    /// No workflow stored with the given Cid or local name.
+    #[error("workflow {0} not found")]
+    WorkflowNotFound(String),
+    /// Workflow cannot be cancelled, as it's not running.
+    #[error("workflow {0} is not running")]
+    WorkflowNotRunning(Cid),
+    /// Wasm module not allowed by the node's module policy.
+    #[error("module {module} is not allowed: {reason}")]
+    ModuleNotAllowed {
+        /// Cid of the denied module.
+        module: Cid,
+        /// Reason the module was denied.
+        reason: String,
+    },
     /// Propagated IO error.
     #[error("error reading data: {0}")]
     Io(#[from] io::Error),
@@ -4,13 +4,17 @@
 
 use super::Error;
 use crate::workflow;
-use homestar_invocation::ipld::DagJson;
 use homestar_wasm::io::Arg;
 use homestar_workflow::Workflow;
+use libipld::{cbor::DagCborCodec, json::DagJsonCodec, prelude::Codec, serde::from_ipld, Ipld};
 use serde::{Deserialize, Serialize};
 use std::{ffi::OsStr, fmt, path::PathBuf, str::FromStr};
 use tokio::fs;
 
+/// Key of the optional list of encoded UCANs in a workflow file, linked from
+/// the proofs of the workflow's tasks.
+const UCANS_KEY: &str = "ucans";
+
 /// Data structure for a workflow file path.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadWorkflow {
@@ -35,26 +39,51 @@ impl fmt::Display for ReadWorkflow {
 }
 
 impl ReadWorkflow {
-    /// Validate and parse the workflow file.
+    /// Validate and parse the workflow file, along with its optional
+    /// `settings` block and `ucans` list of encoded UCANs.
     ///
-    /// Validation is currently limited to checking the file extension,
-    /// or attempting to treat the file as JSON if no extension is provided.
+    /// The format is determined by the file extension:
+    ///   * `json` or `dag-json` for DAG-JSON (also tried if no extension is
+    ///     provided);
+    ///   * `cbor` or `dagcbor` for DAG-CBOR;
+    ///   * `yaml` or `yml` for YAML, which follows the same structure as
+    ///     DAG-JSON, e.g. links as `"/": <cid>`.
     pub(crate) async fn validate_and_parse<'a>(
         &self,
-    ) -> Result<(Workflow<'a, Arg>, workflow::Settings), Error> {
-        match self.file.extension().and_then(OsStr::to_str) {
-            None | Some("json") => {
+    ) -> Result<(Workflow<'a, Arg>, workflow::RequestedSettings, Vec<String>), Error> {
+        let ipld: Ipld = match self.file.extension().and_then(OsStr::to_str) {
+            None | Some("json") | Some("dag-json") => {
+                let data = fs::read(&self.file.canonicalize()?).await?;
+                DagJsonCodec.decode(&data)?
+            }
+            Some("cbor") | Some("dagcbor") => {
+                let data = fs::read(&self.file.canonicalize()?).await?;
+                DagCborCodec.decode(&data)?
+            }
+            Some("yaml") | Some("yml") => {
                 let data = fs::read_to_string(&self.file.canonicalize()?).await?;
-                // TODO: Parse this from the workflow data/file itself.
-                let workflow_settings = workflow::Settings::default();
-                Ok((
-                    DagJson::from_json_string(data).map_err(anyhow::Error::new)?,
-                    workflow_settings,
-                ))
+                let value: serde_json::Value =
+                    serde_yaml::from_str(&data).map_err(anyhow::Error::new)?;
+                DagJsonCodec.decode(value.to_string().as_bytes())?
             }
+            Some(ext) => return Err(Error::UnsupportedWorkflow(ext.to_string())),
+        };
 
-            Some(ext) => Err(Error::UnsupportedWorkflow(ext.to_string())),
-        }
+        let workflow_settings = workflow::RequestedSettings::from_workflow_ipld(&ipld)?;
+        let ucans = ucans(&ipld)?;
+        let workflow = Workflow::try_from(ipld).map_err(anyhow::Error::new)?;
+        Ok((workflow, workflow_settings, ucans))
+    }
+}
+
+/// Read the optional `ucans` list of a workflow, given as Ipld, next to its
+/// tasks.
+fn ucans(ipld: &Ipld) -> anyhow::Result<Vec<String>> {
+    match ipld {
+        Ipld::Map(map) => map
+            .get(UCANS_KEY)
+            .map_or_else(|| Ok(vec![]), |ucans| Ok(from_ipld(ucans.to_owned())?)),
+        _ => Ok(vec![]),
     }
 }
 
@@ -63,6 +92,7 @@ mod test {
     use super::*;
     use homestar_invocation::{
         authority::UcanPrf,
+        ipld::{DagCbor, DagJson},
         task::{instruction::RunInstruction, Resources},
         test_utils, Task,
     };
@@ -90,7 +120,8 @@ mod test {
         workflow.to_file(path.display().to_string()).unwrap();
         let workflow_file = ReadWorkflow { file: path.clone() };
 
-        let (validated_workflow, _settings) = workflow_file.validate_and_parse().await.unwrap();
+        let (validated_workflow, _settings, _ucans) =
+            workflow_file.validate_and_parse().await.unwrap();
 
         assert_eq!(workflow, validated_workflow);
 
@@ -112,7 +143,7 @@ mod test {
         let workflow_file = ReadWorkflow {
             file: new_path.clone(),
         };
-        let (newly_validated_workflow, _settings) =
+        let (newly_validated_workflow, _settings, _ucans) =
             workflow_file.validate_and_parse().await.unwrap();
         assert_eq!(workflow, newly_validated_workflow);
     }
@@ -134,8 +165,92 @@ mod test {
         workflow.to_file(path.display().to_string()).unwrap();
         let workflow_file = ReadWorkflow { file: path.clone() };
 
-        let (validated_workflow, _settings) = workflow_file.validate_and_parse().await.unwrap();
+        let (validated_workflow, _settings, _ucans) =
+            workflow_file.validate_and_parse().await.unwrap();
 
         assert_eq!(workflow, validated_workflow);
     }
+
+    #[tokio::test]
+    async fn validate_and_parse_workflow_formats() {
+        let config = Resources::default();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1),
+            config.clone().into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2),
+            config.into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![task1, task2]);
+
+        let dag_json = workflow.clone().to_dagjson_string().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&dag_json).unwrap();
+        let files = [
+            (
+                "./fixtures/test_formats.dag-json",
+                dag_json.clone().into_bytes(),
+            ),
+            (
+                "./fixtures/test_formats.cbor",
+                workflow.clone().to_cbor().unwrap(),
+            ),
+            (
+                "./fixtures/test_formats.dagcbor",
+                workflow.clone().to_cbor().unwrap(),
+            ),
+            (
+                "./fixtures/test_formats.yaml",
+                serde_yaml::to_string(&value).unwrap().into_bytes(),
+            ),
+        ];
+
+        for (path, data) in files {
+            fs::write(path, data).await.unwrap();
+            let workflow_file = ReadWorkflow { file: path.into() };
+            let parsed = workflow_file.validate_and_parse().await;
+            fs::remove_file(path).await.unwrap();
+
+            let (parsed_workflow, _settings, _ucans) = parsed.unwrap();
+            assert_eq!(workflow, parsed_workflow, "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_and_parse_workflow_with_settings() {
+        let path = PathBuf::from("./fixtures/test_settings.yaml");
+        let config = Resources::default();
+        let (instruction, _) = test_utils::wasm_instruction_with_nonce::<Arg>();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            config.into(),
+            UcanPrf::default(),
+        );
+        let workflow = Workflow::new(vec![task]);
+
+        let mut value: serde_json::Value =
+            serde_json::from_str(&workflow.to_json_string().unwrap()).unwrap();
+        value["settings"] = serde_json::json!({"retries": 2, "retry_max_delay": 1000});
+        value["ucans"] = serde_json::json!(["eyJhbGciOiJFZERTQSJ9.e30.c2ln"]);
+        fs::write(&path, serde_yaml::to_string(&value).unwrap())
+            .await
+            .unwrap();
+
+        let workflow_file = ReadWorkflow { file: path.clone() };
+        let parsed = workflow_file.validate_and_parse().await;
+        fs::remove_file(path).await.unwrap();
+
+        let (parsed_workflow, settings, ucans) = parsed.unwrap();
+        assert_eq!(workflow, parsed_workflow);
+        assert_eq!(settings.retries, Some(2));
+        assert_eq!(
+            settings.retry_max_delay,
+            Some(std::time::Duration::from_secs(1))
+        );
+        assert_eq!(settings.timeout, None);
+        assert_eq!(ucans, vec!["eyJhbGciOiJFZERTQSJ9.e30.c2ln"]);
+    }
 }
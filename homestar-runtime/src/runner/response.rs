@@ -5,10 +5,14 @@ use crate::{
     cli::show::{self, ApplyStyle},
     runner::WorkflowReceiptInfo,
     workflow::{self, IndexedResources},
+    Receipt,
 };
 use chrono::NaiveDateTime;
 use faststr::FastStr;
+use homestar_invocation::task;
+use homestar_wasm::wasmtime::describe::ModuleDescription;
 use libipld::Cid;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{fmt, net::SocketAddr, sync::Arc};
 use tabled::{
@@ -117,6 +121,297 @@ impl show::ConsoleTable for AckWorkflow {
     }
 }
 
+/// Summary of a workflow, listed by the `workflow list` command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct WorkflowSummary {
+    pub(crate) cid: Cid,
+    pub(crate) name: String,
+    pub(crate) status: workflow::Status,
+    pub(crate) progress: String,
+    pub(crate) created_at: String,
+}
+
+impl WorkflowSummary {
+    /// Summarize a workflow for listing, given its number of receipts.
+    pub(crate) fn new(stored: &workflow::Stored, progress_count: u32) -> Self {
+        Self {
+            cid: stored.cid.cid(),
+            name: stored.name.clone().unwrap_or_default(),
+            status: stored.status.clone(),
+            progress: format!("{}/{}", progress_count, stored.num_tasks),
+            created_at: stored.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+/// Workflows listed for response / display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AckWorkflowList {
+    pub(crate) workflows: Vec<WorkflowSummary>,
+}
+
+impl AckWorkflowList {
+    /// Create a new [AckWorkflowList] response.
+    pub(crate) fn new(workflows: Vec<WorkflowSummary>) -> Self {
+        Self { workflows }
+    }
+}
+
+impl show::ConsoleTable for AckWorkflowList {
+    fn table(&self) -> show::Output {
+        if self.workflows.is_empty() {
+            let mut builder = Builder::default();
+            builder.push_record(["Workflows".to_string()]);
+            builder.push_record(["<none>".to_string()]);
+            builder.build().default_with_title("workflows")
+        } else {
+            Table::new(&self.workflows).default_with_title("workflows")
+        }
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Detailed workflow information, shown by the `workflow show` command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct AckWorkflowDetail {
+    pub(crate) cid: Cid,
+    pub(crate) name: String,
+    pub(crate) status: workflow::Status,
+    pub(crate) num_tasks: u32,
+    pub(crate) progress_count: u32,
+    pub(crate) retries: i32,
+    pub(crate) created_at: String,
+    pub(crate) completed_at: String,
+    #[tabled(skip)]
+    pub(crate) resources: IndexedResources,
+    #[tabled(skip)]
+    pub(crate) progress: Vec<Cid>,
+}
+
+impl AckWorkflowDetail {
+    /// Workflow details for response / display.
+    pub(crate) fn new(info: workflow::Info, stored: workflow::Stored) -> Self {
+        Self {
+            cid: info.cid,
+            name: stored.name.unwrap_or_default(),
+            status: stored.status,
+            num_tasks: info.num_tasks,
+            progress_count: info.progress_count,
+            retries: stored.retries,
+            created_at: stored.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            completed_at: stored
+                .completed_at
+                .map(|timestamp| timestamp.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            resources: info.resources,
+            progress: info.progress,
+        }
+    }
+}
+
+impl show::ConsoleTable for AckWorkflowDetail {
+    fn table(&self) -> show::Output {
+        show::Output::new(Table::new(vec![self]).to_string())
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        let table = self.table();
+
+        let mut resource_table = Table::new(
+            self.resources
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>(),
+        );
+        resource_table
+            .with(Modify::new(Rows::first()).with(Format::content(|_s| "Resources".to_string())));
+
+        let mut progress_table_builder = Builder::default();
+        progress_table_builder.push_record(["Receipts".to_string()]);
+        for cid in &self.progress {
+            progress_table_builder.push_record([cid.to_string()]);
+        }
+        if progress_table_builder.count_records() == 1 {
+            progress_table_builder.push_record(["<none>".to_string()]);
+        }
+        let progress_table = progress_table_builder.build();
+
+        let tbl = col![table, resource_table, progress_table].default_with_title("workflow");
+
+        tbl.echo()
+    }
+}
+
+/// Summary of a receipt, listed by the `workflow receipts` command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct ReceiptSummary {
+    pub(crate) cid: Cid,
+    pub(crate) instruction: Cid,
+    pub(crate) ran: String,
+    pub(crate) output: String,
+}
+
+impl From<&Receipt> for ReceiptSummary {
+    fn from(receipt: &Receipt) -> Self {
+        let output = match receipt.output() {
+            task::Result::Ok(_) => "ok",
+            task::Result::Error(_) => "error",
+            task::Result::Just(_) => "just",
+        };
+
+        Self {
+            cid: receipt.cid(),
+            instruction: receipt.instruction().cid(),
+            ran: receipt.ran(),
+            output: output.to_string(),
+        }
+    }
+}
+
+/// Receipts of a workflow for response / display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AckWorkflowReceipts {
+    pub(crate) cid: Cid,
+    pub(crate) receipts: Vec<ReceiptSummary>,
+}
+
+impl AckWorkflowReceipts {
+    /// Create a new [AckWorkflowReceipts] response.
+    pub(crate) fn new(cid: Cid, receipts: Vec<ReceiptSummary>) -> Self {
+        Self { cid, receipts }
+    }
+}
+
+impl show::ConsoleTable for AckWorkflowReceipts {
+    fn table(&self) -> show::Output {
+        if self.receipts.is_empty() {
+            let mut builder = Builder::default();
+            builder.push_record([format!("Receipts for {}", self.cid)]);
+            builder.push_record(["<none>".to_string()]);
+            builder.build().default_with_title("receipts")
+        } else {
+            Table::new(&self.receipts).default_with_title("receipts")
+        }
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Acknowledgement of a cancelled workflow for response / display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tabled, JsonSchema)]
+#[schemars(rename = "cancel_workflow")]
+pub struct AckCancel {
+    #[schemars(with = "String", description = "Cid of the cancelled workflow")]
+    pub(crate) cid: Cid,
+    #[schemars(description = "Local name of the cancelled workflow")]
+    pub(crate) name: String,
+    #[schemars(description = "Status of the workflow")]
+    pub(crate) status: workflow::Status,
+}
+
+impl fmt::Display for AckCancel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cid: {}, status: {}", self.cid, self.status)
+    }
+}
+
+impl AckCancel {
+    /// Create a new [AckCancel] response.
+    pub(crate) fn new(cid: Cid, name: Option<String>) -> Self {
+        Self {
+            cid,
+            name: name.unwrap_or_default(),
+            status: workflow::Status::Cancelled,
+        }
+    }
+}
+
+impl show::ConsoleTable for AckCancel {
+    fn table(&self) -> show::Output {
+        Table::new(vec![self]).default_with_title("cancel")
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
+/// Exported functions of a Wasm module for response / display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct AckModule(#[schemars(with = "ModuleSchema")] ModuleDescription);
+
+/// Schema of a [ModuleDescription].
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "describe_module")]
+struct ModuleSchema {
+    #[schemars(description = "Cid of the module")]
+    cid: String,
+    #[schemars(description = "Exported functions, in export order")]
+    functions: Vec<FunctionSchema>,
+}
+
+/// Schema of a [FunctionDescription].
+///
+/// [FunctionDescription]: homestar_wasm::wasmtime::describe::FunctionDescription
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "function")]
+struct FunctionSchema {
+    #[schemars(description = "Name of the function, as exported")]
+    name: String,
+    #[schemars(description = "Parameter types, as WIT text")]
+    params: Vec<String>,
+    #[schemars(description = "Result types, as WIT text")]
+    results: Vec<String>,
+    #[schemars(description = "Function signature, as WIT text")]
+    wit: String,
+    #[schemars(description = "JSON Schema of the function's args")]
+    args_schema: serde_json::Value,
+}
+
+impl AckModule {
+    /// Create a new [AckModule] response.
+    pub(crate) fn new(description: ModuleDescription) -> Self {
+        Self(description)
+    }
+}
+
+impl show::ConsoleTable for AckModule {
+    fn table(&self) -> show::Output {
+        let mut builder = Builder::default();
+        builder.push_record([
+            "function".to_string(),
+            "signature".to_string(),
+            "args schema".to_string(),
+        ]);
+        for func in &self.0.functions {
+            builder.push_record([
+                func.name.clone(),
+                func.wit.clone(),
+                serde_json::to_string_pretty(&func.args_schema).unwrap_or_default(),
+            ]);
+        }
+        if builder.count_records() == 1 {
+            builder.push_record(["<none>".to_string(), "".to_string(), "".to_string()]);
+        }
+
+        builder
+            .build()
+            .default_with_title(&format!("module - {}", self.0.cid))
+    }
+
+    fn echo_table(&self) -> Result<(), std::io::Error> {
+        self.table().echo()
+    }
+}
+
 /// Ping response for display.
 #[derive(Debug, Tabled)]
 pub(crate) struct Ping {
@@ -137,6 +137,12 @@ impl<'a> TaskScheduler<'a> {
 
             if let Ok(pointers) = pointers {
                 if let Ok(found) = Db::find_instruction_pointers(&pointers, conn) {
+                    // Tasks whose receipts aren't cacheable, e.g. ones
+                    // that made network calls, are run again.
+                    let found: Vec<_> = found
+                        .into_iter()
+                        .filter(|receipt| receipt.is_cacheable())
+                        .collect();
                     for receipt in found.iter() {
                         resources_to_fetch.retain(|(cid, _)| *cid != receipt.instruction().cid());
                         linkmap.insert(receipt.instruction().cid(), receipt.output_as_arg());
@@ -178,7 +184,7 @@ impl<'a> TaskScheduler<'a> {
                     },
                 );
         if let Ok(found) = Db::find_instruction_pointers(&promises_as_pointers, conn) {
-            for receipt in found.iter() {
+            for receipt in found.iter().filter(|receipt| receipt.is_cacheable()) {
                 cids_to_resolve.retain(|cid| *cid != receipt.instruction().cid());
                 linkmap.insert(receipt.instruction().cid(), receipt.output_as_arg());
             }
@@ -447,6 +453,65 @@ mod test {
         assert_eq!(ctx.resume_step, None);
     }
 
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn initialize_task_scheduler_reruns_failed_instruction() {
+        let settings = TestSettings::load();
+        let config = Resources::default();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            config.clone().into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2.clone()),
+            config.into(),
+            UcanPrf::default(),
+        );
+
+        let invocation_receipt = InvocationReceipt::new(
+            Invocation::new(task1.clone()).try_into().unwrap(),
+            task::Result::Error(Ipld::String("wasm module trapped".to_string())),
+            Ipld::Null,
+            None,
+            UcanPrf::default(),
+        );
+        let receipt = Receipt::try_with(
+            instruction1.clone().try_into().unwrap(),
+            &invocation_receipt,
+        )
+        .unwrap();
+
+        let db = MemoryDb::setup_connection_pool(&settings.node, None).unwrap();
+        let mut conn = db.conn().unwrap();
+        MemoryDb::store_receipt(receipt, &mut conn).unwrap();
+
+        let workflow = Workflow::new(vec![task1.clone(), task2.clone()]);
+        let fetch_fn = |_rscs: FnvHashSet<Resource>| {
+            async {
+                let mut index_map = IndexMap::new();
+                index_map.insert(Resource::Url(instruction1.resource().to_owned()), vec![]);
+                index_map.insert(Resource::Url(instruction2.resource().to_owned()), vec![]);
+                Ok(index_map)
+            }
+            .boxed()
+        };
+
+        let builder = workflow::Builder::new(workflow);
+        let graph = builder.graph().unwrap();
+
+        let scheduler_ctx = TaskScheduler::init(graph.into(), &mut conn, fetch_fn)
+            .await
+            .unwrap();
+
+        let ctx = scheduler_ctx.scheduler;
+
+        assert!(ctx.linkmap.read().await.is_empty());
+        assert!(ctx.ran.is_none());
+        assert_eq!(ctx.run.len(), 2);
+        assert_eq!(ctx.resume_step, None);
+    }
+
     #[test]
     fn duplicate_task_no_nonce() {
         let config = Resources::default();
@@ -2,12 +2,15 @@
 
 use config::{Config, ConfigError, Environment, File};
 use derive_builder::Builder;
+use homestar_invocation::consts;
+use homestar_wasm::wasmtime::logs::DEFAULT_LOG_CAPACITY;
 use http::Uri;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr, DurationMilliSeconds, DurationSeconds};
 #[cfg(feature = "ipfs")]
 use std::net::Ipv4Addr;
 use std::{
+    collections::BTreeMap,
     env,
     net::{IpAddr, Ipv6Addr},
     path::PathBuf,
@@ -55,12 +58,32 @@ pub struct Node {
     /// Database settings.
     #[serde(default)]
     pub(crate) db: Database,
+    /// Per-task resource limits.
+    #[serde(default)]
+    pub(crate) resource_limits: ResourceLimits,
+    /// Limits on settings requested by workflows.
+    #[serde(default)]
+    pub(crate) workflow_limits: WorkflowLimits,
+    /// Wasm compilation, caching and execution settings.
+    #[serde(default)]
+    pub(crate) wasm: Wasm,
+    /// Outgoing HTTP settings for tasks.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    #[serde(default)]
+    pub(crate) http: Http,
+    /// UCAN authorization settings for running workflows.
+    #[serde(default)]
+    pub(crate) ucan: Ucan,
     /// Garbage collection interval.
     #[serde_as(as = "DurationSeconds<u64>")]
     pub(crate) gc_interval: Duration,
     /// Shutdown timeout.
     #[serde_as(as = "DurationSeconds<u64>")]
     pub(crate) shutdown_timeout: Duration,
+    /// Resume workflows left pending or running, e.g. after a crash, on
+    /// startup.
+    pub(crate) resume_workflows: bool,
 }
 
 /// Database-related settings for a homestar node.
@@ -79,6 +102,213 @@ pub struct Database {
     ///
     /// [pool]: crate::db::Pool
     pub(crate) max_pool_size: u32,
+    /// Retention policy for stored receipts and workflows.
+    #[serde(default)]
+    pub(crate) retention: Retention,
+}
+
+/// Retention policy for receipts, workflows and blocks put by their tasks
+/// stored by a homestar node, applied on every [gc interval] tick and by
+/// `homestar db prune`.
+///
+/// Nothing is pruned by default. Receipts awaited by pending or running
+/// workflows are never pruned.
+///
+/// [gc interval]: Node::gc_interval
+#[serde_as]
+#[derive(Builder, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct Retention {
+    /// Maximum age in seconds of receipts, of blocks put by tasks, and of
+    /// completed, failed or cancelled workflows.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    pub(crate) max_age: Option<Duration>,
+    /// Maximum number of receipts kept per workflow, newest first.
+    pub(crate) max_receipts_per_workflow: Option<u32>,
+    /// Keep only the receipts of terminal tasks, whose output no other
+    /// task in the workflow awaits, once a workflow is completed, failed
+    /// or cancelled.
+    pub(crate) terminal_only: bool,
+}
+
+impl Retention {
+    /// Whether any retention policy is set.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.max_receipts_per_workflow.is_some() || self.terminal_only
+    }
+}
+
+/// Resource limits (ceilings) for tasks run on a homestar node.
+///
+/// Workflows with tasks requesting more than these limits are rejected.
+/// Requests equal to a task's default resources, which workflows built with
+/// default metadata carry, count as unset: they're clamped to these limits
+/// rather than rejected.
+#[serde_as]
+#[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Maximum fuel a task can request.
+    ///
+    /// Fuel is unbounded if not set.
+    pub(crate) max_fuel: Option<u64>,
+    /// Maximum memory in bytes a task can request.
+    pub(crate) max_memory: u64,
+    /// Maximum time in milliseconds a task can request.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    pub(crate) max_time: Duration,
+}
+
+/// Limits (ceilings) on settings requested by workflows run on a homestar
+/// node.
+///
+/// Workflows requesting settings beyond these limits are rejected.
+#[serde_as]
+#[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct WorkflowLimits {
+    /// Maximum number of retries a workflow can request.
+    pub(crate) max_retries: u32,
+    /// Maximum delay in milliseconds between retries a workflow can request.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    pub(crate) max_retry_delay: Duration,
+    /// Maximum timeout in seconds a workflow can request.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub(crate) max_timeout: Duration,
+}
+
+/// Wasm compilation, caching and execution settings for a homestar node.
+///
+/// Components are compiled once per resource and kept in memory, and can
+/// optionally be persisted to disk as precompiled artifacts, reused across
+/// restarts by the same wasmtime version and configuration.
+#[serde_as]
+#[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct Wasm {
+    /// Maximum number of compiled components kept in memory.
+    pub(crate) component_cache_capacity: usize,
+    /// Persist precompiled components to disk.
+    pub(crate) precompile_cache: bool,
+    /// Directory precompiled components are persisted to, defaulting to
+    /// `precompiled` under the homestar data directory.
+    pub(crate) precompile_dir: Option<PathBuf>,
+    /// Run all tasks deterministically, with a fixed clock and randomness
+    /// seeded by each instruction, whether or not a task asks for it.
+    pub(crate) deterministic: bool,
+    /// Maximum size, in bytes of messages, of the guest logs kept per task.
+    pub(crate) max_task_log_bytes: usize,
+    /// Attach the guest logs kept for a task to its receipt's metadata.
+    ///
+    /// Off by default, as logs become part of the receipt's Cid.
+    pub(crate) task_logs_in_receipt: bool,
+    /// Maximum number of blocks a task can put through the host `ipld`
+    /// interface.
+    pub(crate) max_blocks_per_task: u32,
+    /// Maximum number of block bytes a task can put through the host `ipld`
+    /// interface.
+    pub(crate) max_block_bytes_per_task: u64,
+    /// WASI capabilities tasks may request.
+    pub(crate) wasi: WasiAllowlist,
+    /// Policy for the Wasm modules `wasm/run` tasks may run.
+    pub(crate) module_policy: ModulePolicy,
+}
+
+/// Policy for the Wasm modules `wasm/run` tasks may run, checked before a
+/// workflow is accepted and its resources fetched.
+///
+/// Denied modules are logged under the `audit` category and counted by the
+/// `wasm_modules_denied` metric.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "allow", rename_all = "snake_case")]
+pub(crate) enum ModulePolicy {
+    /// Run any module.
+    #[default]
+    All,
+    /// Run only modules in a list of Cids.
+    Allowlist {
+        /// Cids of allowed modules.
+        modules: Vec<String>,
+    },
+    /// Run only modules with a manifest signed by one of a list of trusted
+    /// publishers.
+    Signed {
+        /// DIDs of trusted publishers.
+        publishers: Vec<String>,
+        /// Directory of signed module manifests, as JSON files.
+        manifests: PathBuf,
+    },
+}
+
+/// UCAN authorization settings. Tasks are run without checking their proofs
+/// unless enforced.
+#[derive(Builder, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct Ucan {
+    /// Require tasks to carry UCAN proofs of the capability to run them,
+    /// delegated from a root authority.
+    pub(crate) enforce: bool,
+    /// DIDs of root authorities delegation chains must lead back to.
+    pub(crate) root_authorities: Vec<String>,
+}
+
+/// Allowlist of WASI capabilities tasks may request. Tasks are granted
+/// none by default.
+#[derive(Builder, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub(crate) struct WasiAllowlist {
+    /// Host directories tasks may preopen, read-only, keyed by the path
+    /// they're mounted at in the guest.
+    pub(crate) dirs: BTreeMap<String, PathBuf>,
+    /// Names of environment variables tasks may set.
+    pub(crate) env: Vec<String>,
+    /// Allow tasks to pass arguments.
+    pub(crate) args: bool,
+}
+
+/// Outgoing HTTP settings for tasks. Requests are denied unless their host
+/// is allowed.
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+#[derive(Builder, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+#[serde(default)]
+pub struct Http {
+    /// Hosts, and their methods, tasks may send requests to.
+    pub(crate) allow: Vec<HttpAllow>,
+    /// Maximum number of requests per task.
+    pub(crate) max_requests_per_task: u32,
+    /// Maximum number of response body bytes per task.
+    pub(crate) max_response_bytes_per_task: u64,
+}
+
+/// Host, and the methods, tasks may send requests to.
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub(crate) struct HttpAllow {
+    /// Host, optionally with a port, e.g. `api.internal:8080`.
+    pub(crate) host: String,
+    /// Methods allowed, or any method if empty.
+    #[serde(default)]
+    pub(crate) methods: Vec<String>,
+}
+
+impl Wasm {
+    /// Directory precompiled components are persisted to, if enabled.
+    pub(crate) fn precompile_dir(&self) -> Option<PathBuf> {
+        self.precompile_cache.then(|| {
+            self.precompile_dir
+                .clone()
+                .unwrap_or_else(|| data_dir().join("precompiled"))
+        })
+    }
 }
 
 /// Monitoring settings.
@@ -121,6 +351,25 @@ pub struct Network {
     pub(crate) ipfs: Ipfs,
     /// Webserver settings
     pub(crate) webserver: Webserver,
+    /// Policy for receipts received from the network.
+    pub(crate) receipt_policy: ReceiptPolicy,
+}
+
+/// Policy for receipts received from the network, over pub/sub or the DHT,
+/// checked before they're stored or used to resolve awaited results.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "accept", rename_all = "snake_case")]
+pub enum ReceiptPolicy {
+    /// Accept all receipts, signed or not.
+    #[default]
+    All,
+    /// Accept only receipts signed by their issuer.
+    Signed,
+    /// Accept only receipts signed by one of a list of trusted issuers.
+    Allowlist {
+        /// DIDs of trusted issuers.
+        issuers: Vec<String>,
+    },
 }
 
 /// IPFS Settings
@@ -197,6 +446,13 @@ impl Default for Node {
             monitoring: Default::default(),
             network: Default::default(),
             db: Default::default(),
+            resource_limits: Default::default(),
+            workflow_limits: Default::default(),
+            wasm: Default::default(),
+            #[cfg(feature = "http")]
+            http: Default::default(),
+            ucan: Default::default(),
+            resume_workflows: false,
         }
     }
 }
@@ -216,6 +472,38 @@ impl Node {
     pub fn shutdown_timeout(&self) -> Duration {
         self.shutdown_timeout
     }
+
+    /// Per-task resource limits.
+    pub fn resource_limits(&self) -> &ResourceLimits {
+        &self.resource_limits
+    }
+
+    /// Limits on settings requested by workflows.
+    pub fn workflow_limits(&self) -> &WorkflowLimits {
+        &self.workflow_limits
+    }
+
+    /// Wasm compilation, caching and execution settings.
+    pub fn wasm(&self) -> &Wasm {
+        &self.wasm
+    }
+
+    /// Outgoing HTTP settings for tasks.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn http(&self) -> &Http {
+        &self.http
+    }
+
+    /// UCAN authorization settings.
+    pub fn ucan(&self) -> &Ucan {
+        &self.ucan
+    }
+
+    /// Whether to resume interrupted workflows on startup.
+    pub fn resume_workflows(&self) -> bool {
+        self.resume_workflows
+    }
 }
 
 impl Default for Database {
@@ -223,6 +511,84 @@ impl Default for Database {
         Self {
             max_pool_size: 100,
             url: None,
+            retention: Retention::default(),
+        }
+    }
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_fuel: None,
+            max_memory: consts::WASM_MAX_MEMORY,
+            max_time: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl Default for Wasm {
+    fn default() -> Self {
+        Self {
+            component_cache_capacity: 64,
+            precompile_cache: false,
+            precompile_dir: None,
+            deterministic: false,
+            max_task_log_bytes: DEFAULT_LOG_CAPACITY,
+            task_logs_in_receipt: false,
+            max_blocks_per_task: 1024,
+            max_block_bytes_per_task: 16 * 1024 * 1024,
+            wasi: WasiAllowlist::default(),
+            module_policy: ModulePolicy::default(),
+        }
+    }
+}
+
+impl From<&Wasm> for homestar_wasm::wasmtime::blocks::BlockQuota {
+    fn from(wasm: &Wasm) -> Self {
+        Self {
+            max_blocks: wasm.max_blocks_per_task,
+            max_bytes: wasm.max_block_bytes_per_task,
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+impl Default for Http {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            max_requests_per_task: 16,
+            max_response_bytes_per_task: 4 * 1024 * 1024,
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+impl From<&Http> for homestar_wasm::wasmtime::http::HttpPolicy {
+    fn from(http: &Http) -> Self {
+        Self {
+            allow: http
+                .allow
+                .iter()
+                .map(|allow| homestar_wasm::wasmtime::http::HttpAllow {
+                    host: allow.host.clone(),
+                    methods: allow.methods.clone(),
+                })
+                .collect(),
+            max_requests: http.max_requests_per_task,
+            max_response_bytes: http.max_response_bytes_per_task,
+        }
+    }
+}
+
+impl Default for WorkflowLimits {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            max_retry_delay: Duration::from_secs(600),
+            max_timeout: Duration::from_secs(86400),
         }
     }
 }
@@ -260,6 +626,7 @@ impl Default for Network {
             #[cfg_attr(docsrs, doc(cfg(feature = "ipfs")))]
             ipfs: Default::default(),
             webserver: Webserver::default(),
+            receipt_policy: ReceiptPolicy::default(),
         }
     }
 }
@@ -281,6 +648,11 @@ impl Network {
     pub(crate) fn webserver(&self) -> &Webserver {
         &self.webserver
     }
+
+    /// Policy for receipts received from the network.
+    pub(crate) fn receipt_policy(&self) -> &ReceiptPolicy {
+        &self.receipt_policy
+    }
 }
 
 #[cfg(feature = "ipfs")]
@@ -390,6 +762,12 @@ fn config_dir() -> PathBuf {
     config_dir.join("homestar")
 }
 
+fn data_dir() -> PathBuf {
+    let data_dir = env::var("XDG_DATA_HOME")
+        .map_or_else(|_| home_dir().join(".local").join("share"), PathBuf::from);
+    data_dir.join("homestar")
+}
+
 fn home_dir() -> PathBuf {
     let home = env::var(HOME_VAR).unwrap_or_else(|_| panic!("{} not found", HOME_VAR));
     PathBuf::from(home)
@@ -424,6 +802,31 @@ mod test {
         default_modded_settings.shutdown_timeout = Duration::from_secs(20);
         default_modded_settings.network.libp2p.node_addresses =
             vec!["/ip4/127.0.0.1/tcp/9998/ws".to_string().try_into().unwrap()];
+        default_modded_settings.resource_limits.max_fuel = Some(10_000_000);
+        default_modded_settings.resource_limits.max_time = Duration::from_secs(60);
+        default_modded_settings.db.retention.max_age = Some(Duration::from_secs(2592000));
+        default_modded_settings.db.retention.terminal_only = true;
+        default_modded_settings.wasm.component_cache_capacity = 16;
+        default_modded_settings.wasm.precompile_cache = true;
+        default_modded_settings.wasm.wasi.dirs =
+            BTreeMap::from([("/data".to_string(), "/srv/homestar/data".into())]);
+        default_modded_settings.wasm.wasi.env = vec!["MODE".to_string()];
+        default_modded_settings.wasm.wasi.args = true;
+        default_modded_settings.wasm.module_policy = ModulePolicy::Allowlist {
+            modules: vec![
+                "bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q".to_string(),
+            ],
+        };
+        default_modded_settings.network.receipt_policy = ReceiptPolicy::Allowlist {
+            issuers: vec!["did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp".to_string()],
+        };
+        default_modded_settings.ucan.enforce = true;
+        default_modded_settings.ucan.root_authorities =
+            vec!["did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp".to_string()];
+        assert_eq!(
+            default_modded_settings.wasm.precompile_dir(),
+            Some(data_dir().join("precompiled"))
+        );
         assert_eq!(settings.node(), &default_modded_settings);
     }
 
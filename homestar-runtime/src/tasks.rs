@@ -5,9 +5,11 @@ use enum_assoc::Assoc;
 use std::path::PathBuf;
 
 mod fetch;
+mod module_policy;
 mod wasm;
 
 pub(crate) use fetch::*;
+pub(crate) use module_policy::*;
 pub(crate) use wasm::*;
 
 const WASM_OP: &str = "wasm/run";
@@ -0,0 +1,271 @@
+//! Checks of the Wasm modules `wasm/run` tasks run against the node's
+//! [ModulePolicy].
+
+use crate::{metrics::Reason, receipt::signer::public_key, settings::ModulePolicy};
+use libipld::Cid;
+use serde::{Deserialize, Serialize};
+use serde_with::{base64::Base64, serde_as, DisplayFromStr};
+use std::{fs, path::Path};
+use tokio::task;
+use tracing::warn;
+use url::Url;
+
+/// Reason a Wasm module was denied by the node's [ModulePolicy].
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub(crate) enum ModuleDenial {
+    /// Module resource is not addressed by a Cid, e.g. an `https://` URL.
+    #[error("module is not addressed by a Cid")]
+    Unaddressed,
+    /// Module is not in the allowlist of modules.
+    #[error("module is not in the allowlist")]
+    NotAllowlisted,
+    /// Module has no manifest validly signed by a trusted publisher.
+    #[error("module has no manifest signed by a trusted publisher")]
+    Unsigned,
+}
+
+impl Reason for ModuleDenial {
+    fn reason(&self) -> &'static str {
+        match self {
+            ModuleDenial::Unaddressed => "unaddressed",
+            ModuleDenial::NotAllowlisted => "not_allowlisted",
+            ModuleDenial::Unsigned => "unsigned",
+        }
+    }
+}
+
+/// Manifest of a Wasm module, signed by its publisher's `did:key` over the
+/// bytes of the module's Cid.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ModuleManifest {
+    #[serde_as(as = "DisplayFromStr")]
+    pub(crate) module: Cid,
+    pub(crate) publisher: String,
+    #[serde_as(as = "Base64")]
+    pub(crate) signature: Vec<u8>,
+}
+
+impl ModuleManifest {
+    /// Whether the manifest is validly signed by one of `publishers`.
+    fn signed_by(&self, publishers: &[String]) -> bool {
+        publishers.contains(&self.publisher)
+            && public_key(&self.publisher)
+                .map(|public| public.verify(&self.module.to_bytes(), &self.signature))
+                .unwrap_or(false)
+    }
+}
+
+/// A [ModulePolicy] along with the manifests it trusts, loaded once, e.g.
+/// per workflow, rather than for each module checked.
+#[derive(Debug, Clone)]
+pub(crate) struct LoadedModulePolicy<'a> {
+    policy: &'a ModulePolicy,
+    manifests: Vec<ModuleManifest>,
+}
+
+impl<'a> LoadedModulePolicy<'a> {
+    /// Load the manifests of a [ModulePolicy::Signed] policy, reading them
+    /// on the blocking thread pool, off the async executor.
+    pub(crate) async fn load(policy: &'a ModulePolicy) -> Self {
+        let manifests = match policy {
+            ModulePolicy::Signed { manifests, .. } => {
+                let dir = manifests.to_path_buf();
+                task::spawn_blocking(move || load_manifests(&dir))
+                    .await
+                    .unwrap_or_default()
+            }
+            ModulePolicy::All | ModulePolicy::Allowlist { .. } => vec![],
+        };
+
+        Self { policy, manifests }
+    }
+
+    /// Check the Wasm module at `rsc` against the [ModulePolicy].
+    ///
+    /// Modules are compared by the multihash of their Cid, so a module is
+    /// matched whatever the version or codec of the Cid it's referenced by.
+    pub(crate) fn check(&self, rsc: &Url) -> Result<(), ModuleDenial> {
+        let module = || module_cid(rsc).ok_or(ModuleDenial::Unaddressed);
+
+        match self.policy {
+            ModulePolicy::All => Ok(()),
+            ModulePolicy::Allowlist { modules } => {
+                let module = module()?;
+                modules
+                    .iter()
+                    .filter_map(|cid| Cid::try_from(cid.as_str()).ok())
+                    .any(|cid| cid.hash() == module.hash())
+                    .then_some(())
+                    .ok_or(ModuleDenial::NotAllowlisted)
+            }
+            ModulePolicy::Signed { publishers, .. } => {
+                let module = module()?;
+                self.manifests
+                    .iter()
+                    .any(|manifest| {
+                        manifest.module.hash() == module.hash() && manifest.signed_by(publishers)
+                    })
+                    .then_some(())
+                    .ok_or(ModuleDenial::Unsigned)
+            }
+        }
+    }
+}
+
+/// Cid of a module addressed by an `ipfs://` URL.
+fn module_cid(rsc: &Url) -> Option<Cid> {
+    match (rsc.scheme(), rsc.host_str()) {
+        ("ipfs", Some(cid)) => Cid::try_from(cid).ok(),
+        _ => None,
+    }
+}
+
+/// Load the JSON [ModuleManifest]s in a directory, skipping any that can't
+/// be read or parsed.
+fn load_manifests(dir: &Path) -> Vec<ModuleManifest> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        warn!(
+            subject = "module_policy.manifests.err",
+            category = "module_policy",
+            dir = dir.display().to_string(),
+            "failed to read module manifests directory"
+        );
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| Ok(serde_json::from_slice::<ModuleManifest>(&bytes)?))
+                .map_err(|err| {
+                    warn!(
+                        subject = "module_policy.manifest.err",
+                        category = "module_policy",
+                        path = path.display().to_string(),
+                        err=?err,
+                        "skipping invalid module manifest"
+                    )
+                })
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::receipt::signer::ReceiptSigner;
+    use libp2p::identity::Keypair;
+
+    const WASM_CID: &str = "bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q";
+
+    async fn check(rsc: &Url, policy: &ModulePolicy) -> Result<(), ModuleDenial> {
+        LoadedModulePolicy::load(policy).await.check(rsc)
+    }
+
+    #[tokio::test]
+    async fn allows_modules_per_allowlist() {
+        let rsc = Url::parse(&format!("ipfs://{WASM_CID}")).unwrap();
+        let cid = Cid::try_from(WASM_CID).unwrap();
+
+        assert!(check(&rsc, &ModulePolicy::All).await.is_ok());
+        assert!(check(
+            &rsc,
+            &ModulePolicy::Allowlist {
+                modules: vec![WASM_CID.to_string()]
+            }
+        )
+        .await
+        .is_ok());
+        assert!(check(
+            &rsc,
+            &ModulePolicy::Allowlist {
+                modules: vec![Cid::new_v1(0x55, *cid.hash()).to_string()]
+            }
+        )
+        .await
+        .is_ok());
+        assert_eq!(
+            check(
+                &rsc,
+                &ModulePolicy::Allowlist {
+                    modules: vec![
+                        "bafybeiejevluvtoevgk66plh5t6xiy3ikyuuxg3vgofuvpeckb6eadresm".to_string()
+                    ]
+                }
+            )
+            .await,
+            Err(ModuleDenial::NotAllowlisted)
+        );
+        assert_eq!(
+            check(
+                &Url::parse("https://example.com/add.wasm").unwrap(),
+                &ModulePolicy::Allowlist {
+                    modules: vec![WASM_CID.to_string()]
+                }
+            )
+            .await,
+            Err(ModuleDenial::Unaddressed)
+        );
+    }
+
+    #[tokio::test]
+    async fn allows_modules_signed_by_trusted_publishers() {
+        let rsc = Url::parse(&format!("ipfs://{WASM_CID}")).unwrap();
+        let module = Cid::try_from(WASM_CID).unwrap();
+        let keypair = Keypair::generate_ed25519();
+        let publisher = ReceiptSigner::new(keypair.clone())
+            .unwrap()
+            .issuer()
+            .to_string();
+        let other = ReceiptSigner::new(Keypair::generate_ed25519())
+            .unwrap()
+            .issuer()
+            .to_string();
+
+        let manifests =
+            std::env::temp_dir().join(format!("homestar-manifests-{}", rand::random::<u64>()));
+        fs::create_dir_all(&manifests).unwrap();
+        let manifest = ModuleManifest {
+            module,
+            publisher: publisher.clone(),
+            signature: keypair.sign(&module.to_bytes()).unwrap(),
+        };
+        fs::write(
+            manifests.join("add.json"),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+        fs::write(manifests.join("invalid.json"), b"{}").unwrap();
+
+        let signed = |publishers: Vec<String>| ModulePolicy::Signed {
+            publishers,
+            manifests: manifests.clone(),
+        };
+        assert!(check(&rsc, &signed(vec![publisher.clone()])).await.is_ok());
+        assert_eq!(
+            check(&rsc, &signed(vec![other.clone()])).await,
+            Err(ModuleDenial::Unsigned)
+        );
+
+        let forged = ModuleManifest {
+            publisher: other.clone(),
+            ..manifest
+        };
+        fs::write(
+            manifests.join("add.json"),
+            serde_json::to_vec(&forged).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            check(&rsc, &signed(vec![other])).await,
+            Err(ModuleDenial::Unsigned)
+        );
+
+        fs::remove_dir_all(manifests).unwrap();
+    }
+}
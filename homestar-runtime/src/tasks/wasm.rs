@@ -3,37 +3,143 @@
 //! [tasks]: homestar_invocation::Task
 
 use super::FileLoad;
-use homestar_invocation::task::instruction::Args;
+use crate::metrics;
+use homestar_invocation::{
+    receipt::metadata::{DURATION_KEY, FUEL_USED_KEY, PEAK_MEMORY_KEY},
+    task::instruction::{Args, Nonce},
+};
 use homestar_wasm::{
     io::{Arg, Output},
-    wasmtime::{world::Env, Error as WasmRuntimeError, State, World},
+    wasmtime::{logs::LogBuffer, world::Env, Error as WasmRuntimeError, Runtime, State},
+};
+use libipld::{
+    multihash::{Code, MultihashDigest},
+    Cid, Ipld,
 };
+use std::{collections::BTreeMap, time::Duration};
+use tokio::time;
 use tracing::Instrument;
+use url::Url;
 
 #[allow(dead_code)]
 #[allow(missing_debug_implementations)]
 pub(crate) struct WasmContext {
     env: Env<State>,
+    runtime: Runtime,
+}
+
+/// Resources used by a Wasm task run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Usage {
+    /// Fuel consumed, i.e. initial minus remaining fuel.
+    pub(crate) fuel_used: u64,
+    /// Peak memory used in bytes.
+    pub(crate) peak_memory: u64,
+    /// Wall-clock duration of the call into the guest, excluding
+    /// compilation and instantiation.
+    pub(crate) duration: Duration,
+}
+
+impl Usage {
+    /// Add [Usage] entries to receipt metadata.
+    pub(crate) fn extend_meta(&self, meta: &mut BTreeMap<String, Ipld>) {
+        meta.insert(FUEL_USED_KEY.into(), self.fuel_used.into());
+        meta.insert(PEAK_MEMORY_KEY.into(), self.peak_memory.into());
+        meta.insert(
+            DURATION_KEY.into(),
+            u64::try_from(self.duration.as_millis())
+                .unwrap_or(u64::MAX)
+                .into(),
+        );
+    }
+
+    /// Record [Usage] histograms for a function of a Wasm module.
+    pub(crate) fn record(&self, fun: &str, module: Cid) {
+        metrics::wasm::record_usage(self, fun, module)
+    }
 }
 
 impl WasmContext {
     #[allow(dead_code)]
-    pub(crate) fn new(data: State) -> Result<Self, WasmRuntimeError> {
+    pub(crate) fn new(runtime: Runtime, data: State) -> Result<Self, WasmRuntimeError> {
         Ok(Self {
-            env: World::default(data)?,
+            env: runtime.env(data)?,
+            runtime,
         })
     }
 
-    /// Instantiate environment via [World] and execute on [Args].
+    /// Cid a Wasm resource is cached under: the Cid of an `ipfs://` resource,
+    /// or the content Cid of its bytes otherwise.
+    pub(crate) fn resource_cid(rsc: &Url, bytes: &[u8]) -> Cid {
+        match (rsc.scheme(), rsc.host_str()) {
+            ("ipfs", Some(cid)) => Cid::try_from(cid).ok(),
+            _ => None,
+        }
+        .unwrap_or_else(|| Runtime::content_cid(bytes))
+    }
+
+    /// Seed for a deterministic run of an instruction, derived from its
+    /// Cid and nonce, so any node running it draws the same randomness.
+    pub(crate) fn seed(instruction_cid: Cid, nonce: &Nonce) -> [u8; 32] {
+        let mut bytes = instruction_cid.to_bytes();
+        bytes.extend(nonce.to_vec());
+        let mut seed = [0; 32];
+        seed.copy_from_slice(Code::Sha3_256.digest(&bytes).digest());
+        seed
+    }
+
+    /// Instantiate environment via the shared [Runtime], compiling the
+    /// resource only if not already cached, and execute on [Args].
+    ///
+    /// Execution, but not instantiation, is given up on after `timeout`,
+    /// e.g. if stuck in a host call the guest can't be interrupted in.
     #[allow(dead_code)]
     pub(crate) async fn run<'a>(
         &mut self,
-        bytes: Vec<u8>,
+        cid: Cid,
+        bytes: &[u8],
         fun_name: &'a str,
         args: Args<Arg>,
+        timeout: Duration,
     ) -> Result<Output, WasmRuntimeError> {
-        let env = World::instantiate_with_current_env(bytes, fun_name, &mut self.env).await?;
-        env.execute(args).in_current_span().await
+        let env = self
+            .runtime
+            .instantiate(cid, bytes, fun_name, &mut self.env)
+            .await?;
+        let output = time::timeout(timeout, env.execute(args).in_current_span())
+            .await
+            .unwrap_or(Err(WasmRuntimeError::WasmInterrupted));
+        self.env.finish_logs();
+        output
+    }
+
+    /// Log records written by the guest in the most recent run.
+    pub(crate) fn logs(&self) -> &LogBuffer {
+        self.env.logs()
+    }
+
+    /// Outgoing HTTP requests made by the guest in the most recent run.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub(crate) fn http_calls(&self) -> Vec<homestar_wasm::wasmtime::http::HttpCall> {
+        self.env.http_calls()
+    }
+
+    /// Number of outgoing HTTP requests admitted in the most recent run.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub(crate) fn http_requests(&self) -> u32 {
+        self.env.http_requests()
+    }
+
+    /// Resources used by the most recent run, so far if still running or
+    /// cut short.
+    pub(crate) fn usage(&self) -> Usage {
+        Usage {
+            fuel_used: self.env.fuel_consumed(),
+            peak_memory: self.env.memory_consumed(),
+            duration: self.env.call_duration(),
+        }
     }
 }
 
@@ -59,4 +165,49 @@ mod test {
 
         assert!(!wat.is_empty());
     }
+
+    #[test]
+    fn usage_receipt_meta() {
+        let usage = Usage {
+            fuel_used: 42,
+            peak_memory: 65536,
+            duration: Duration::from_millis(1500),
+        };
+
+        let mut meta = BTreeMap::new();
+        usage.extend_meta(&mut meta);
+        assert_eq!(meta.get(FUEL_USED_KEY), Some(&Ipld::Integer(42)));
+        assert_eq!(meta.get(PEAK_MEMORY_KEY), Some(&Ipld::Integer(65536)));
+        assert_eq!(meta.get(DURATION_KEY), Some(&Ipld::Integer(1500)));
+    }
+
+    #[test]
+    fn seed_from_instruction() {
+        let cid = Runtime::content_cid(b"instruction");
+        let nonce = Nonce::generate();
+
+        let seed = WasmContext::seed(cid, &nonce);
+        assert_eq!(seed, WasmContext::seed(cid, &nonce));
+        assert_ne!(seed, WasmContext::seed(cid, &Nonce::generate()));
+        assert_ne!(
+            seed,
+            WasmContext::seed(Runtime::content_cid(b"other"), &nonce)
+        );
+    }
+
+    #[test]
+    fn resource_cid_from_url_or_content() {
+        let cid = "bafybeihzvrlcfqf6ffbp2juhuakspxj2bdsc54cabxnuxfvuqy5lvfxapy";
+        let ipfs = Url::parse(&format!("ipfs://{cid}")).unwrap();
+        assert_eq!(
+            WasmContext::resource_cid(&ipfs, b"wasm").to_string(),
+            cid.to_string()
+        );
+
+        let https = Url::parse("https://example.com/add.wasm").unwrap();
+        assert_eq!(
+            WasmContext::resource_cid(&https, b"wasm"),
+            Runtime::content_cid(b"wasm")
+        );
+    }
 }
@@ -7,9 +7,10 @@ use crate::{
     channel::AsyncChannelSender,
     db::Database,
     event_handler::Event,
+    receipt::signer::ReceiptSigner,
     settings,
     tasks::Fetch,
-    worker::WorkerMessage,
+    worker::{WorkerMessage, WorkerNode},
     workflow::{self, Resource},
     Settings, Worker,
 };
@@ -21,10 +22,11 @@ use homestar_invocation::{
     task::{instruction::RunInstruction, Resources},
     Task,
 };
-use homestar_wasm::io::Arg;
+use homestar_wasm::{io::Arg, wasmtime::Runtime as WasmRuntime};
 use homestar_workflow::Workflow;
 use indexmap::IndexMap;
 use libipld::Cid;
+use libp2p::identity::Keypair;
 
 /// Utility structure for building out [Worker]s for testing purposes.
 ///
@@ -48,8 +50,8 @@ pub(crate) struct WorkerBuilder<'a> {
     workflow: Workflow<'a, Arg>,
     /// [Workflow] settings.
     workflow_settings: workflow::Settings,
-    /// Network settings.
-    network_settings: settings::Dht,
+    /// Node settings.
+    node_settings: settings::Node,
 }
 
 /// Utility structure for building out [Worker]s for testing purposes.
@@ -69,8 +71,8 @@ pub(crate) struct WorkerBuilder<'a> {
     workflow: Workflow<'a, Arg>,
     /// [Workflow] settings.
     workflow_settings: workflow::Settings,
-    /// Network settings.
-    network_settings: settings::Dht,
+    /// Node settings.
+    node_settings: settings::Node,
 }
 
 impl<'a> WorkerBuilder<'a> {
@@ -107,7 +109,7 @@ impl<'a> WorkerBuilder<'a> {
             name: Some(workflow_cid.to_string()),
             workflow,
             workflow_settings: workflow::Settings::default(),
-            network_settings: settings.network.libp2p.dht,
+            node_settings: settings,
         }
     }
 
@@ -117,7 +119,13 @@ impl<'a> WorkerBuilder<'a> {
         Worker::new(
             self.workflow,
             self.workflow_settings,
-            self.network_settings,
+            WorkerNode {
+                settings: &self.node_settings,
+                wasm_runtime: WasmRuntime::global().unwrap().clone(),
+                receipt_signer: ReceiptSigner::new(Keypair::generate_ed25519())
+                    .unwrap()
+                    .into(),
+            },
             self.name,
             self.event_sender.into(),
             self.runner_sender,
@@ -10,44 +10,111 @@ use crate::{
     channel::AsyncChannelSender,
     db::Database,
     event_handler::{event::Captured, Event},
-    receipt::metadata::{REPLAYED_KEY, WORKFLOW_KEY, WORKFLOW_NAME_KEY},
+    metrics::{self, Reason},
+    receipt::{
+        metadata::{REPLAYED_KEY, WORKFLOW_KEY, WORKFLOW_NAME_KEY},
+        signer::ReceiptSigner,
+    },
     runner::{ModifiedSet, RunningTaskSet},
     scheduler::ExecutionGraph,
     settings,
-    tasks::{RegisteredTasks, WasmContext},
+    tasks::{LoadedModulePolicy, RegisteredTasks, Usage, WasmContext},
     workflow::{self, Resource},
     Db, Receipt, TaskScheduler,
 };
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, Result};
 use chrono::NaiveDateTime;
 use faststr::FastStr;
 use fnv::FnvHashSet;
 use futures::{future::BoxFuture, FutureExt};
 use homestar_invocation::{
-    authority::UcanPrf, ipld::DagCbor, receipt::metadata::OP_KEY, task, Pointer,
-    Receipt as InvocationReceipt,
+    authority::UcanPrf,
+    ipld::DagCbor,
+    receipt::metadata::{CACHEABLE_KEY, DETERMINISTIC_KEY, HTTP_KEY, LOGS_KEY, OP_KEY, WASI_KEY},
+    task::{self, Resources, WasiRequest},
+    Pointer, Receipt as InvocationReceipt,
 };
 use homestar_wasm::{
     io::{Arg, Output},
-    wasmtime::State,
+    wasmtime::{
+        logs::LogRecord, runtime::EPOCH_TICK, wasi::WasiCapabilities, Error as WasmRuntimeError,
+        Runtime as WasmRuntime, State,
+    },
 };
 use homestar_workflow::Workflow;
 use indexmap::IndexMap;
 use libipld::{Cid, Ipld};
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, panic::AssertUnwindSafe, sync::Arc, time::Duration};
 use tokio::task::JoinSet;
-use tracing::{debug, debug_span, error, info, info_span, instrument, Instrument};
+use tracing::{debug, debug_span, error, info, info_span, instrument, warn, Instrument};
+use url::Url;
 
+mod blocks;
+mod failure;
+#[cfg(feature = "websocket-notify")]
+mod logs;
 mod poller;
 mod resolver;
+use blocks::WorkflowBlocks;
+use failure::TaskFailure;
+#[cfg(feature = "websocket-notify")]
+use logs::TaskLogSink;
 use poller::Poll;
 use resolver::Resolver;
 
 use self::resolver::DHTResolver;
 
+/// Time past a task's time limit before giving up on a host call, leaving
+/// guest execution to be interrupted at the limit itself.
+const HOST_CALL_GRACE: Duration = EPOCH_TICK.saturating_mul(10);
+
 /// [JoinSet] of tasks run by a [Worker].
-#[allow(dead_code)]
-pub(crate) type TaskSet = JoinSet<anyhow::Result<(Output, Pointer, Pointer, Ipld, Ipld)>>;
+pub(crate) type TaskSet = JoinSet<TaskRun>;
+
+/// Outcome of a task run by a [Worker], along with what's needed to build
+/// its receipt.
+#[derive(Debug)]
+pub(crate) struct TaskRun {
+    /// What the run produced, or a failure if it panicked.
+    execution: TaskExecution,
+    /// Name of the function called.
+    fun: String,
+    /// [Pointer] to the task's instruction.
+    instruction_ptr: Pointer,
+    /// [Pointer] to the task's invocation.
+    invocation_ptr: Pointer,
+    /// Metadata sent along with the captured receipt.
+    additional_meta: Ipld,
+    /// Whether the task ran deterministically.
+    deterministic: bool,
+    /// WASI capabilities granted to the task.
+    wasi: WasiRequest,
+}
+
+/// What running a task produced.
+#[derive(Debug)]
+struct TaskExecution {
+    /// Output and resource usage of the run, or why it failed.
+    executed: Result<(Output, Usage), TaskFailure>,
+    /// Guest logs to attach to the receipt.
+    logs: Vec<LogRecord>,
+    /// Outgoing HTTP calls made by the task.
+    http_calls: Vec<Ipld>,
+    /// Whether the task made any outgoing requests, recorded or not.
+    made_requests: bool,
+}
+
+impl TaskExecution {
+    /// [TaskExecution] of a task that failed before producing anything.
+    fn failed(failure: TaskFailure) -> Self {
+        Self {
+            executed: Err(failure),
+            logs: Vec::new(),
+            http_calls: Vec::new(),
+            made_requests: false,
+        }
+    }
+}
 
 /// Messages sent to [Worker] from [Runner].
 ///
@@ -58,6 +125,18 @@ pub(crate) enum WorkerMessage {
     Dropped(Cid),
 }
 
+/// Node settings, and the node's shared Wasm runtime and receipt signer, a
+/// [Worker] runs a workflow's tasks with.
+#[allow(missing_debug_implementations)]
+pub(crate) struct WorkerNode<'n> {
+    /// Settings of the node.
+    pub(crate) settings: &'n settings::Node,
+    /// Shared Wasm engine, linker and compiled component cache.
+    pub(crate) wasm_runtime: WasmRuntime,
+    /// Signer of receipts, on behalf of the node.
+    pub(crate) receipt_signer: Arc<ReceiptSigner>,
+}
+
 /// Worker that operates over a given [TaskScheduler].
 #[allow(dead_code)]
 #[allow(missing_debug_implementations)]
@@ -72,6 +151,10 @@ pub(crate) struct Worker<'a, DB: Database> {
     ///
     /// [Runner]: crate::Runner
     pub(crate) runner_sender: AsyncChannelSender<WorkerMessage>,
+    /// Shared Wasm engine, linker and compiled component cache.
+    pub(crate) wasm_runtime: WasmRuntime,
+    /// Signer of receipts, on behalf of the node.
+    pub(crate) receipt_signer: Arc<ReceiptSigner>,
     /// [Database] pool to pull connections from for the [Worker] run.
     pub(crate) db: DB,
     /// Local name of the [Workflow] being run.
@@ -82,6 +165,13 @@ pub(crate) struct Worker<'a, DB: Database> {
     pub(crate) workflow_settings: Arc<workflow::Settings>,
     /// Network settings.
     pub(crate) network_settings: Arc<settings::Dht>,
+    /// Per-task resource limits of the node.
+    pub(crate) resource_limits: Arc<settings::ResourceLimits>,
+    /// Wasm execution settings of the node.
+    pub(crate) wasm_settings: Arc<settings::Wasm>,
+    /// Outgoing HTTP policy of the node.
+    #[cfg(feature = "http")]
+    pub(crate) http_policy: Arc<homestar_wasm::wasmtime::http::HttpPolicy>,
     /// [NaiveDateTime] of when the [Workflow] was started.
     pub(crate) workflow_started: NaiveDateTime,
 }
@@ -91,25 +181,58 @@ where
     DB: Database + 'static,
 {
     /// Instantiate a new [Worker] for a [Workflow].
-    ///
-    /// TODO: integrate settings within workflow
     #[allow(dead_code)]
     pub(crate) async fn new<S: Into<FastStr>>(
         workflow: Workflow<'a, Arg>,
         settings: workflow::Settings,
-        network_settings: settings::Dht,
+        node: WorkerNode<'_>,
         // Name would be runner specific, separated from core workflow spec.
         name: Option<S>,
         event_sender: Arc<AsyncChannelSender<Event>>,
         runner_sender: AsyncChannelSender<WorkerMessage>,
         db: DB,
     ) -> Result<Worker<'a, DB>> {
+        let WorkerNode {
+            settings: node_settings,
+            wasm_runtime,
+            receipt_signer,
+        } = node;
         let workflow_len = workflow.len();
-        // Need to take ownership here to get the cid.
+        // Need to take ownership here to get the cid and encoding.
         let workflow_cid = workflow.to_owned().to_cid()?;
+        let workflow_data = workflow.to_owned().to_cbor()?;
 
         let builder = workflow::Builder::new(workflow);
-        let graph = builder.graph()?;
+        let mut graph = builder.graph()?;
+
+        // Resolve each task's resources against the node's limits, rejecting
+        // the workflow if any task asks for more, or for WASI capabilities,
+        // than allowed, or runs a module the node doesn't allow.
+        let resource_limits = node_settings.resource_limits();
+        let wasi_allowlist = &node_settings.wasm().wasi;
+        let module_policy = LoadedModulePolicy::load(&node_settings.wasm().module_policy).await;
+        let deterministic = node_settings.wasm().deterministic;
+        for node in graph.schedule.iter_mut().flatten() {
+            let vertex = node.inner_mut();
+            let instruction_cid = vertex.instruction.to_owned().to_cid()?;
+            vertex.resources =
+                resolve_resources(instruction_cid, &vertex.resources, resource_limits)?;
+            check_wasi(instruction_cid, vertex.resources.wasi(), wasi_allowlist)?;
+            if let Some(RegisteredTasks::WasmRun) =
+                RegisteredTasks::ability(&vertex.instruction.op().to_string())
+            {
+                check_module(
+                    instruction_cid,
+                    vertex.instruction.resource(),
+                    &module_policy,
+                )?;
+            }
+            if deterministic {
+                vertex.resources.set_deterministic(true);
+            }
+        }
+
+        let network_settings = node_settings.network().libp2p().dht();
         let name = name
             .map(|n| n.into())
             .unwrap_or(FastStr::from_string(workflow_cid.to_string()));
@@ -125,16 +248,26 @@ where
         )
         .await?;
 
+        // Keep the workflow itself around, so it can be resumed if
+        // interrupted.
+        Db::store_workflow_data(workflow_cid, &workflow_data, &mut db.conn()?)?;
+
         Ok(Self {
             graph: graph.into(),
             event_sender,
             runner_sender,
+            wasm_runtime,
+            receipt_signer,
             db,
             workflow_name: name,
             workflow_info: workflow_info.into(),
             workflow_settings: settings.into(),
             workflow_started: timestamp,
-            network_settings: network_settings.into(),
+            network_settings: network_settings.clone().into(),
+            resource_limits: resource_limits.clone().into(),
+            wasm_settings: node_settings.wasm().clone().into(),
+            #[cfg(feature = "http")]
+            http_policy: Arc::new(node_settings.http().into()),
         })
     }
 
@@ -296,6 +429,7 @@ where
             }
         }
 
+        let mut failed = false;
         for batch in scheduler.run.into_iter() {
             let mut task_set = TaskSet::new();
             let mut handles = Vec::new();
@@ -304,13 +438,12 @@ where
                 let vertice = node.into_inner();
                 let invocation_ptr = vertice.invocation;
                 let instruction = vertice.instruction;
+                let resources = vertice.resources;
                 let rsc = instruction.resource();
                 let parsed = vertice.parsed;
                 let fun = parsed.fun().ok_or_else(|| anyhow!("no function defined"))?;
 
                 let args = parsed.into_args();
-                let receipt_meta =
-                    Ipld::Map(BTreeMap::from([(OP_KEY.into(), fun.to_string().into())]));
 
                 let additional_meta = Ipld::Map(BTreeMap::from([
                     (REPLAYED_KEY.into(), Ipld::Bool(false)),
@@ -330,10 +463,42 @@ where
                             .get(&Resource::Url(rsc.to_owned()))
                             .ok_or_else(|| anyhow!("resource not available"))?
                             .to_owned();
+                        let wasm_cid = WasmContext::resource_cid(rsc, &wasm);
 
+                        let nonce = instruction.nonce().to_owned();
                         let instruction_ptr = Pointer::try_from(instruction)?;
-                        let state = State::default();
-                        let mut wasm_ctx = WasmContext::new(state)?;
+                        let time_limit = resources.time().unwrap_or(self.resource_limits.max_time);
+                        let deterministic = resources.deterministic();
+                        let wasi = resources.wasi().to_owned();
+                        let mut state = State::from(resources);
+                        state.set_time_limit(Some(time_limit));
+                        if deterministic {
+                            state.set_deterministic(WasmContext::seed(
+                                instruction_ptr.cid(),
+                                &nonce,
+                            ));
+                        }
+                        state.set_wasi_capabilities(wasi_capabilities(
+                            &wasi,
+                            &self.wasm_settings.wasi,
+                        ));
+                        state.set_blocks(Arc::new(WorkflowBlocks::new(
+                            self.workflow_info.cid(),
+                            scheduler.resources.clone(),
+                            self.db.clone(),
+                        )));
+                        state.set_block_quota(self.wasm_settings.as_ref().into());
+                        state.set_log_capacity(self.wasm_settings.max_task_log_bytes);
+                        #[cfg(feature = "http")]
+                        state.set_http_policy(self.http_policy.as_ref().clone());
+                        #[cfg(feature = "websocket-notify")]
+                        state.set_log_sink(Arc::new(TaskLogSink::new(
+                            self.workflow_info.cid(),
+                            instruction_ptr.cid(),
+                            self.event_sender.clone(),
+                        )));
+                        let attach_logs = self.wasm_settings.task_logs_in_receipt;
+                        let mut wasm_ctx = WasmContext::new(self.wasm_runtime.clone(), state)?;
 
                         let db = self.db.clone();
                         let linkmap = scheduler.linkmap.clone();
@@ -353,36 +518,84 @@ where
                                 .boxed()
                         });
 
-                        let handle = task_set.spawn(async move {
-                             match resolved.await {
-                                Ok(inst_result) => {
-                                    match wasm_ctx.run(wasm, &fun, inst_result).instrument({
-                                        debug_span!("wasm_run").or_current()
-                                    }).await {
-                                        Ok(output) => Ok((
-                                            output,
-                                            instruction_ptr,
-                                            invocation_ptr,
-                                            receipt_meta,
-                                            additional_meta)),
-                                        Err(err) => Err(
-                                            anyhow!("cannot execute wasm module: {:#?}", err))
-                                            .with_context(|| {
-                                                format!("not able to run fn {fun} for cid: {instruction_ptr}, in workflow {workflow_cid}")
-                                            }),
+                        let handle = task_set.spawn(
+                            async move {
+                                let execution = async {
+                                    let mut logs = Vec::new();
+                                    #[cfg_attr(not(feature = "http"), allow(unused_mut))]
+                                    let mut http_calls = Vec::new();
+                                    #[cfg_attr(not(feature = "http"), allow(unused_mut))]
+                                    let mut made_requests = false;
+                                    let executed = match resolved.await {
+                                        Ok(inst_result) => {
+                                            // Guests are interrupted at their time
+                                            // limit; the grace only bounds host calls.
+                                            let ran = wasm_ctx
+                                                .run(
+                                                    wasm_cid,
+                                                    &wasm,
+                                                    &fun,
+                                                    inst_result,
+                                                    time_limit + HOST_CALL_GRACE,
+                                                )
+                                                .instrument(debug_span!("wasm_run").or_current())
+                                                .await;
+
+                                            let usage = wasm_ctx.usage();
+                                            usage.record(&fun, wasm_cid);
+                                            if attach_logs {
+                                                logs = wasm_ctx.logs().records().to_vec();
+                                            }
+                                            #[cfg(feature = "http")]
+                                            {
+                                                http_calls = wasm_ctx
+                                                    .http_calls()
+                                                    .into_iter()
+                                                    .map(Ipld::from)
+                                                    .collect();
+                                                made_requests = wasm_ctx.http_requests() > 0;
+                                            }
+                                            match ran {
+                                                Ok(output) => Ok((output, usage)),
+                                                Err(WasmRuntimeError::WasmInterrupted) => {
+                                                    Err(TaskFailure::timeout(time_limit, usage))
+                                                }
+                                                Err(err) => Err(TaskFailure::wasm(err, usage)),
+                                            }
+                                        }
+                                        Err(err) => Err(TaskFailure::resolve(err)),
+                                    };
+
+                                    TaskExecution {
+                                        executed,
+                                        logs,
+                                        http_calls,
+                                        made_requests,
                                     }
-                                },
-                                Err(err) => {
-                                    Err(anyhow!("error resolving cid: {:#?}", err))
-                                        .with_context(|| {
-                                            format!("not able to resolve instruction: {instruction_ptr}, in workflow {workflow_cid}")
-                                        })
+                                };
+
+                                // Keep what's needed for the receipt outside
+                                // the run, so a panicking task still gets an
+                                // error receipt.
+                                let execution = AssertUnwindSafe(execution)
+                                    .catch_unwind()
+                                    .await
+                                    .unwrap_or_else(|panic| {
+                                        TaskExecution::failed(TaskFailure::panic(panic))
+                                    });
+
+                                TaskRun {
+                                    execution,
+                                    fun,
+                                    instruction_ptr,
+                                    invocation_ptr,
+                                    additional_meta,
+                                    deterministic,
+                                    wasi,
                                 }
                             }
-                        }
-                        .instrument({
-                            info_span!("spawn_workflow_tasks").or_current()
-                        }));
+                            .instrument(info_span!("spawn_workflow_tasks").or_current()),
+                        );
 
                         handles.push(handle);
                     }
@@ -398,39 +611,106 @@ where
             // Concurrently add handles to Runner's running set.
             running_tasks.append_or_insert(self.workflow_info.cid(), handles);
             while let Some(res) = task_set.join_next().await {
-                let (executed, instruction_ptr, invocation_ptr, receipt_meta, add_meta) = match res
-                {
-                    Ok(Ok(data)) => data,
-                    Ok(Err(err)) => {
+                let TaskRun {
+                    execution:
+                        TaskExecution {
+                            executed,
+                            logs,
+                            http_calls,
+                            made_requests,
+                        },
+                    fun,
+                    instruction_ptr,
+                    invocation_ptr,
+                    additional_meta,
+                    deterministic,
+                    wasi,
+                } = match res {
+                    Ok(data) => data,
+                    // Panics are caught within tasks, so only aborted tasks,
+                    // e.g. of a dropped workflow, end up here.
+                    Err(err) => {
                         error!(
                             subject = "worker.run.task.err",
                             category = "worker.run",
                             err = format!("{:#?}", err),
                             "error in running task"
                         );
-                        break;
+                        failed = true;
+                        continue;
                     }
-                    Err(err) => {
+                };
+
+                let (result, mut receipt_meta) = match executed {
+                    Ok((output, usage)) => {
+                        let mut receipt_meta = BTreeMap::from([(OP_KEY.into(), fun.into())]);
+                        usage.extend_meta(&mut receipt_meta);
+                        // Only successful runs are reproducible; failures
+                        // may stem from node-specific limits, responses to
+                        // network calls and preopened host directories may
+                        // differ between runs and nodes.
+                        if deterministic && !made_requests && wasi.dirs().is_empty() {
+                            receipt_meta.insert(DETERMINISTIC_KEY.into(), true.into());
+                        }
+                        (
+                            task::Result::Ok(Ipld::try_from(output)?),
+                            Ipld::Map(receipt_meta),
+                        )
+                    }
+                    Err(failure) => {
                         error!(
                             subject = "worker.run.task.err",
                             category = "worker.run",
-                            err = format!("{:#?}", err),
+                            workflow_cid = self.workflow_info.cid.to_string(),
+                            instruction_cid = instruction_ptr.cid().to_string(),
+                            error_category = failure.category().to_string(),
+                            err = failure.message(),
                             "error in running task"
                         );
-                        break;
+                        failed = true;
+                        let receipt_meta = failure.receipt_meta(&fun);
+                        (failure.into_result(), receipt_meta)
                     }
                 };
 
-                let output_to_store = Ipld::try_from(executed)?;
+                if let Ipld::Map(ref mut meta) = receipt_meta {
+                    // Record granted capabilities, whether or not the run
+                    // succeeded, for auditability. Their host-provided
+                    // inputs may change, so the receipt isn't reused.
+                    if !wasi.is_empty() {
+                        meta.insert(WASI_KEY.into(), wasi.into());
+                        meta.insert(CACHEABLE_KEY.into(), false.into());
+                    }
+                    if !logs.is_empty() {
+                        meta.insert(
+                            LOGS_KEY.into(),
+                            Ipld::List(logs.into_iter().map(Ipld::from).collect()),
+                        );
+                    }
+                    if !http_calls.is_empty() {
+                        meta.insert(HTTP_KEY.into(), Ipld::List(http_calls));
+                    }
+                    // Responses may differ between runs, so receipts of
+                    // tasks that made network calls aren't reused, even if
+                    // cut short before a call was recorded.
+                    if made_requests {
+                        meta.insert(CACHEABLE_KEY.into(), false.into());
+                    }
+                }
+
                 let invocation_receipt = InvocationReceipt::new(
                     invocation_ptr,
-                    task::Result::Ok(output_to_store),
+                    result,
                     receipt_meta,
-                    None,
+                    Some(self.receipt_signer.issuer().to_owned()),
                     UcanPrf::default(),
                 );
 
-                let receipt = Receipt::try_with(instruction_ptr, &invocation_receipt)?;
+                let mut receipt = Receipt::try_with(instruction_ptr, &invocation_receipt)?;
+                let signature = self
+                    .receipt_signer
+                    .sign(receipt.instruction(), &invocation_receipt)?;
+                receipt.set_signature(signature);
 
                 scheduler
                     .linkmap
@@ -471,27 +751,190 @@ where
                     .send_async(Event::CapturedReceipt(Captured::with(
                         stored_receipt.cid(),
                         self.workflow_info.clone(),
-                        Some(add_meta),
+                        Some(additional_meta),
                     )))
                     .await;
             }
+
+            // Tasks in later batches may depend on the failed task, so
+            // stop the workflow here.
+            if failed {
+                break;
+            }
         }
 
-        // Set the workflow status to `completed`
         let conn = &mut self.db.conn()?;
-        Db::set_workflow_status(self.workflow_info.cid, workflow::Status::Completed, conn)?;
-
-        info!(
-            subject = "worker.end_workflow",
-            category = "worker.run",
-            workflow_cid = self.workflow_info.cid.to_string(),
-            "workflow completed"
-        );
+        if failed {
+            // Set the workflow status to `failed`
+            Db::set_workflow_status(self.workflow_info.cid, workflow::Status::Failed, conn)?;
+
+            info!(
+                subject = "worker.end_workflow",
+                category = "worker.run",
+                workflow_cid = self.workflow_info.cid.to_string(),
+                "workflow failed"
+            );
+        } else {
+            // Set the workflow status to `completed`
+            Db::set_workflow_status(self.workflow_info.cid, workflow::Status::Completed, conn)?;
+
+            info!(
+                subject = "worker.end_workflow",
+                category = "worker.run",
+                workflow_cid = self.workflow_info.cid.to_string(),
+                "workflow completed"
+            );
+        }
 
         Ok(())
     }
 }
 
+/// Resolve a task's requested [Resources] against the node's
+/// [settings::ResourceLimits].
+///
+/// Resources left unset by the task, or set to their [Resources::default]
+/// value, as workflows built with default metadata are, take their default,
+/// bounded by the node's limit. Other requests above a limit are rejected.
+fn resolve_resources(
+    instruction_cid: Cid,
+    requested: &Resources,
+    limits: &settings::ResourceLimits,
+) -> Result<Resources, workflow::Error> {
+    let defaults = Resources::default();
+    let check = |resource, requested: Option<u64>, default: u64, limit: Option<u64>| match (
+        requested.filter(|requested| *requested != default),
+        limit,
+    ) {
+        (Some(requested), Some(limit)) if requested > limit => {
+            Err(workflow::Error::ResourceLimitExceeded {
+                instruction: instruction_cid.to_string(),
+                resource,
+                requested,
+                limit,
+            })
+        }
+        (Some(requested), _) => Ok(requested),
+        (None, Some(limit)) => Ok(std::cmp::min(default, limit)),
+        (None, None) => Ok(default),
+    };
+
+    let fuel = check(
+        "fuel",
+        requested.fuel(),
+        defaults.fuel().unwrap_or(u64::MAX),
+        limits.max_fuel,
+    )?;
+    let memory = check(
+        "bytes of memory",
+        requested.memory(),
+        defaults.memory().unwrap_or(limits.max_memory),
+        Some(limits.max_memory),
+    )?;
+    let time = check(
+        "ms of time",
+        requested.time().map(|t| t.as_millis() as u64),
+        defaults.time().unwrap_or(limits.max_time).as_millis() as u64,
+        Some(limits.max_time.as_millis() as u64),
+    )?;
+
+    let mut resources = Resources::new(fuel, memory, Duration::from_millis(time));
+    resources.set_deterministic(requested.deterministic());
+    resources.set_wasi(requested.wasi().to_owned());
+    Ok(resources)
+}
+
+/// Check a task's requested WASI capabilities against the node's
+/// [settings::WasiAllowlist], rejecting any capability not allowed.
+fn check_wasi(
+    instruction_cid: Cid,
+    requested: &WasiRequest,
+    allowlist: &settings::WasiAllowlist,
+) -> Result<(), workflow::Error> {
+    let not_allowed = |capability, requested: &str| {
+        Err(workflow::Error::WasiCapabilityNotAllowed {
+            instruction: instruction_cid.to_string(),
+            capability,
+            requested: requested.to_string(),
+        })
+    };
+
+    if let Some(dir) = requested
+        .dirs()
+        .iter()
+        .find(|dir| !allowlist.dirs.contains_key(*dir))
+    {
+        return not_allowed("directory", dir);
+    }
+    if let Some(key) = requested
+        .env()
+        .keys()
+        .find(|key| !allowlist.env.contains(*key))
+    {
+        return not_allowed("environment variable", key);
+    }
+    if !allowlist.args {
+        if let Some(arg) = requested.args().first() {
+            return not_allowed("argument", arg);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the Wasm module a task runs against the node's loaded
+/// [settings::ModulePolicy], recording any denial in the node's audit trail:
+/// the `audit` log category and the `wasm_modules_denied` metric.
+fn check_module(
+    instruction_cid: Cid,
+    rsc: &Url,
+    policy: &LoadedModulePolicy<'_>,
+) -> Result<(), workflow::Error> {
+    policy.check(rsc).map_err(|denial| {
+        warn!(
+            subject = "worker.module_denied",
+            category = "audit",
+            instruction_cid = instruction_cid.to_string(),
+            module = rsc.to_string(),
+            reason = denial.reason(),
+            "denied module: {denial}"
+        );
+        metrics::wasm::record_module_denial(&denial);
+
+        workflow::Error::ModuleNotAllowed {
+            instruction: instruction_cid.to_string(),
+            module: rsc.to_string(),
+            reason: denial.to_string(),
+        }
+    })
+}
+
+/// [WasiCapabilities] granted for a task's checked [WasiRequest], mapping
+/// requested directories to their host paths.
+fn wasi_capabilities(
+    requested: &WasiRequest,
+    allowlist: &settings::WasiAllowlist,
+) -> WasiCapabilities {
+    let capabilities = requested
+        .dirs()
+        .iter()
+        .filter_map(|guest| allowlist.dirs.get(guest).map(|host| (host, guest)))
+        .fold(WasiCapabilities::new(), |capabilities, (host, guest)| {
+            capabilities.with_dir(host, guest)
+        });
+    let capabilities = requested
+        .env()
+        .iter()
+        .fold(capabilities, |capabilities, (key, value)| {
+            capabilities.with_env(key, value)
+        });
+
+    requested
+        .args()
+        .iter()
+        .fold(capabilities, |capabilities, arg| capabilities.with_arg(arg))
+}
+
 impl<'a, DB> Drop for Worker<'a, DB>
 where
     DB: Database,
@@ -512,6 +955,9 @@ mod test {
         workflow::{IndexedResources, Status},
     };
     use homestar_invocation::{
+        receipt::metadata::{
+            DURATION_KEY, ERROR_CATEGORY_KEY, ERROR_MESSAGE_KEY, FUEL_USED_KEY, PEAK_MEMORY_KEY,
+        },
         task::{instruction::RunInstruction, Resources},
         Invocation, Task,
     };
@@ -595,6 +1041,8 @@ mod test {
                     captured_receipt = true;
                     receipts_cnt += 1;
                 }
+                #[cfg(feature = "websocket-notify")]
+                Event::TaskLog(_) => {}
                 _ => panic!("Wrong event type"),
             }
         }
@@ -711,7 +1159,13 @@ mod test {
         }
 
         // we should have received 1 receipt
-        let next_run_receipt = rx.recv_async().await.unwrap();
+        let next_run_receipt = loop {
+            match rx.recv_async().await.unwrap() {
+                #[cfg(feature = "websocket-notify")]
+                Event::TaskLog(_) => continue,
+                event => break event,
+            }
+        };
 
         let (_next_receipt, wf_info) = match next_run_receipt {
             Event::CapturedReceipt(Captured {
@@ -725,6 +1179,15 @@ mod test {
 
                 assert_ne!(next_receipt, receipt);
 
+                let Ipld::Map(meta) = next_receipt.meta() else {
+                    panic!("receipt meta is not a map")
+                };
+                assert!(matches!(meta.get(FUEL_USED_KEY), Some(Ipld::Integer(fuel)) if *fuel > 0));
+                assert!(
+                    matches!(meta.get(PEAK_MEMORY_KEY), Some(Ipld::Integer(memory)) if *memory > 0)
+                );
+                assert!(matches!(meta.get(DURATION_KEY), Some(Ipld::Integer(_))));
+
                 (next_receipt, info)
             }
             _ => panic!("Wrong event type"),
@@ -745,6 +1208,236 @@ mod test {
         assert_eq!(workflow_stored.status, Status::Completed);
     }
 
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn failed_task_produces_error_receipt() {
+        let settings = TestSettings::load();
+
+        let (instruction1, instruction2, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+
+        // Not enough fuel to run the first task.
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            Resources::new(1, 1024 * 1024 * 100, Duration::from_secs(10)).into(),
+            UcanPrf::default(),
+        );
+
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(vec![task1, task2]);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let workflow_cid = builder.workflow_cid();
+        let worker = builder.build().await;
+
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks, fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                receipts.push(MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap());
+            }
+        }
+
+        // Only the failing task is receipted, as the dependent task never runs.
+        assert_eq!(receipts.len(), 1);
+        let receipt = &receipts[0];
+        assert_eq!(receipt.instruction().cid(), instruction1.to_cid().unwrap());
+        assert!(matches!(receipt.output(), task::Result::Error(_)));
+
+        let Ipld::Map(meta) = receipt.meta() else {
+            panic!("receipt meta is not a map")
+        };
+        assert_eq!(meta.get(OP_KEY), Some(&Ipld::String("add_one".into())));
+        assert_eq!(
+            meta.get(ERROR_CATEGORY_KEY),
+            Some(&Ipld::String("trap".into()))
+        );
+        assert!(meta.contains_key(ERROR_MESSAGE_KEY));
+        assert!(meta.contains_key(FUEL_USED_KEY));
+        assert!(meta.contains_key(PEAK_MEMORY_KEY));
+        assert!(meta.contains_key(DURATION_KEY));
+
+        let workflow_stored = MemoryDb::select_workflow(workflow_cid, &mut conn).unwrap();
+        assert_eq!(workflow_stored.status, Status::Failed);
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn wasi_grants_are_not_cached_or_deterministic() {
+        let mut settings = TestSettings::load();
+        settings.node.wasm.deterministic = true;
+        settings.node.wasm.wasi.dirs =
+            BTreeMap::from([("/data".to_string(), std::env::temp_dir())]);
+
+        let (instruction1, instruction2, _) =
+            homestar_invocation::test_utils::related_wasm_instructions::<Arg>();
+
+        let mut granted = Resources::default();
+        granted.set_wasi(WasiRequest::new(
+            vec!["/data".to_string()],
+            BTreeMap::new(),
+            vec![],
+        ));
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            granted.into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2.clone()),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+
+        let (tx, rx) = test_utils::event::setup_event_channel(settings.node.clone());
+
+        let builder = WorkerBuilder::new(settings.node)
+            .with_event_sender(tx)
+            .with_tasks(vec![task1, task2]);
+        let fetch_fn = builder.fetch_fn();
+        let db = builder.db();
+        let worker = builder.build().await;
+
+        let running_tasks = Arc::new(RunningTaskSet::new());
+        worker.run(running_tasks, fetch_fn).await.unwrap();
+
+        let mut conn = db.conn().unwrap();
+        let mut receipts = vec![];
+        while let Ok(event) = rx.recv_async().await {
+            if let Event::CapturedReceipt(Captured { receipt, .. }) = event {
+                receipts.push(MemoryDb::find_receipt_by_cid(receipt, &mut conn).unwrap());
+            }
+        }
+        assert_eq!(receipts.len(), 2);
+
+        let meta = |instruction: Cid| {
+            let receipt = receipts
+                .iter()
+                .find(|receipt| receipt.instruction().cid() == instruction)
+                .unwrap();
+            let Ipld::Map(meta) = receipt.meta() else {
+                panic!("receipt meta is not a map")
+            };
+            (receipt.is_cacheable(), meta.to_owned())
+        };
+
+        let (cacheable, granted_meta) = meta(instruction1.to_cid().unwrap());
+        assert!(!cacheable);
+        assert!(granted_meta.contains_key(WASI_KEY));
+        assert!(!granted_meta.contains_key(DETERMINISTIC_KEY));
+
+        let (cacheable, meta) = meta(instruction2.to_cid().unwrap());
+        assert!(cacheable);
+        assert!(!meta.contains_key(WASI_KEY));
+        assert_eq!(meta.get(DETERMINISTIC_KEY), Some(&Ipld::Bool(true)));
+    }
+
+    #[test]
+    fn resolve_task_resources_against_limits() {
+        let cid = Cid::default();
+        let limits = settings::ResourceLimits {
+            max_fuel: Some(1_000),
+            max_memory: 1024,
+            max_time: Duration::from_secs(10),
+        };
+
+        let requested = Resources::new(500, 512, Duration::from_secs(5));
+        let resolved = resolve_resources(cid, &requested, &limits).unwrap();
+        assert_eq!(resolved, requested);
+
+        // Unset resources take the default, bounded by the node's limits.
+        let requested = Resources::try_from(Ipld::Map(BTreeMap::new())).unwrap();
+        let resolved = resolve_resources(cid, &requested, &limits).unwrap();
+        assert_eq!(
+            resolved,
+            Resources::new(1_000, 1024, Duration::from_secs(10))
+        );
+
+        let requested = Resources::new(500, 512, Duration::from_secs(20));
+        let err = resolve_resources(cid, &requested, &limits).unwrap_err();
+        assert!(matches!(
+            err,
+            workflow::Error::ResourceLimitExceeded {
+                requested: 20_000,
+                limit: 10_000,
+                ..
+            }
+        ));
+
+        let requested = Resources::new(2_000, 512, Duration::from_secs(5));
+        assert!(resolve_resources(cid, &requested, &limits).is_err());
+
+        let mut requested = Resources::new(500, 512, Duration::from_secs(5));
+        requested.set_deterministic(true);
+        let resolved = resolve_resources(cid, &requested, &limits).unwrap();
+        assert!(resolved.deterministic());
+
+        // Default resources are treated as unset, and clamped to the limits.
+        let resolved = resolve_resources(cid, &Resources::default(), &limits).unwrap();
+        assert_eq!(
+            resolved,
+            Resources::new(1_000, 1024, Duration::from_secs(10))
+        );
+
+        // Fuel is unbounded when no limit is set.
+        let limits = settings::ResourceLimits::default();
+        let resolved = resolve_resources(cid, &Resources::default(), &limits).unwrap();
+        assert_eq!(resolved, Resources::default());
+    }
+
+    #[test]
+    fn check_wasi_against_allowlist() {
+        let cid = Cid::default();
+        let allowlist = settings::WasiAllowlist {
+            dirs: BTreeMap::from([("/data".to_string(), "/srv/data".into())]),
+            env: vec!["MODE".to_string()],
+            args: false,
+        };
+
+        let requested = WasiRequest::new(
+            vec!["/data".to_string()],
+            BTreeMap::from([("MODE".to_string(), "fast".to_string())]),
+            vec![],
+        );
+        check_wasi(cid, &requested, &allowlist).unwrap();
+        let capabilities = wasi_capabilities(&requested, &allowlist);
+        assert_eq!(
+            capabilities.dirs()[0].host,
+            std::path::Path::new("/srv/data")
+        );
+        assert_eq!(capabilities.dirs()[0].guest, "/data");
+        assert_eq!(capabilities.env(), &[("MODE".into(), "fast".into())]);
+
+        let requested = WasiRequest::new(vec!["/etc".to_string()], BTreeMap::new(), vec![]);
+        assert!(matches!(
+            check_wasi(cid, &requested, &allowlist),
+            Err(workflow::Error::WasiCapabilityNotAllowed {
+                capability: "directory",
+                ..
+            })
+        ));
+
+        let requested = WasiRequest::new(
+            vec![],
+            BTreeMap::from([("HOME".to_string(), "/".to_string())]),
+            vec![],
+        );
+        assert!(check_wasi(cid, &requested, &allowlist).is_err());
+
+        let requested = WasiRequest::new(vec![], BTreeMap::new(), vec!["-v".to_string()]);
+        assert!(check_wasi(cid, &requested, &allowlist).is_err());
+    }
+
     #[homestar_runtime_proc_macro::db_async_test]
     fn initialize_worker_with_all_receipted_instruction() {
         let mut settings = TestSettings::load();
@@ -0,0 +1,114 @@
+//! [BlockStore] backing the host `ipld` interface for a [Worker]'s tasks.
+//!
+//! [Worker]: crate::Worker
+
+use crate::{db::Database, workflow::Resource};
+use async_trait::async_trait;
+use homestar_wasm::wasmtime::blocks::BlockStore;
+use indexmap::IndexMap;
+use libipld::Cid;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Blocks of a running workflow: its fetched resources, blocks put by
+/// tasks, and stored [Receipt]s.
+///
+/// Blocks put by tasks, up to the node's per-task [BlockQuota], are kept
+/// alongside the workflow's resources for the rest of its run, and stored
+/// in the [Database] for later runs and exports, until pruned along with
+/// the workflow per the node's [Retention] policy.
+///
+/// [BlockQuota]: homestar_wasm::wasmtime::blocks::BlockQuota
+/// [Receipt]: crate::Receipt
+/// [Retention]: crate::settings::Retention
+pub(crate) struct WorkflowBlocks<DB: Database> {
+    workflow_cid: Cid,
+    resources: Arc<RwLock<IndexMap<Resource, Vec<u8>>>>,
+    db: DB,
+}
+
+impl<DB: Database> WorkflowBlocks<DB> {
+    /// Create a new [WorkflowBlocks] over a workflow's resources and the
+    /// [Database].
+    pub(crate) fn new(
+        workflow_cid: Cid,
+        resources: Arc<RwLock<IndexMap<Resource, Vec<u8>>>>,
+        db: DB,
+    ) -> Self {
+        Self {
+            workflow_cid,
+            resources,
+            db,
+        }
+    }
+}
+
+#[async_trait]
+impl<DB: Database> BlockStore for WorkflowBlocks<DB> {
+    async fn get_block(&self, cid: Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(bytes) = self.resources.read().await.get(&Resource::Cid(cid)) {
+            return Ok(Some(bytes.to_owned()));
+        }
+
+        let conn = &mut self.db.conn()?;
+        if let Some(bytes) = DB::find_block(cid, conn)? {
+            return Ok(Some(bytes));
+        }
+
+        match DB::find_receipt_by_cid(cid, conn) {
+            Ok(receipt) => Ok(Some(receipt.block()?)),
+            Err(diesel::result::Error::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn put_block(&self, cid: Cid, bytes: Vec<u8>) -> anyhow::Result<()> {
+        DB::store_block(self.workflow_cid, cid, &bytes, &mut self.db.conn()?)?;
+        self.resources
+            .write()
+            .await
+            .insert(Resource::Cid(cid), bytes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::{self, db::MemoryDb};
+    use homestar_invocation::Pointer;
+    use libipld::multihash::{Code, MultihashDigest};
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn get_resources_receipts_and_put_blocks() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let (_, receipt) = test_utils::receipt::receipts();
+        MemoryDb::store_receipt(receipt.clone(), &mut db.conn().unwrap()).unwrap();
+
+        let workflow_cid = Cid::new_v1(0x55, Code::Sha3_256.digest(b"workflow"));
+        let blocks = WorkflowBlocks::new(workflow_cid, Default::default(), db.clone());
+        assert_eq!(
+            blocks.get_block(receipt.cid()).await.unwrap(),
+            Some(receipt.block().unwrap())
+        );
+
+        let cid = Cid::new_v1(0x55, Code::Sha3_256.digest(b"block"));
+        assert_eq!(blocks.get_block(cid).await.unwrap(), None);
+        blocks.put_block(cid, b"block".to_vec()).await.unwrap();
+        assert_eq!(
+            blocks.get_block(cid).await.unwrap(),
+            Some(b"block".to_vec())
+        );
+
+        // Put blocks outlive the workflow's run.
+        let blocks = WorkflowBlocks::new(workflow_cid, Default::default(), db.clone());
+        assert_eq!(
+            blocks.get_block(cid).await.unwrap(),
+            Some(b"block".to_vec())
+        );
+
+        MemoryDb::delete_workflows(&[Pointer::new(workflow_cid)], &mut db.conn().unwrap()).unwrap();
+        assert_eq!(blocks.get_block(cid).await.unwrap(), None);
+    }
+}
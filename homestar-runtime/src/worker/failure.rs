@@ -0,0 +1,218 @@
+//! Failures of tasks run by a [Worker], captured as error receipts.
+//!
+//! [Worker]: crate::Worker
+
+use crate::tasks::Usage;
+use homestar_invocation::{
+    error::ResolveError,
+    receipt::metadata::{CACHEABLE_KEY, ERROR_CATEGORY_KEY, ERROR_MESSAGE_KEY, OP_KEY},
+    task,
+};
+use homestar_wasm::wasmtime::Error as WasmRuntimeError;
+use libipld::Ipld;
+use std::{any::Any, collections::BTreeMap, fmt, time::Duration};
+
+/// Category of error a task ran into, recorded in the metadata of an
+/// error receipt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorCategory {
+    /// Arguments awaiting other tasks could not be resolved.
+    Resolve,
+    /// Arguments or results could not be converted between Ipld and Wasm.
+    Conversion,
+    /// Wasm component could not be parsed or instantiated.
+    Instantiation,
+    /// Wasm execution trapped, e.g. ran out of fuel or memory.
+    Trap,
+    /// Wasm execution exceeded its time limit.
+    Timeout,
+    /// Task panicked while running.
+    Panic,
+    /// Unknown error.
+    Unknown,
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorCategory::Resolve => write!(f, "resolve"),
+            ErrorCategory::Conversion => write!(f, "conversion"),
+            ErrorCategory::Instantiation => write!(f, "instantiation"),
+            ErrorCategory::Trap => write!(f, "trap"),
+            ErrorCategory::Timeout => write!(f, "timeout"),
+            ErrorCategory::Panic => write!(f, "panic"),
+            ErrorCategory::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl From<&WasmRuntimeError> for ErrorCategory {
+    fn from(err: &WasmRuntimeError) -> Self {
+        match err {
+            WasmRuntimeError::ResolvePromise(_) => ErrorCategory::Resolve,
+            WasmRuntimeError::InterpreterError(_) => ErrorCategory::Conversion,
+            WasmRuntimeError::WasmRuntime(_) => ErrorCategory::Trap,
+            WasmRuntimeError::WasmInterrupted => ErrorCategory::Timeout,
+            WasmRuntimeError::IntoWasmComponent(_)
+            | WasmRuntimeError::WasmInstantiation
+            | WasmRuntimeError::WasmParser(_)
+            | WasmRuntimeError::WasmFunctionNotFound(_)
+            | WasmRuntimeError::WatComponent(_)
+            | WasmRuntimeError::Wat(_)
+            | WasmRuntimeError::WasiPreopen(..) => ErrorCategory::Instantiation,
+            WasmRuntimeError::Unknown => ErrorCategory::Unknown,
+        }
+    }
+}
+
+/// Failure of a task, to be stored as a [task::Result::Error] receipt.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TaskFailure {
+    category: ErrorCategory,
+    message: String,
+    usage: Usage,
+}
+
+impl TaskFailure {
+    /// Create a [TaskFailure] for a task that could not resolve its
+    /// arguments.
+    pub(crate) fn resolve(err: ResolveError) -> Self {
+        Self {
+            category: ErrorCategory::Resolve,
+            message: err.to_string(),
+            usage: Usage::default(),
+        }
+    }
+
+    /// Create a [TaskFailure] for a task that failed during Wasm
+    /// instantiation or execution.
+    pub(crate) fn wasm(err: WasmRuntimeError, usage: Usage) -> Self {
+        let category = ErrorCategory::from(&err);
+        let message = match err {
+            WasmRuntimeError::WasmRuntime(err) => format!("{err:#}"),
+            err => err.to_string(),
+        };
+
+        Self {
+            category,
+            message,
+            usage,
+        }
+    }
+
+    /// Create a [TaskFailure] for a task that exceeded its time limit.
+    pub(crate) fn timeout(time_limit: Duration, usage: Usage) -> Self {
+        Self {
+            category: ErrorCategory::Timeout,
+            message: format!(
+                "wasm module exceeded time limit of {}ms",
+                time_limit.as_millis()
+            ),
+            usage,
+        }
+    }
+
+    /// Create a [TaskFailure] for a task that panicked while running.
+    pub(crate) fn panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|msg| msg.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "task panicked".to_string());
+
+        Self {
+            category: ErrorCategory::Panic,
+            message,
+            usage: Usage::default(),
+        }
+    }
+
+    /// [ErrorCategory] of the failure.
+    pub(crate) fn category(&self) -> ErrorCategory {
+        self.category
+    }
+
+    /// Error message of the failure.
+    pub(crate) fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Receipt metadata for the failure of the given operation.
+    pub(crate) fn receipt_meta(&self, op: &str) -> Ipld {
+        let mut meta = BTreeMap::from([
+            (OP_KEY.into(), op.into()),
+            (ERROR_CATEGORY_KEY.into(), self.category.to_string().into()),
+            (ERROR_MESSAGE_KEY.into(), self.message.clone().into()),
+            (CACHEABLE_KEY.into(), false.into()),
+        ]);
+        self.usage.extend_meta(&mut meta);
+        Ipld::Map(meta)
+    }
+
+    /// Convert the failure into a [task::Result::Error].
+    pub(crate) fn into_result(self) -> task::Result<Ipld> {
+        task::Result::Error(Ipld::String(self.message))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homestar_invocation::receipt::metadata::FUEL_USED_KEY;
+
+    #[test]
+    fn failure_receipt_meta() {
+        let failure = TaskFailure::wasm(
+            WasmRuntimeError::WasmRuntime(anyhow::anyhow!("all fuel consumed")),
+            Usage {
+                fuel_used: 42,
+                ..Default::default()
+            },
+        );
+        assert_eq!(failure.category(), ErrorCategory::Trap);
+
+        let meta = failure.receipt_meta("add-one");
+        let Ipld::Map(map) = meta else {
+            panic!("receipt meta is not a map")
+        };
+        assert_eq!(map.get(OP_KEY), Some(&Ipld::String("add-one".into())));
+        assert_eq!(
+            map.get(ERROR_CATEGORY_KEY),
+            Some(&Ipld::String("trap".into()))
+        );
+        assert_eq!(
+            map.get(ERROR_MESSAGE_KEY),
+            Some(&Ipld::String("all fuel consumed".into()))
+        );
+        assert_eq!(map.get(FUEL_USED_KEY), Some(&Ipld::Integer(42)));
+        assert_eq!(map.get(CACHEABLE_KEY), Some(&Ipld::Bool(false)));
+
+        assert_eq!(
+            failure.into_result(),
+            task::Result::Error(Ipld::String("all fuel consumed".into()))
+        );
+    }
+
+    #[test]
+    fn timeout_failure() {
+        let failure = TaskFailure::timeout(Duration::from_millis(100), Usage::default());
+        assert_eq!(failure.category(), ErrorCategory::Timeout);
+        assert_eq!(
+            failure.message(),
+            "wasm module exceeded time limit of 100ms"
+        );
+
+        let interrupted = TaskFailure::wasm(WasmRuntimeError::WasmInterrupted, Usage::default());
+        assert_eq!(interrupted.category(), ErrorCategory::Timeout);
+    }
+
+    #[test]
+    fn panic_failure() {
+        let failure = TaskFailure::panic(Box::new("index out of bounds"));
+        assert_eq!(failure.category(), ErrorCategory::Panic);
+        assert_eq!(failure.message(), "index out of bounds");
+
+        let failure = TaskFailure::panic(Box::new(42));
+        assert_eq!(failure.message(), "task panicked");
+    }
+}
@@ -0,0 +1,50 @@
+//! [LogSink] publishing guest log records of a [Worker]'s tasks to
+//! subscribers as they're written.
+//!
+//! [Worker]: crate::Worker
+
+use crate::{
+    channel::AsyncChannelSender,
+    event_handler::{event::TaskLog, Event},
+};
+use homestar_wasm::wasmtime::logs::{LogRecord, LogSink};
+use libipld::Cid;
+use std::sync::Arc;
+
+/// Sends guest log records of a task to the [EventHandler], to be published
+/// on the task's log subscription.
+///
+/// Records are dropped, rather than blocking the guest, if the event channel
+/// is full.
+///
+/// [EventHandler]: crate::EventHandler
+pub(crate) struct TaskLogSink {
+    workflow: Cid,
+    instruction: Cid,
+    event_sender: Arc<AsyncChannelSender<Event>>,
+}
+
+impl TaskLogSink {
+    /// Create a new [TaskLogSink] for an instruction of a workflow.
+    pub(crate) fn new(
+        workflow: Cid,
+        instruction: Cid,
+        event_sender: Arc<AsyncChannelSender<Event>>,
+    ) -> Self {
+        Self {
+            workflow,
+            instruction,
+            event_sender,
+        }
+    }
+}
+
+impl LogSink for TaskLogSink {
+    fn log(&self, record: &LogRecord) {
+        let _ = self.event_sender.try_send(Event::TaskLog(TaskLog {
+            workflow: self.workflow,
+            instruction: self.instruction,
+            record: record.clone(),
+        }));
+    }
+}
@@ -63,13 +63,15 @@ impl Resolver for Cid {
         } else {
             let conn = &mut db.conn()?;
             match Db::find_instruction_by_cid(self, conn) {
-                Ok(found) => Ok(found.output_as_arg()),
-                Err(_) => {
+                // Receipts that aren't cacheable, e.g. errors, are run
+                // again rather than resolved.
+                Ok(found) if found.is_cacheable() => Ok(found.output_as_arg()),
+                _ => {
                     debug!(
                         subject = "worker.resolve_cid",
                         category = "worker.run",
                         cid = self.to_string(),
-                        "no related instruction receipt found in the DB"
+                        "no cacheable instruction receipt found in the DB"
                     );
                     Err(ResolveError::UnresolvedCid((self).to_string()))
                 }
@@ -117,6 +119,8 @@ where
                 )))
                 .await;
 
+            // Found receipts have been verified against the node's receipt
+            // policy; rejected ones resolve to an error.
             let found = match timeout_at(Instant::now() + self.p2p_receipt_timeout, rx.recv_async())
                 .await
             {
@@ -137,6 +141,12 @@ where
                 )),
             };
 
+            if !found.receipt.is_cacheable() {
+                bail!(ResolveError::UnresolvedCid(format!(
+                    "receipt found for {cid} is not cacheable"
+                )));
+            }
+
             let conn = &mut ctx.db.conn()?;
 
             let receipt = Db::commit_receipt(self.workflow_cid, found.clone().receipt, conn)
@@ -4,9 +4,12 @@
 //! [UCAN Invocation]: <https://github.com/ucan-wg/invocation>
 
 use crate::scheduler::ExecutionGraph;
-use anyhow::{anyhow, bail};
+use anyhow::bail;
 use core::fmt;
-use dagga::{dot::DagLegend, Node};
+use dagga::{
+    dot::{DagLegend, Edge},
+    Node,
+};
 use diesel::{
     backend::Backend,
     deserialize::{self, FromSql},
@@ -18,7 +21,7 @@ use diesel::{
 use homestar_invocation::{
     task::{
         instruction::{Parse, Parsed, RunInstruction},
-        Instruction,
+        Instruction, Resources,
     },
     Invocation, Pointer,
 };
@@ -32,15 +35,19 @@ use std::{collections::BTreeMap, path::Path};
 use tracing::debug;
 use url::Url;
 
+pub(crate) mod authorization;
 pub(crate) mod error;
+mod filter;
 mod info;
 pub mod settings;
+mod validation;
 
 pub(crate) use error::Error;
+pub use filter::Filter;
 pub(crate) use info::{Info, Stored, StoredReceipt};
 pub use info::{Status, StatusMapping, WORKFLOW_TAG};
 #[allow(unused_imports)]
-pub use settings::Settings;
+pub use settings::{RequestedSettings, Settings};
 
 type Dag<'a> = dagga::Dag<Vertex<'a>, usize>;
 
@@ -91,13 +98,107 @@ impl AOTContext<'static> {
     /// [dot]: <https://graphviz.org/doc/info/lang.html>
     #[allow(dead_code)]
     pub(crate) fn dot(&self, name: &str, path: &Path) -> anyhow::Result<()> {
-        DagLegend::new(self.dag.nodes())
-            .with_name(name)
-            .save_to(
-                path.to_str()
-                    .ok_or_else(|| anyhow!("path is not correctly formatted"))?,
-            )
-            .map_err(|e| anyhow!(e))
+        let mut file = std::fs::File::create(path)?;
+        dot2::render(&self.legend(name), &mut file)?;
+        Ok(())
+    }
+
+    /// Render [Dag] as a [dot] graph, as written by [AOTContext::dot].
+    ///
+    /// [Dag]: dagga::Dag
+    /// [dot]: <https://graphviz.org/doc/info/lang.html>
+    pub(crate) fn render_dot(&self, name: &str) -> anyhow::Result<String> {
+        let mut out = vec![];
+        dot2::render(&self.legend(name), &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    fn legend(&self, name: &str) -> Legend {
+        Legend(DagLegend::new(self.dag.nodes()).with_name(name))
+    }
+}
+
+/// Name of the placeholder node [DagLegend] adds for missing inputs.
+const GHOST_ROOT_NAME: &str = "required_resources";
+
+/// Wrapper around [DagLegend] that renders the same graph without the
+/// debug output [DagLegend] prints to stdout while labelling nodes.
+struct Legend(DagLegend<usize>);
+
+impl<'a> dot2::Labeller<'a> for Legend {
+    type Node = Node<(), usize>;
+    type Edge = Edge<usize>;
+    type Subgraph = usize;
+
+    fn graph_id(&'a self) -> dot2::Result<dot2::Id<'a>> {
+        self.0.graph_id()
+    }
+
+    fn node_id(&'a self, n: &Self::Node) -> dot2::Result<dot2::Id<'a>> {
+        let id = self
+            .0
+            .node_ids
+            .get(n.name())
+            .map_or_else(|| "ghost_node".to_string(), |id| format!("node_{id}"));
+        dot2::Id::new(id)
+    }
+
+    fn node_label(&'a self, n: &Self::Node) -> dot2::Result<dot2::label::Text<'a>> {
+        self.0.node_label(n)
+    }
+
+    fn node_style(&'a self, n: &Self::Node) -> dot2::Style {
+        if n.name() == GHOST_ROOT_NAME {
+            dot2::Style::Dotted
+        } else {
+            dot2::Style::None
+        }
+    }
+
+    fn edge_label(&'a self, e: &Self::Edge) -> dot2::label::Text<'a> {
+        self.0.edge_label(e)
+    }
+
+    fn edge_color(&'a self, e: &Self::Edge) -> Option<dot2::label::Text<'a>> {
+        self.0.edge_color(e)
+    }
+
+    fn subgraph_id(&'a self, s: &Self::Subgraph) -> Option<dot2::Id<'a>> {
+        self.0.subgraph_id(s)
+    }
+
+    fn subgraph_label(&'a self, s: &Self::Subgraph) -> dot2::label::Text<'a> {
+        self.0.subgraph_label(s)
+    }
+}
+
+impl<'a> dot2::GraphWalk<'a> for Legend {
+    type Node = Node<(), usize>;
+    type Edge = Edge<usize>;
+    type Subgraph = usize;
+
+    fn nodes(&'a self) -> dot2::Nodes<'a, Self::Node> {
+        self.0.nodes()
+    }
+
+    fn edges(&'a self) -> dot2::Edges<'a, Self::Edge> {
+        self.0.edges()
+    }
+
+    fn source(&'a self, edge: &Self::Edge) -> Self::Node {
+        self.0.source(edge)
+    }
+
+    fn target(&'a self, edge: &Self::Edge) -> Self::Node {
+        self.0.target(edge)
+    }
+
+    fn subgraphs(&'a self) -> dot2::Subgraphs<'a, Self::Subgraph> {
+        self.0.subgraphs()
+    }
+
+    fn subgraph_nodes(&'a self, s: &Self::Subgraph) -> dot2::Nodes<'a, Self::Node> {
+        self.0.subgraph_nodes(s)
     }
 }
 
@@ -109,6 +210,7 @@ pub(crate) struct Vertex<'a> {
     pub(crate) instruction: Instruction<'a, Arg>,
     pub(crate) parsed: Parsed<Arg>,
     pub(crate) invocation: Pointer,
+    pub(crate) resources: Resources,
 }
 
 /// [Origin] of a [Cid] being in/not-in a [Workflow] itself.
@@ -147,11 +249,13 @@ impl<'a> Vertex<'a> {
         instruction: Instruction<'a, Arg>,
         parsed: Parsed<Arg>,
         invocation: Pointer,
+        resources: Resources,
     ) -> Vertex<'a> {
         Vertex {
             instruction,
             parsed,
             invocation,
+            resources,
         }
     }
 }
@@ -174,7 +278,9 @@ impl<'a> Builder<'a> {
 
     /// Convert the [Workflow] into an batch-separated [ExecutionGraph].
     pub(crate) fn graph(self) -> Result<ExecutionGraph<'a>, Error> {
-        let aot = self.aot()?;
+        let aot = self
+            .aot()
+            .map_err(|err| err.downcast::<Error>().unwrap_or_else(Error::AoT))?;
         if let Err(_e) = aot.dag.detect_duplicates() {
             homestar_invocation::bail!(Error::DuplicateTask)
         }
@@ -219,6 +325,18 @@ impl<'a> Builder<'a> {
                     // Clone as we're owning the struct going backward.
                     let ptr: Pointer = Invocation::<Arg>::from(task.clone()).try_into()?;
 
+                    // Tasks without resource configuration fall back to
+                    // the defaults, but invalid configuration is rejected.
+                    let task_resources = match task.meta() {
+                        Ipld::Null => Resources::default(),
+                        meta => {
+                            Resources::try_from(meta).map_err(|err| Error::InvalidResources {
+                                instruction: instr_cid.to_string(),
+                                reason: err.to_string(),
+                            })?
+                        }
+                    };
+
                     let RunInstruction::Expanded(instr) = task.into_instruction() else {
                         bail!("workflow tasks/instructions must be expanded / inlined")
                     };
@@ -249,9 +367,10 @@ impl<'a> Builder<'a> {
                             .or_insert_with(|| vec![Resource::Cid(cid.to_owned())]);
                     });
 
-                    let node = Node::new(Vertex::new(instr.to_owned(), parsed, ptr))
-                        .with_name(instr_cid.to_string())
-                        .with_result(i);
+                    let node =
+                        Node::new(Vertex::new(instr.to_owned(), parsed, ptr, task_resources))
+                            .with_name(instr_cid.to_string())
+                            .with_result(i);
 
                     if !reads.is_empty() {
                         dag.add_node(node.with_reads(reads.clone()));
@@ -532,6 +651,23 @@ mod test {
             .any(|node| node.name() == instr1 || node.name() == instr2));
     }
 
+    #[test]
+    fn reject_invalid_resources() {
+        let instruction = test_utils::wasm_instruction::<Arg>();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            Ipld::String("fuel".to_string()),
+            UcanPrf::default(),
+        );
+        let instruction_cid = task.instruction_cid().unwrap().to_string();
+
+        let builder = Builder::new(Workflow::new(vec![task]));
+        assert!(matches!(
+            builder.graph(),
+            Err(Error::InvalidResources { instruction, .. }) if instruction == instruction_cid
+        ));
+    }
+
     #[test]
     fn build_seq_schedule() {
         let config = Resources::default();
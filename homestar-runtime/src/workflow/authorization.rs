@@ -0,0 +1,613 @@
+//! Authorization of a [Workflow]'s tasks by the UCANs linked from their
+//! proofs (`prf`).
+//!
+//! A task is authorized when one of its proofs is a UCAN addressed to the
+//! node, granting the task's ability on its resource, and delegated from one
+//! of the node's root authorities through the UCANs linked from its own
+//! proofs. Every UCAN in the chain must be signed by its issuer and within
+//! its time bounds.
+//!
+//! Caveats aren't understood by the node, so only capabilities without
+//! caveats, e.g. `{}`, authorize a task.
+//!
+//! [Workflow]: homestar_workflow::Workflow
+
+use crate::{db::Database, settings, workflow::Error, Db};
+use homestar_invocation::task::{instruction::RunInstruction, Task};
+use homestar_wasm::io::Arg;
+use homestar_workflow::Workflow;
+use libipld::{
+    multihash::{Code, MultihashDigest},
+    Cid,
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use ucan::{
+    crypto::did::{DidParser, KeyConstructorSlice, ED25519_MAGIC_BYTES},
+    Ucan,
+};
+use ucan_key_support::ed25519::bytes_to_ed25519_key;
+
+/// Key types UCANs may be signed with.
+const SUPPORTED_KEYS: &KeyConstructorSlice = &[(ED25519_MAGIC_BYTES, bytes_to_ed25519_key)];
+
+/// Maximum number of UCANs in a delegation chain.
+const MAX_CHAIN_LENGTH: usize = 16;
+
+/// Authorize all tasks of a [Workflow] by the UCANs linked from their proofs,
+/// if enforced by the node's [settings::Ucan].
+///
+/// Linked UCANs are resolved from the encoded `tokens` sent along with the
+/// workflow, then from the UCANs stored by previous runs. Once the workflow
+/// is authorized, the tokens of the delegation chains that authorized its
+/// tasks are stored for later runs, e.g. on resume. Other `tokens` are
+/// dropped.
+///
+/// [Workflow]: homestar_workflow::Workflow
+pub(crate) async fn authorize(
+    workflow: &Workflow<'_, Arg>,
+    tokens: &[String],
+    settings: &settings::Ucan,
+    audience: &str,
+    db: &impl Database,
+) -> Result<(), Error> {
+    if !settings.enforce {
+        return Ok(());
+    }
+
+    let mut authorizer = Authorizer {
+        tokens,
+        settings,
+        audience,
+        db,
+        did_parser: DidParser::new(SUPPORTED_KEYS),
+        verified: HashMap::new(),
+    };
+
+    for task in workflow.tasks_ref() {
+        authorizer.authorize_task(task).await?;
+    }
+
+    if !authorizer.verified.is_empty() {
+        let ucans: Vec<(Cid, String)> = authorizer.verified.into_iter().collect();
+        Db::store_ucans(&ucans, &mut db.conn()?).map_err(anyhow::Error::from)?;
+    }
+
+    Ok(())
+}
+
+/// Walks delegation chains for the tasks of a single workflow.
+struct Authorizer<'a, DB: Database> {
+    tokens: &'a [String],
+    settings: &'a settings::Ucan,
+    audience: &'a str,
+    db: &'a DB,
+    did_parser: DidParser,
+    /// Tokens of the delegation chains that authorized a task, by the Cid
+    /// they were resolved by.
+    verified: HashMap<Cid, String>,
+}
+
+impl<'a, DB: Database> Authorizer<'a, DB> {
+    async fn authorize_task(&mut self, task: &Task<'_, Arg>) -> Result<(), Error> {
+        let instruction = task
+            .instruction_cid()
+            .map(|cid| cid.to_string())
+            .unwrap_or_default();
+        let unauthorized = |reason: String| Error::Unauthorized {
+            instruction: instruction.clone(),
+            reason,
+        };
+
+        let RunInstruction::Expanded(run) = task.run() else {
+            return Err(unauthorized(
+                "instruction pointers cannot be authorized".to_string(),
+            ));
+        };
+        let resource = run.resource().to_string();
+        let ability = run.op().to_string();
+
+        if task.prf().inner().is_empty() {
+            return Err(unauthorized("task has no proofs".to_string()));
+        }
+
+        let mut reasons = vec![];
+        for link in task.prf().inner() {
+            match self.authorize_chain(*link.cid(), &resource, &ability).await {
+                Ok(()) => return Ok(()),
+                Err(reason) => reasons.push(format!("{}: {reason}", link.cid())),
+            }
+        }
+
+        Err(unauthorized(reasons.join("; ")))
+    }
+
+    /// Check that the UCAN at `cid` grants `ability` on `resource` to the
+    /// node, through a valid delegation chain from a root authority.
+    async fn authorize_chain(
+        &mut self,
+        cid: Cid,
+        resource: &str,
+        ability: &str,
+    ) -> Result<(), String> {
+        let now = now();
+        let (token, mut ucan) = self.resolve(cid)?;
+        if ucan.audience() != self.audience {
+            return Err(format!(
+                "UCAN is addressed to {}, not this node",
+                ucan.audience()
+            ));
+        }
+        self.check(&ucan, resource, ability, now).await?;
+        let mut chain = vec![(cid, token)];
+
+        for _ in 0..MAX_CHAIN_LENGTH {
+            if self
+                .settings
+                .root_authorities
+                .iter()
+                .any(|root| root == ucan.issuer())
+            {
+                self.verified.extend(chain);
+                return Ok(());
+            }
+
+            let mut parent = None;
+            for proof in ucan.proofs().iter().flatten() {
+                let Ok((cid, (token, proof))) = Cid::try_from(proof.as_str())
+                    .map_err(|err| err.to_string())
+                    .and_then(|cid| Ok((cid, self.resolve(cid)?)))
+                else {
+                    continue;
+                };
+
+                if proof.audience() == ucan.issuer()
+                    && outlives(&proof, &ucan)
+                    && self.check(&proof, resource, ability, now).await.is_ok()
+                {
+                    chain.push((cid, token));
+                    parent = Some(proof);
+                    break;
+                }
+            }
+
+            ucan = parent.ok_or_else(|| {
+                format!(
+                    "no valid delegation to {} from a root authority",
+                    ucan.issuer()
+                )
+            })?;
+        }
+
+        Err(format!(
+            "delegation chain is longer than {MAX_CHAIN_LENGTH} UCANs"
+        ))
+    }
+
+    /// Check a single UCAN's time bounds, capabilities and signature.
+    async fn check(
+        &mut self,
+        ucan: &Ucan,
+        resource: &str,
+        ability: &str,
+        now: u64,
+    ) -> Result<(), String> {
+        if ucan.is_expired(Some(now)) {
+            return Err(format!("UCAN issued by {} has expired", ucan.issuer()));
+        }
+
+        if ucan.not_before().is_some_and(|nbf| nbf > now) {
+            return Err(format!("UCAN issued by {} is not yet valid", ucan.issuer()));
+        }
+
+        if !ucan.capabilities().iter().any(|cap| {
+            unrestricted(&cap.caveat) && covers(&cap.resource, &cap.ability, resource, ability)
+        }) {
+            return Err(format!(
+                "UCAN issued by {} does not grant {ability} on {resource}",
+                ucan.issuer()
+            ));
+        }
+
+        ucan.check_signature(&mut self.did_parser)
+            .await
+            .map_err(|err| format!("invalid signature by {}: {err}", ucan.issuer()))
+    }
+
+    /// Resolve an encoded UCAN by its Cid, from the tokens sent along with
+    /// the workflow or from the database, returning the token along with the
+    /// decoded UCAN.
+    fn resolve(&self, cid: Cid) -> Result<(String, Ucan), String> {
+        let token = match self.tokens.iter().find(|token| matches_cid(token, cid)) {
+            Some(token) => token.to_owned(),
+            None => {
+                let mut conn = self.db.conn().map_err(|err| err.to_string())?;
+                Db::find_ucan(cid, &mut conn)
+                    .map_err(|err| err.to_string())?
+                    .ok_or_else(|| format!("UCAN {cid} not found"))?
+            }
+        };
+
+        let ucan =
+            Ucan::try_from(token.as_str()).map_err(|err| format!("invalid UCAN {cid}: {err}"))?;
+        Ok((token, ucan))
+    }
+}
+
+/// Whether a capability granted on `granted_resource` for `granted_ability`
+/// covers calling `ability` on `resource`.
+///
+/// Resources must match exactly, unless `*` is granted. Abilities are
+/// case-insensitive, and may be granted for a whole namespace, e.g. `wasm/*`.
+fn covers(granted_resource: &str, granted_ability: &str, resource: &str, ability: &str) -> bool {
+    let resource_covered = granted_resource == "*" || granted_resource == resource;
+    let ability_covered = granted_ability == "*"
+        || granted_ability.eq_ignore_ascii_case(ability)
+        || granted_ability.strip_suffix("/*").is_some_and(|namespace| {
+            ability
+                .split_once('/')
+                .is_some_and(|(prefix, _)| prefix.eq_ignore_ascii_case(namespace))
+        });
+
+    resource_covered && ability_covered
+}
+
+/// Whether a capability's caveat places no restrictions, i.e. is empty, or
+/// lists an empty caveat among the alternatives it allows.
+fn unrestricted(caveat: &Value) -> bool {
+    match caveat {
+        Value::Null => true,
+        Value::Object(caveat) => caveat.is_empty(),
+        Value::Array(caveats) => caveats.iter().any(unrestricted),
+        _ => false,
+    }
+}
+
+/// Whether a delegated `proof` remains valid for at least as long as the
+/// `ucan` it authorizes.
+fn outlives(proof: &Ucan, ucan: &Ucan) -> bool {
+    match (proof.expires_at(), ucan.expires_at()) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(parent), Some(child)) => parent >= child,
+    }
+}
+
+/// Whether an encoded UCAN token is addressed by a Cid, whatever its codec
+/// and hash function.
+fn matches_cid(token: &str, cid: Cid) -> bool {
+    Code::try_from(cid.hash().code())
+        .map(|code| Cid::new_v1(cid.codec(), code.digest(token.as_bytes())) == cid)
+        .unwrap_or(false)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::db::MemoryDb;
+    use homestar_invocation::{authority::UcanPrf, ipld::Link, test_utils};
+    use libipld::Ipld;
+    use serde_json::json;
+    use ucan::{builder::UcanBuilder, capability::Capability, crypto::KeyMaterial};
+    use ucan_key_support::ed25519::Ed25519KeyMaterial;
+
+    const RESOURCE: &str = "ipfs://bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q";
+
+    /// Raw multicodec, used to address encoded UCAN tokens.
+    const RAW: u64 = 0x55;
+
+    /// Cid of an encoded UCAN token, as UCANs link to their proofs by default.
+    fn token_cid(token: &str) -> Cid {
+        Cid::new_v1(RAW, Code::Blake3_256.digest(token.as_bytes()))
+    }
+
+    /// Generate an ed25519 key, along with its `did:key`.
+    async fn key() -> (Ed25519KeyMaterial, String) {
+        let private = ed25519_zebra::SigningKey::from(rand::random::<[u8; 32]>());
+        let key = Ed25519KeyMaterial(
+            ed25519_zebra::VerificationKey::from(&private),
+            Some(private),
+        );
+        let did = key.get_did().await.unwrap();
+        (key, did)
+    }
+
+    fn capability(resource: &str, ability: &str, caveat: Value) -> Capability {
+        Capability::new(resource.to_string(), ability.to_string(), caveat)
+    }
+
+    /// Sign a UCAN delegating `capability` from `issuer` to `audience`,
+    /// linking to its `proof`, if any.
+    async fn delegate(
+        issuer: &Ed25519KeyMaterial,
+        audience: &str,
+        capability: Capability,
+        proof: Option<&Ucan>,
+        expiration: u64,
+        not_before: Option<u64>,
+    ) -> Ucan {
+        let mut builder = UcanBuilder::default()
+            .issued_by(issuer)
+            .for_audience(audience)
+            .with_expiration(expiration)
+            .claiming_capability(capability);
+        if let Some(not_before) = not_before {
+            builder = builder.not_before(not_before);
+        }
+        if let Some(proof) = proof {
+            builder = builder.witnessed_by(proof, None);
+        }
+
+        builder.build().unwrap().sign().await.unwrap()
+    }
+
+    fn encode(ucans: &[&Ucan]) -> Vec<String> {
+        ucans.iter().map(|ucan| ucan.encode().unwrap()).collect()
+    }
+
+    /// Workflow of a single task, with proofs linking to `ucans`.
+    fn workflow(ucans: &[&Ucan]) -> Workflow<'static, Arg> {
+        let prf = encode(ucans)
+            .iter()
+            .map(|token| Link::new(token_cid(token)))
+            .collect();
+
+        Workflow::new(vec![Task::new(
+            RunInstruction::Expanded(test_utils::wasm_instruction::<Arg>()),
+            Ipld::Null,
+            UcanPrf::new(prf),
+        )])
+    }
+
+    /// Assert that a workflow whose task is proved by `ucan` alone is not
+    /// authorized, for a reason containing `expected`.
+    async fn assert_rejected(
+        ucan: &Ucan,
+        settings: &settings::Ucan,
+        audience: &str,
+        db: &impl Database,
+        expected: &str,
+    ) {
+        match authorize(&workflow(&[ucan]), &encode(&[ucan]), settings, audience, db).await {
+            Err(Error::Unauthorized { reason, .. }) => assert!(
+                reason.contains(expected),
+                "expected {expected:?}, got {reason:?}"
+            ),
+            result => panic!("expected an unauthorized error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn capabilities_cover_tasks() {
+        let rsc = "ipfs://bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q";
+
+        assert!(covers(rsc, "wasm/run", rsc, "wasm/run"));
+        assert!(covers(rsc, "WASM/RUN", rsc, "wasm/run"));
+        assert!(covers(rsc, "wasm/*", rsc, "wasm/run"));
+        assert!(covers("*", "*", rsc, "wasm/run"));
+        assert!(!covers(rsc, "wasm/run", "ipfs://other", "wasm/run"));
+        assert!(!covers(rsc, "wasm/*", rsc, "wasi/run"));
+        assert!(!covers(rsc, "wasm/run", rsc, "wasm/call"));
+    }
+
+    #[test]
+    fn tokens_match_their_cids() {
+        let token = "eyJhbGciOiJFZERTQSIsInR5cCI6IkpXVCJ9.e30.c2ln";
+        let cid = token_cid(token);
+
+        assert!(matches_cid(token, cid));
+        assert!(!matches_cid("eyJ9.e30.c2ln", cid));
+        assert!(matches_cid(
+            token,
+            Cid::new_v1(0x55, Code::Sha2_256.digest(token.as_bytes()))
+        ));
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn authorizes_per_settings() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let workflow = workflow(&[]);
+        let audience = "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp";
+
+        assert!(
+            authorize(&workflow, &[], &settings::Ucan::default(), audience, &db)
+                .await
+                .is_ok()
+        );
+
+        let enforced = settings::Ucan {
+            enforce: true,
+            root_authorities: vec![audience.to_string()],
+        };
+        assert!(matches!(
+            authorize(&workflow, &[], &enforced, audience, &db).await,
+            Err(Error::Unauthorized { reason, .. }) if reason == "task has no proofs"
+        ));
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn authorizes_delegation_chains() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let (root, root_did) = key().await;
+        let (alice, alice_did) = key().await;
+        let (_, node_did) = key().await;
+        let enforced = settings::Ucan {
+            enforce: true,
+            root_authorities: vec![root_did],
+        };
+        let expiration = now() + 3600;
+
+        let root_to_alice = delegate(
+            &root,
+            &alice_did,
+            capability(RESOURCE, "wasm/*", json!({})),
+            None,
+            expiration,
+            None,
+        )
+        .await;
+        let alice_to_node = delegate(
+            &alice,
+            &node_did,
+            capability(RESOURCE, "wasm/run", json!({})),
+            Some(&root_to_alice),
+            expiration,
+            None,
+        )
+        .await;
+
+        let unrelated = delegate(
+            &alice,
+            &node_did,
+            capability(RESOURCE, "wasm/run", json!({})),
+            None,
+            expiration,
+            None,
+        )
+        .await;
+
+        let workflow = workflow(&[&alice_to_node]);
+        let mut tokens = encode(&[&root_to_alice, &alice_to_node, &unrelated]);
+        tokens.push("not a UCAN".to_string());
+        assert!(authorize(&workflow, &tokens, &enforced, &node_did, &db)
+            .await
+            .is_ok());
+
+        // Tokens of the authorizing chain are stored once authorized, e.g.
+        // for the workflow to be authorized again on resume, but no others.
+        assert!(authorize(&workflow, &[], &enforced, &node_did, &db)
+            .await
+            .is_ok());
+        let mut conn = db.conn().unwrap();
+        for token in &tokens[2..] {
+            assert!(MemoryDb::find_ucan(token_cid(token), &mut conn)
+                .unwrap()
+                .is_none());
+        }
+    }
+
+    #[homestar_runtime_proc_macro::db_async_test]
+    fn rejects_invalid_delegations() {
+        let settings = TestSettings::load();
+        let db = MemoryDb::setup_connection_pool(settings.node(), None).unwrap();
+        let (root, root_did) = key().await;
+        let (alice, alice_did) = key().await;
+        let (_, node_did) = key().await;
+        let enforced = settings::Ucan {
+            enforce: true,
+            root_authorities: vec![root_did],
+        };
+        let expiration = now() + 3600;
+        let wasm_run = || capability(RESOURCE, "wasm/run", json!({}));
+
+        let expired = delegate(&root, &node_did, wasm_run(), None, now() - 60, None).await;
+        assert_rejected(&expired, &enforced, &node_did, &db, "has expired").await;
+
+        let not_yet_valid = delegate(
+            &root,
+            &node_did,
+            wasm_run(),
+            None,
+            expiration,
+            Some(now() + 1800),
+        )
+        .await;
+        assert_rejected(
+            &not_yet_valid,
+            &enforced,
+            &node_did,
+            &db,
+            "is not yet valid",
+        )
+        .await;
+
+        let other_audience = delegate(&root, &alice_did, wasm_run(), None, expiration, None).await;
+        assert_rejected(&other_audience, &enforced, &node_did, &db, "not this node").await;
+
+        let other_resource = delegate(
+            &root,
+            &node_did,
+            capability("ipfs://other", "wasm/run", json!({})),
+            None,
+            expiration,
+            None,
+        )
+        .await;
+        assert_rejected(
+            &other_resource,
+            &enforced,
+            &node_did,
+            &db,
+            "does not grant wasm/run",
+        )
+        .await;
+
+        let other_ability = delegate(
+            &root,
+            &node_did,
+            capability(RESOURCE, "wasi/run", json!({})),
+            None,
+            expiration,
+            None,
+        )
+        .await;
+        assert_rejected(
+            &other_ability,
+            &enforced,
+            &node_did,
+            &db,
+            "does not grant wasm/run",
+        )
+        .await;
+
+        let with_caveats = delegate(
+            &root,
+            &node_did,
+            capability(RESOURCE, "wasm/run", json!({"max_fuel": "100"})),
+            None,
+            expiration,
+            None,
+        )
+        .await;
+        assert_rejected(
+            &with_caveats,
+            &enforced,
+            &node_did,
+            &db,
+            "does not grant wasm/run",
+        )
+        .await;
+
+        // Not delegated from a root authority.
+        let unrooted = delegate(&alice, &node_did, wasm_run(), None, expiration, None).await;
+        assert_rejected(
+            &unrooted,
+            &enforced,
+            &node_did,
+            &db,
+            "no valid delegation to",
+        )
+        .await;
+
+        // Signed over another payload.
+        let valid = delegate(&root, &node_did, wasm_run(), None, expiration, None).await;
+        let token = valid.encode().unwrap();
+        let other = unrooted.encode().unwrap();
+        let (payload, _) = token.rsplit_once('.').unwrap();
+        let (_, signature) = other.rsplit_once('.').unwrap();
+        let forged = Ucan::try_from(format!("{payload}.{signature}").as_str()).unwrap();
+        assert_rejected(&forged, &enforced, &node_did, &db, "invalid signature").await;
+    }
+}
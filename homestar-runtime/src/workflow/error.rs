@@ -8,6 +8,66 @@ pub(crate) enum Error {
         "workflow cannot contain duplicate tasks: use a nonce (nnc field) to ensure uniqueness"
     )]
     DuplicateTask,
+    /// Task's resource configuration could not be parsed.
+    #[error("task {instruction} has invalid resource configuration: {reason}")]
+    InvalidResources {
+        /// Cid of the task's instruction.
+        instruction: String,
+        /// Reason the configuration could not be parsed.
+        reason: String,
+    },
+    /// Task requests resources beyond the node's configured limits.
+    #[error(
+        "task {instruction} requests {requested} {resource}, exceeding the node limit of {limit}"
+    )]
+    ResourceLimitExceeded {
+        /// Cid of the task's instruction.
+        instruction: String,
+        /// Name of the resource, e.g. fuel.
+        resource: &'static str,
+        /// Amount requested by the task.
+        requested: u64,
+        /// Limit configured on the node.
+        limit: u64,
+    },
+    /// Task requests a WASI capability not allowed by the node.
+    #[error("task {instruction} requests {capability} {requested}, which the node does not allow")]
+    WasiCapabilityNotAllowed {
+        /// Cid of the task's instruction.
+        instruction: String,
+        /// Kind of capability, e.g. directory.
+        capability: &'static str,
+        /// Capability requested by the task.
+        requested: String,
+    },
+    /// Task runs a Wasm module not allowed by the node's module policy.
+    #[error("task {instruction} runs module {module}, which the node does not allow: {reason}")]
+    ModuleNotAllowed {
+        /// Cid of the task's instruction.
+        instruction: String,
+        /// Resource of the denied module.
+        module: String,
+        /// Reason the module was denied.
+        reason: String,
+    },
+    /// Task is not authorized by the UCANs linked from its proofs.
+    #[error("task {instruction} is not authorized: {reason}")]
+    Unauthorized {
+        /// Cid of the task's instruction.
+        instruction: String,
+        /// Reason no proof authorizes the task.
+        reason: String,
+    },
+    /// Workflow requests a setting beyond the node's configured limits.
+    #[error("workflow requests {requested} {setting}, exceeding the node limit of {limit}")]
+    SettingLimitExceeded {
+        /// Name of the setting, e.g. retries.
+        setting: &'static str,
+        /// Amount requested by the workflow.
+        requested: u64,
+        /// Limit configured on the node.
+        limit: u64,
+    },
     /// Invalid schedule error.
     #[error("Schedule could not be generated from workflow: {0}")]
     InvalidSchedule(String),
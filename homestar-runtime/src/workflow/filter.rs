@@ -0,0 +1,80 @@
+//! Filters for listing stored [Workflow]s.
+//!
+//! [Workflow]: homestar_workflow::Workflow
+
+use super::Status;
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+/// Default maximum number of workflows listed.
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Filters for listing workflows with the `workflow list` command.
+#[derive(Debug, Clone, PartialEq, Args, Serialize, Deserialize)]
+pub struct Filter {
+    /// Only list workflows with this status.
+    #[arg(
+        short = 's',
+        long = "status",
+        value_name = "STATUS",
+        help = "Only list workflows with this status: pending, running, completed, stuck, failed or cancelled [optional]"
+    )]
+    pub status: Option<Status>,
+    /// Only list workflows with this local name.
+    #[arg(
+        short = 'n',
+        long = "name",
+        value_name = "NAME",
+        help = "Only list workflows with this local name [optional]"
+    )]
+    pub name: Option<String>,
+    /// Only list workflows created on or after this date.
+    #[arg(
+        long = "since",
+        value_name = "DATE",
+        help = "Only list workflows created on or after this date, e.g. 2024-01-31 [optional]"
+    )]
+    pub since: Option<NaiveDate>,
+    /// Only list workflows created on or before this date.
+    #[arg(
+        long = "until",
+        value_name = "DATE",
+        help = "Only list workflows created on or before this date, e.g. 2024-01-31 [optional]"
+    )]
+    pub until: Option<NaiveDate>,
+    /// Maximum number of workflows listed, most recently created first.
+    #[arg(
+        long = "limit",
+        value_name = "LIMIT",
+        default_value_t = DEFAULT_LIMIT,
+        help = "Maximum number of workflows listed, most recently created first"
+    )]
+    pub limit: u32,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            status: None,
+            name: None,
+            since: None,
+            until: None,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+impl Filter {
+    /// Inclusive lower bound on creation time, at the start of `since`.
+    pub(crate) fn created_since(&self) -> Option<NaiveDateTime> {
+        self.since.and_then(|date| date.and_hms_opt(0, 0, 0))
+    }
+
+    /// Exclusive upper bound on creation time, at the end of `until`.
+    pub(crate) fn created_until(&self) -> Option<NaiveDateTime> {
+        self.until
+            .and_then(|date| date.succ_opt())
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    }
+}
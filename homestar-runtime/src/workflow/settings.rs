@@ -2,8 +2,21 @@
 //!
 //! [Workflow]: homestar_workflow::Workflow
 
+use super::Error;
+use crate::settings::WorkflowLimits;
+use homestar_invocation::ipld::DagCbor;
+use libipld::{
+    serde::{from_ipld, to_ipld},
+    Ipld,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds, DurationSeconds};
 use std::time::Duration;
 
+/// Key of the optional settings block of a workflow.
+pub(crate) const SETTINGS_KEY: &str = "settings";
+
 /// Workflow settings.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Settings {
@@ -17,6 +30,123 @@ pub struct Settings {
     pub(crate) timeout: Duration,
 }
 
+impl Settings {
+    /// Resolve [RequestedSettings] against the node's [WorkflowLimits].
+    ///
+    /// Settings not requested fall back to their defaults, capped by the
+    /// limits.
+    pub(crate) fn resolve(
+        requested: &RequestedSettings,
+        limits: &WorkflowLimits,
+    ) -> Result<Self, Error> {
+        let defaults = Self::default();
+        let check = |setting, requested: Option<u64>, default: u64, limit: u64| match requested {
+            Some(requested) if requested > limit => Err(Error::SettingLimitExceeded {
+                setting,
+                requested,
+                limit,
+            }),
+            Some(requested) => Ok(requested),
+            None => Ok(std::cmp::min(default, limit)),
+        };
+        let as_millis = |duration: Duration| duration.as_millis() as u64;
+
+        let retries = check(
+            "retries",
+            requested.retries.map(u64::from),
+            defaults.retries.into(),
+            limits.max_retries.into(),
+        )?;
+        let retry_max_delay = check(
+            "ms of maximum retry delay",
+            requested.retry_max_delay.map(as_millis),
+            as_millis(defaults.retry_max_delay),
+            as_millis(limits.max_retry_delay),
+        )?;
+        let retry_initial_delay = check(
+            "ms of initial retry delay",
+            requested.retry_initial_delay.map(as_millis),
+            as_millis(defaults.retry_initial_delay),
+            retry_max_delay,
+        )?;
+        let timeout = check(
+            "seconds of timeout",
+            requested.timeout.map(|timeout| timeout.as_secs()),
+            defaults.timeout.as_secs(),
+            limits.max_timeout.as_secs(),
+        )?;
+
+        Ok(Self {
+            retries: retries as u32,
+            retry_max_delay: Duration::from_millis(retry_max_delay),
+            retry_initial_delay: Duration::from_millis(retry_initial_delay),
+            timeout: Duration::from_secs(timeout),
+        })
+    }
+}
+
+/// Workflow settings requested for a run, e.g. through the `settings`
+/// block of a workflow file.
+///
+/// Settings left out fall back to the node's defaults.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+#[schemars(
+    title = "Workflow settings",
+    description = "Settings requested for a workflow run"
+)]
+pub struct RequestedSettings {
+    /// Number of retries for the workflow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) retries: Option<u32>,
+    /// Maximum delay between retries, in milliseconds.
+    #[serde_as(as = "Option<DurationMilliSeconds<u64>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<u64>")]
+    pub(crate) retry_max_delay: Option<Duration>,
+    /// Initial delay between retries, in milliseconds.
+    #[serde_as(as = "Option<DurationMilliSeconds<u64>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<u64>")]
+    pub(crate) retry_initial_delay: Option<Duration>,
+    /// Timeout for the workflow, in seconds.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<u64>")]
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl RequestedSettings {
+    /// Read the optional `settings` block of a workflow, given as Ipld,
+    /// next to its tasks.
+    pub(crate) fn from_workflow_ipld(ipld: &Ipld) -> anyhow::Result<Self> {
+        match ipld {
+            Ipld::Map(map) => map.get(SETTINGS_KEY).map_or_else(
+                || Ok(Self::default()),
+                |settings| settings.clone().try_into(),
+            ),
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+impl From<RequestedSettings> for Ipld {
+    fn from(settings: RequestedSettings) -> Self {
+        to_ipld(settings).unwrap_or(Ipld::Null)
+    }
+}
+
+impl TryFrom<Ipld> for RequestedSettings {
+    type Error = anyhow::Error;
+
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        Ok(from_ipld(ipld)?)
+    }
+}
+
+impl DagCbor for RequestedSettings {}
+
 #[cfg(all(not(test), not(feature = "test-utils")))]
 impl Default for Settings {
     fn default() -> Self {
@@ -41,3 +171,80 @@ impl Default for Settings {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn resolve_requested_settings() {
+        let limits = WorkflowLimits::default();
+
+        let settings = Settings::resolve(&RequestedSettings::default(), &limits).unwrap();
+        assert_eq!(settings, Settings::default());
+
+        let requested = RequestedSettings {
+            retries: Some(2),
+            timeout: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let settings = Settings::resolve(&requested, &limits).unwrap();
+        assert_eq!(settings.retries, 2);
+        assert_eq!(settings.timeout, Duration::from_secs(60));
+        assert_eq!(
+            settings.retry_max_delay,
+            Settings::default().retry_max_delay
+        );
+
+        let requested = RequestedSettings {
+            timeout: Some(limits.max_timeout + Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let err = Settings::resolve(&requested, &limits).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "workflow requests 86401 seconds of timeout, exceeding the node limit of 86400"
+        );
+    }
+
+    #[test]
+    fn requested_settings_from_workflow_ipld() {
+        let ipld = Ipld::Map(BTreeMap::from([
+            ("tasks".into(), Ipld::List(vec![])),
+            (
+                SETTINGS_KEY.into(),
+                Ipld::Map(BTreeMap::from([
+                    ("retries".into(), Ipld::Integer(1)),
+                    ("retry_initial_delay".into(), Ipld::Integer(250)),
+                ])),
+            ),
+        ]));
+
+        let requested = RequestedSettings::from_workflow_ipld(&ipld).unwrap();
+        assert_eq!(
+            requested,
+            RequestedSettings {
+                retries: Some(1),
+                retry_initial_delay: Some(Duration::from_millis(250)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            RequestedSettings::try_from(Ipld::from(requested.clone())).unwrap(),
+            requested
+        );
+
+        let ipld = Ipld::Map(BTreeMap::from([("tasks".into(), Ipld::List(vec![]))]));
+        assert_eq!(
+            RequestedSettings::from_workflow_ipld(&ipld).unwrap(),
+            RequestedSettings::default()
+        );
+
+        let ipld = Ipld::Map(BTreeMap::from([(
+            SETTINGS_KEY.into(),
+            Ipld::Map(BTreeMap::from([("retry".into(), Ipld::Integer(1))])),
+        )]));
+        assert!(RequestedSettings::from_workflow_ipld(&ipld).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Ahead-of-time validation of a [Workflow], without running it.
+//!
+//! [Workflow]: homestar_workflow::Workflow
+
+use super::{AOTContext, Builder, Vertex};
+use crate::{scheduler::ExecutionGraph, tasks::RegisteredTasks};
+use dagga::Node;
+use homestar_invocation::{
+    ipld::DagCbor,
+    task::instruction::{Parse, RunInstruction},
+};
+use libipld::Cid;
+use std::{collections::HashMap, fmt};
+
+/// Issue found when validating a [Workflow].
+///
+/// [Workflow]: homestar_workflow::Workflow
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Issue {
+    /// Workflow contains duplicate tasks, which need a nonce to be unique.
+    DuplicateTask,
+    /// Workflow's tasks await each other in a cycle.
+    Cycle(String),
+    /// Schedule could not be generated from the workflow.
+    InvalidSchedule(String),
+    /// Task calls an ability not registered with the runtime.
+    UnknownAbility {
+        /// Cid of the task's instruction.
+        instruction: Cid,
+        /// Ability called by the task.
+        ability: String,
+    },
+    /// Task awaits an instruction that is not part of the workflow, which
+    /// can only be resolved from the network at runtime.
+    DanglingAwait {
+        /// Cid of the task's instruction.
+        instruction: Cid,
+        /// Cid of the awaited instruction.
+        awaits: Cid,
+    },
+}
+
+impl Issue {
+    /// Whether the issue prevents the workflow from running.
+    pub(crate) fn is_error(&self) -> bool {
+        !matches!(self, Issue::DanglingAwait { .. })
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::DuplicateTask => write!(
+                f,
+                "workflow cannot contain duplicate tasks: use a nonce (nnc field) to ensure uniqueness"
+            ),
+            Issue::Cycle(err) => write!(f, "workflow contains a cycle: {err}"),
+            Issue::InvalidSchedule(err) => {
+                write!(f, "schedule could not be generated from workflow: {err}")
+            }
+            Issue::UnknownAbility {
+                instruction,
+                ability,
+            } => write!(f, "task {instruction} calls unknown ability {ability}"),
+            Issue::DanglingAwait {
+                instruction,
+                awaits,
+            } => write!(
+                f,
+                "task {instruction} awaits {awaits}, which is not part of the workflow"
+            ),
+        }
+    }
+}
+
+/// Result of validating a [Workflow] ahead-of-time.
+///
+/// [Workflow]: homestar_workflow::Workflow
+#[derive(Debug, Clone)]
+pub(crate) struct Validation<'a> {
+    /// Cid of the validated [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) cid: Cid,
+    /// Number of tasks in the [Workflow].
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) num_tasks: u32,
+    /// Issues found, errors and warnings alike.
+    pub(crate) issues: Vec<Issue>,
+    /// Batched execution graph, if one could be built.
+    pub(crate) graph: Option<ExecutionGraph<'a>>,
+    /// Ahead-of-time context the graph is built from.
+    aot: AOTContext<'a>,
+}
+
+impl<'a> Validation<'a> {
+    /// Whether the [Workflow] can be run, i.e. no errors were found.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) fn is_valid(&self) -> bool {
+        !self.issues.iter().any(Issue::is_error)
+    }
+
+    /// Render the batched schedule as a [Mermaid] flowchart.
+    ///
+    /// [Mermaid]: <https://mermaid.js.org/syntax/flowchart.html>
+    pub(crate) fn mermaid(&self) -> Option<String> {
+        let graph = self.graph.as_ref()?;
+        let mut out = String::from("flowchart TD\n");
+        for (idx, batch) in graph.schedule.iter().enumerate() {
+            out.push_str(&format!("  subgraph batch_{idx} [batch {idx}]\n"));
+            for node in batch {
+                out.push_str(&format!(
+                    "    {}[\"{}\"]\n",
+                    node_id(node),
+                    node_label(node, "<br/>")
+                ));
+            }
+            out.push_str("  end\n");
+        }
+        for (from, to) in edges(&graph.schedule) {
+            out.push_str(&format!("  {from} --> {to}\n"));
+        }
+        Some(out)
+    }
+}
+
+impl Validation<'static> {
+    /// Render the [Workflow]'s graph as a [dot] graph, to be read by
+    /// graphviz, etc., if an execution graph could be built.
+    ///
+    /// [dot]: <https://graphviz.org/doc/info/lang.html>
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) fn dot(&self) -> anyhow::Result<Option<String>> {
+        self.graph
+            .as_ref()
+            .map(|_| self.aot.render_dot(&self.cid.to_string()))
+            .transpose()
+    }
+}
+
+impl<'a> Builder<'a> {
+    /// Validate the [Workflow] ahead-of-time, without running it, collecting
+    /// all [Issue]s found along with its batched execution graph.
+    ///
+    /// Errors are only returned if the [Workflow] cannot be processed at
+    /// all, e.g. if its tasks are not expanded.
+    ///
+    /// [Workflow]: homestar_workflow::Workflow
+    pub(crate) fn validate(self) -> anyhow::Result<Validation<'a>> {
+        let cid = self.inner().to_owned().to_cid()?;
+        let num_tasks = self.inner().len();
+        let lookup_table = self.lookup_table()?;
+
+        let mut issues = vec![];
+        for task in self.inner().tasks_ref() {
+            let instruction = task.instruction_cid()?;
+            let RunInstruction::Expanded(instr) = task.run() else {
+                continue;
+            };
+
+            let ability = instr.op().to_string();
+            if RegisteredTasks::ability(&ability).is_none() {
+                issues.push(Issue::UnknownAbility {
+                    instruction,
+                    ability,
+                });
+            }
+
+            for awaits in instr.input().parse()?.args().deferreds() {
+                if !lookup_table.contains_key(&awaits) {
+                    issues.push(Issue::DanglingAwait {
+                        instruction,
+                        awaits,
+                    });
+                }
+            }
+        }
+
+        let aot = self.aot()?;
+        let context = aot.clone();
+        if aot.dag.detect_duplicates().is_err() {
+            issues.push(Issue::DuplicateTask);
+        }
+        if let Err(err) = aot.dag.detect_cycles() {
+            issues.push(Issue::Cycle(err.to_string()));
+        }
+
+        let graph = if issues.iter().any(Issue::is_error) {
+            None
+        } else {
+            match aot.dag.build_schedule() {
+                Ok(schedule) => Some(ExecutionGraph {
+                    schedule: schedule.batches,
+                    awaiting: aot.awaiting,
+                    indexed_resources: aot.indexed_resources,
+                }),
+                Err(err) => {
+                    issues.push(Issue::InvalidSchedule(err.to_string()));
+                    None
+                }
+            }
+        };
+
+        Ok(Validation {
+            cid,
+            num_tasks,
+            issues,
+            graph,
+            aot: context,
+        })
+    }
+}
+
+fn node_id(node: &Node<Vertex<'_>, usize>) -> String {
+    let idx = node.get_results().next().copied().unwrap_or_default();
+    format!("task_{idx}")
+}
+
+fn node_label(node: &Node<Vertex<'_>, usize>, separator: &str) -> String {
+    let vertex = node.inner();
+    let op = vertex.instruction.op();
+    match vertex.parsed.fun() {
+        Some(fun) => format!("{op} {fun}{separator}{}", node.name()),
+        None => format!("{op}{separator}{}", node.name()),
+    }
+}
+
+/// Edges from awaited tasks to the tasks awaiting them.
+fn edges(schedule: &[Vec<Node<Vertex<'_>, usize>>]) -> Vec<(String, String)> {
+    let producers: HashMap<usize, String> = schedule
+        .iter()
+        .flatten()
+        .flat_map(|node| node.get_results().map(move |rez| (*rez, node_id(node))))
+        .collect();
+
+    schedule
+        .iter()
+        .flatten()
+        .flat_map(|node| {
+            node.get_reads()
+                .filter_map(|read| producers.get(read))
+                .map(|from| (from.clone(), node_id(node)))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homestar_invocation::{
+        authority::UcanPrf,
+        task::{instruction::Ability, Instruction, Resources},
+        test_utils, Task,
+    };
+    use homestar_wasm::io::Arg;
+    use homestar_workflow::Workflow;
+
+    #[test]
+    fn validate_workflow_and_render_graph() {
+        let config = Resources::default();
+        let (instruction1, instruction2, _) = test_utils::related_wasm_instructions::<Arg>();
+        let task1 = Task::new(
+            RunInstruction::Expanded(instruction1.clone()),
+            config.clone().into(),
+            UcanPrf::default(),
+        );
+        let task2 = Task::new(
+            RunInstruction::Expanded(instruction2),
+            config.into(),
+            UcanPrf::default(),
+        );
+
+        let validation = Builder::new(Workflow::new(vec![task1.clone(), task2.clone()]))
+            .validate()
+            .unwrap();
+        assert!(validation.is_valid());
+        assert!(validation.issues.is_empty());
+        assert_eq!(validation.num_tasks, 2);
+
+        let dot = validation.dot().unwrap().unwrap();
+        assert!(dot.starts_with(&format!("digraph {}", validation.cid)));
+        assert!(dot.contains("subgraph cluster_0"));
+        assert!(dot.contains("subgraph cluster_1"));
+        assert!(dot.contains("node_1 -> node_0"));
+
+        let mermaid = validation.mermaid().unwrap();
+        assert!(mermaid.starts_with("flowchart TD"));
+        assert!(mermaid.contains("task_0 --> task_1"));
+
+        // Without the awaited task, the await dangles.
+        let validation = Builder::new(Workflow::new(vec![task2])).validate().unwrap();
+        assert!(validation.is_valid());
+        assert_eq!(
+            validation.issues,
+            vec![Issue::DanglingAwait {
+                instruction: instruction_cid(&validation, 0),
+                awaits: instruction1.to_owned().to_cid().unwrap(),
+            }]
+        );
+
+        // Duplicates are invalid.
+        let validation = Builder::new(Workflow::new(vec![task1.clone(), task1]))
+            .validate()
+            .unwrap();
+        assert!(!validation.is_valid());
+        assert!(validation.issues.contains(&Issue::DuplicateTask));
+        assert!(validation.dot().unwrap().is_none());
+    }
+
+    #[test]
+    fn validate_unknown_ability() {
+        let (known, _, _) = test_utils::related_wasm_instructions::<Arg>();
+        let instruction = Instruction::<Arg>::new(
+            known.resource().to_owned(),
+            Ability::from("wasm/walk"),
+            known.input().to_owned(),
+        );
+        let instruction_cid = instruction.clone().to_cid().unwrap();
+        let task = Task::new(
+            RunInstruction::Expanded(instruction),
+            Resources::default().into(),
+            UcanPrf::default(),
+        );
+
+        let validation = Builder::new(Workflow::new(vec![task])).validate().unwrap();
+        assert!(!validation.is_valid());
+        assert_eq!(
+            validation.issues,
+            vec![Issue::UnknownAbility {
+                instruction: instruction_cid,
+                ability: "wasm/walk".to_string(),
+            }]
+        );
+    }
+
+    fn instruction_cid(validation: &Validation<'_>, idx: usize) -> Cid {
+        let graph = validation.graph.as_ref().unwrap();
+        graph.schedule.iter().flatten().collect::<Vec<_>>()[idx]
+            .name()
+            .parse()
+            .unwrap()
+    }
+}
@@ -73,6 +73,39 @@ fn test_version_integration() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial_test::parallel]
+fn test_validate_integration() -> Result<()> {
+    Command::new(BIN.as_os_str())
+        .arg("validate")
+        .arg("tests/fixtures/test-workflow-add-one.json")
+        .arg("--graph")
+        .arg("dot")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 tasks in 2 batches"))
+        .stdout(predicate::str::contains("node_1 -> node_0"))
+        .stdout(predicate::str::contains("name:").not());
+
+    Command::new(BIN.as_os_str())
+        .arg("validate")
+        .arg("tests/fixtures/test-workflow-add-one-part-two.json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("warning:"));
+
+    Command::new(BIN.as_os_str())
+        .arg("validate")
+        .arg("tests/fixtures/test-workflow-add-one.txt")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unsupported workflow file type: txt",
+        ));
+
+    Ok(())
+}
+
 #[test]
 #[serial_test::parallel]
 fn test_server_not_running_integration() -> Result<()> {
@@ -328,6 +361,43 @@ fn test_workflow_run_integration_nonced() -> Result<()> {
     })
     .unwrap();
 
+    Command::new(BIN.as_os_str())
+        .arg("workflow")
+        .arg("list")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg("--status")
+        .arg("completed")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(workflow_cid))
+        .stdout(predicate::str::contains("3/3"));
+
+    Command::new(BIN.as_os_str())
+        .arg("workflow")
+        .arg("show")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg(workflow_cid)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("progress_count"))
+        .stdout(predicate::str::contains("retries"))
+        .stdout(predicate::str::contains(
+            "ipfs://bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q",
+        ));
+
+    Command::new(BIN.as_os_str())
+        .arg("workflow")
+        .arg("receipts")
+        .arg("-p")
+        .arg(rpc_port.to_string())
+        .arg(workflow_cid)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("instruction"))
+        .stdout(predicate::str::contains("ok"));
+
     Ok(())
 }
 
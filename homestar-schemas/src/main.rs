@@ -2,7 +2,10 @@
 
 use homestar_invocation::Receipt;
 use homestar_runtime::{
+    runner::response::{AckCancel, AckModule},
+    workflow::RequestedSettings,
     Health, NetworkNotification, NodeInfo, PrometheusData, ReceiptNotification,
+    TaskLogNotification,
 };
 use homestar_workflow::Workflow;
 use schemars::{
@@ -247,16 +250,24 @@ fn generate_api_doc(
         servers: None,
         tags: None,
         param_structure: Some(MethodObjectParamStructure::ByName),
-        params: vec![ContentDescriptorOrReference::ContentDescriptorObject(
-            ContentDescriptorObject {
+        params: vec![
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
                 name: "tasks".to_string(),
                 summary: None,
                 description: None,
                 required: Some(true),
                 schema: JSONSchema::JsonSchemaObject(workflow_schema),
                 deprecated: Some(false),
-            },
-        )],
+            }),
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "settings".to_string(),
+                summary: None,
+                description: Some("settings requested for the workflow run".to_string()),
+                required: Some(false),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(RequestedSettings)),
+                deprecated: Some(false),
+            }),
+        ],
         result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
             name: "subscription_id".to_string(),
             summary: None,
@@ -280,6 +291,147 @@ fn generate_api_doc(
         }),
     };
 
+    let cancel_workflow: MethodObject = MethodObject {
+        name: "cancel_workflow".to_string(),
+        description: None,
+        summary: Some("cancel a running workflow, given its Cid or local name".to_string()),
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::ByName),
+        params: vec![ContentDescriptorOrReference::ContentDescriptorObject(
+            ContentDescriptorObject {
+                name: "workflow".to_string(),
+                summary: None,
+                description: Some("Cid or local name of the workflow".to_string()),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            },
+        )],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "cancel_workflow".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(schema_for!(AckCancel)),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: None,
+    };
+
+    let describe_module: MethodObject = MethodObject {
+        name: "describe_module".to_string(),
+        description: None,
+        summary: Some(
+            "describe the exported functions of a Wasm module, with their WIT types and a JSON Schema of their args"
+                .to_string(),
+        ),
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::ByName),
+        params: vec![ContentDescriptorOrReference::ContentDescriptorObject(
+            ContentDescriptorObject {
+                name: "cid".to_string(),
+                summary: None,
+                description: Some("Cid of the Wasm module".to_string()),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            },
+        )],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "describe_module".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(schema_for!(AckModule)),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: None,
+    };
+
+    let task_logs: MethodObject = MethodObject {
+        name: "subscribe_task_logs".to_string(),
+        description: None,
+        summary: Some("subscribe to guest logs of a task as they're written".to_string()),
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::ByName),
+        params: vec![
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "workflow".to_string(),
+                summary: None,
+                description: Some("Cid of the workflow".to_string()),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            }),
+            ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+                name: "instruction".to_string(),
+                summary: None,
+                description: Some("Cid of the task's instruction".to_string()),
+                required: Some(true),
+                schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+                deprecated: Some(false),
+            }),
+        ],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "subscription_id".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(schema_for!(String)),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: Some(ContentDescriptorObject {
+            name: "task log subscription messages".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(schema_for!(TaskLogNotification)),
+            deprecated: Some(false),
+        }),
+    };
+
+    let task_logs_unsubscribe: MethodObject = MethodObject {
+        name: "unsubscribe_task_logs".to_string(),
+        description: None,
+        summary: None,
+        servers: None,
+        tags: None,
+        param_structure: Some(MethodObjectParamStructure::Either),
+        params: vec![],
+        result: ContentDescriptorOrReference::ContentDescriptorObject(ContentDescriptorObject {
+            name: "unsubscribe result".to_string(),
+            summary: None,
+            description: None,
+            required: Some(true),
+            schema: JSONSchema::JsonSchemaObject(schema_for!(bool)),
+            deprecated: Some(false),
+        }),
+        external_docs: None,
+        errors: None,
+        links: None,
+        examples: None,
+        deprecated: Some(false),
+        x_messages: None,
+    };
+
     let workflow_unsubscribe: MethodObject = MethodObject {
         name: "unsubscribe_run_workflow".to_string(),
         description: None,
@@ -337,6 +489,10 @@ fn generate_api_doc(
             network_unsubscribe,
             workflow,
             workflow_unsubscribe,
+            cancel_workflow,
+            describe_module,
+            task_logs,
+            task_logs_unsubscribe,
         ],
         components: None,
     }
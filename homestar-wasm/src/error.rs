@@ -32,6 +32,12 @@ pub enum InterpreterError {
     /// [Wit]: wasmtime::component::Val
     #[error("incompatible Ipld type to Wit structural conversion: {0:#?}")]
     IpldToWit(String),
+    /// Error converting a resource handle to or from the [Cid] backing its
+    /// resource.
+    ///
+    /// [Cid]: libipld::Cid
+    #[error("cannot convert resource handle: {0}")]
+    ResourceHandle(String),
     /// Bubble-up [TagsError] errors while executing the interpreter.
     #[error(transparent)]
     Tags(#[from] TagsError),
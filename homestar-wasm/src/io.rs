@@ -1,6 +1,9 @@
 //! IO (input/output) types for the Wasm execution.
 
-use crate::{error::InterpreterError, wasmtime::ipld::RuntimeVal};
+use crate::{
+    error::InterpreterError,
+    wasmtime::ipld::{InterfaceType, RuntimeVal},
+};
 use enum_as_inner::EnumAsInner;
 use homestar_invocation::{
     error::InputParseError,
@@ -88,6 +91,10 @@ pub enum Output {
     ///
     /// [Wasm value]: wasmtime::component::Val
     Values(Vec<wasmtime::component::Val>),
+    /// Output already converted to Ipld, as it held resource handles only
+    /// valid within the store they were returned in. Multiple results are
+    /// converted to a list.
+    Ipld(Ipld),
     /// No output, treated as `void`.
     Void,
 }
@@ -98,6 +105,9 @@ impl Output {
         match self {
             Output::Value(v) => Some(v),
             Output::Values(vs) => vs.into_iter().next(),
+            Output::Ipld(ipld) => RuntimeVal::try_from(ipld, &InterfaceType::Any)
+                .ok()
+                .map(RuntimeVal::value),
             Output::Void => None,
         }
     }
@@ -117,6 +127,7 @@ impl TryFrom<Output> for Ipld {
                 })?;
                 Ok(Ipld::List(ipld_vs))
             }
+            Output::Ipld(ipld) => Ok(ipld),
             Output::Void => Ok(Ipld::Null),
         }
     }
@@ -0,0 +1,110 @@
+//! [BlockStore]s backing the host `ipld` interface, through which guests
+//! read and write content-addressed blocks, and the [Block] resource guests
+//! hold handles to.
+
+use async_trait::async_trait;
+use libipld::Cid;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Store of content-addressed blocks available to guests.
+#[async_trait]
+pub trait BlockStore: Send + Sync {
+    /// Get the bytes of the block with the given [Cid], if stored.
+    async fn get_block(&self, cid: Cid) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Put a block, already verified to match its [Cid].
+    async fn put_block(&self, cid: Cid, bytes: Vec<u8>) -> anyhow::Result<()>;
+}
+
+/// Host `block` resource, backed by the [Cid] of a block, which guests hold
+/// handles to in place of the block's bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block(Cid);
+
+impl Block {
+    /// Create a new [Block] resource backed by `cid`.
+    pub fn new(cid: Cid) -> Self {
+        Block(cid)
+    }
+
+    /// [Cid] backing the resource.
+    pub fn cid(&self) -> Cid {
+        self.0
+    }
+}
+
+/// Quota on the blocks a task may put through the host `ipld` interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockQuota {
+    /// Maximum number of blocks put per task.
+    pub max_blocks: u32,
+    /// Maximum number of block bytes put per task.
+    pub max_bytes: u64,
+}
+
+impl Default for BlockQuota {
+    fn default() -> Self {
+        Self {
+            max_blocks: 1024,
+            max_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Blocks and bytes put by a task, counted against its [BlockQuota].
+#[derive(Debug, Default)]
+pub(crate) struct BlockUsage {
+    blocks: u32,
+    bytes: u64,
+}
+
+impl BlockUsage {
+    /// Reserve a block of `len` bytes against `quota`, failing once either
+    /// the block count or the byte quota would be exceeded.
+    pub(crate) fn reserve(&mut self, len: usize, quota: &BlockQuota) -> Result<(), String> {
+        let blocks = self.blocks.saturating_add(1);
+        if blocks > quota.max_blocks {
+            return Err(format!(
+                "task exceeded its quota of {} blocks",
+                quota.max_blocks
+            ));
+        }
+        let bytes = self.bytes.saturating_add(len as u64);
+        if bytes > quota.max_bytes {
+            return Err(format!(
+                "task exceeded its quota of {} block bytes",
+                quota.max_bytes
+            ));
+        }
+        self.blocks = blocks;
+        self.bytes = bytes;
+        Ok(())
+    }
+}
+
+/// In-memory [BlockStore], e.g. scoped to a single task's execution.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryBlockStore(Arc<Mutex<HashMap<Cid, Vec<u8>>>>);
+
+#[async_trait]
+impl BlockStore for MemoryBlockStore {
+    async fn get_block(&self, cid: Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&cid)
+            .cloned())
+    }
+
+    async fn put_block(&self, cid: Cid, bytes: Vec<u8>) -> anyhow::Result<()> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(cid, bytes);
+        Ok(())
+    }
+}
@@ -5,9 +5,14 @@ use homestar_invocation::{consts, task::Resources};
 
 impl From<Resources> for wasmtime::State {
     fn from(resources: Resources) -> wasmtime::State {
-        wasmtime::State::new(
+        let mut state = wasmtime::State::new(
             resources.fuel().unwrap_or(u64::MAX),
-            StoreLimitsAsync::new(Some(consts::WASM_MAX_MEMORY as usize), None),
-        )
+            StoreLimitsAsync::new(
+                Some(resources.memory().unwrap_or(consts::WASM_MAX_MEMORY) as usize),
+                None,
+            ),
+        );
+        state.set_time_limit(resources.time());
+        state
     }
 }
@@ -0,0 +1,242 @@
+//! Introspection of a Wasm component's exported functions, rendering their
+//! signatures as WIT text and the arguments they accept as JSON Schema.
+//!
+//! Schemas follow the Ipld conversions of [RuntimeVal::try_from], i.e. what
+//! a workflow's `args` must look like for each [InterfaceType], see
+//! [json_schema].
+//!
+//! [RuntimeVal::try_from]: crate::wasmtime::ipld::RuntimeVal::try_from
+//! [InterfaceType]: crate::wasmtime::ipld::InterfaceType
+
+use crate::wasmtime::{
+    ipld::{json_schema, InterfaceType},
+    world::component_binary,
+    Error, Runtime, State,
+};
+use libipld::Cid;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wasmparser::{ComponentExternalKind, Parser, Payload};
+use wasmtime::component::Type;
+
+/// JSON Schema draft the generated schemas adhere to.
+const JSON_SCHEMA_DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Description of a Wasm component's root-level exported functions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleDescription {
+    /// Cid of the described resource.
+    pub cid: String,
+    /// Exported functions, in export order.
+    pub functions: Vec<FunctionDescription>,
+}
+
+/// Description of an exported function.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionDescription {
+    /// Name of the function, as exported.
+    pub name: String,
+    /// Parameter types, as WIT text.
+    pub params: Vec<String>,
+    /// Result types, as WIT text.
+    pub results: Vec<String>,
+    /// Function signature, as WIT text.
+    pub wit: String,
+    /// JSON Schema of the function's `args`.
+    pub args_schema: Value,
+}
+
+impl Runtime {
+    /// Describe the exported functions of the resource identified by `cid`,
+    /// compiling `bytes` only if it's not already cached.
+    ///
+    /// Function names are read from the component's export section, and
+    /// their types from an instance of it, on a throwaway store.
+    pub async fn describe(&self, cid: Cid, bytes: &[u8]) -> Result<ModuleDescription, Error> {
+        let names = exported_functions(&component_binary(bytes)?)?;
+        let component = self.component(cid, bytes)?;
+        let mut env = self.env(State::default())?;
+        let functions = env
+            .func_types(&component, &names)
+            .await?
+            .into_iter()
+            .zip(&names)
+            .filter_map(|(types, name)| {
+                types.map(|(params, results)| FunctionDescription::new(name, params, results))
+            })
+            .collect();
+
+        Ok(ModuleDescription {
+            cid: cid.to_string(),
+            functions,
+        })
+    }
+}
+
+/// Names of the functions exported at the root of a component binary, in
+/// export order.
+fn exported_functions(binary: &[u8]) -> Result<Vec<String>, Error> {
+    let mut names = vec![];
+    // Nested modules and components are parsed inline, each between its own
+    // version and end payloads.
+    let mut depth = 0usize;
+    for payload in Parser::new(0).parse_all(binary) {
+        match payload? {
+            Payload::Version { .. } => depth += 1,
+            Payload::End(_) => depth = depth.saturating_sub(1),
+            Payload::ComponentExportSection(exports) if depth == 1 => {
+                for export in exports {
+                    let export = export?;
+                    if export.kind == ComponentExternalKind::Func {
+                        names.push(export.name.0.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(names)
+}
+
+impl FunctionDescription {
+    fn new(name: &str, params: Vec<Type>, results: Vec<Type>) -> Self {
+        let wit_params = params
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| format!("arg{idx}: {}", wit_type(ty)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let wit_results = match results.as_slice() {
+            [] => String::new(),
+            [ty] => format!(" -> {}", wit_type(ty)),
+            tys => format!(
+                " -> ({})",
+                tys.iter()
+                    .enumerate()
+                    .map(|(idx, ty)| format!("ret{idx}: {}", wit_type(ty)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        let mut args_schema = json!({
+            "type": "array",
+            "items": params
+                .iter()
+                .map(|ty| json_schema(&InterfaceType::TypeRef(ty)))
+                .collect::<Vec<_>>(),
+            "minItems": params.len(),
+            "maxItems": params.len(),
+        });
+        args_schema["$schema"] = json!(JSON_SCHEMA_DRAFT);
+        args_schema["title"] = json!(format!("{name} args"));
+
+        Self {
+            name: name.to_string(),
+            params: params.iter().map(wit_type).collect(),
+            results: results.iter().map(wit_type).collect(),
+            wit: format!("{name}: func({wit_params}){wit_results};"),
+            args_schema,
+        }
+    }
+}
+
+/// Render a [Type] as WIT text.
+///
+/// Types are rendered structurally, as type names aren't kept in compiled
+/// components.
+pub fn wit_type(ty: &Type) -> String {
+    fn join(items: impl Iterator<Item = String>) -> String {
+        items.collect::<Vec<_>>().join(", ")
+    }
+
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::Float32 => "float32".to_string(),
+        Type::Float64 => "float64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+        Type::List(list) => format!("list<{}>", wit_type(&list.ty())),
+        Type::Record(record) => format!(
+            "record {{ {} }}",
+            join(
+                record
+                    .fields()
+                    .map(|field| format!("{}: {}", field.name, wit_type(&field.ty)))
+            )
+        ),
+        Type::Tuple(tuple) => format!("tuple<{}>", join(tuple.types().map(|ty| wit_type(&ty)))),
+        Type::Variant(variant) => format!(
+            "variant {{ {} }}",
+            join(variant.cases().map(|case| match case.ty {
+                Some(ty) => format!("{}({})", case.name, wit_type(&ty)),
+                None => case.name.to_string(),
+            }))
+        ),
+        Type::Enum(enum_ty) => format!(
+            "enum {{ {} }}",
+            join(enum_ty.names().map(|name| name.to_string()))
+        ),
+        Type::Option(option) => format!("option<{}>", wit_type(&option.ty())),
+        Type::Result(result) => match (result.ok(), result.err()) {
+            (Some(ok), Some(err)) => format!("result<{}, {}>", wit_type(&ok), wit_type(&err)),
+            (Some(ok), None) => format!("result<{}>", wit_type(&ok)),
+            (None, Some(err)) => format!("result<_, {}>", wit_type(&err)),
+            (None, None) => "result".to_string(),
+        },
+        Type::Flags(flags) => format!(
+            "flags {{ {} }}",
+            join(flags.names().map(|name| name.to_string()))
+        ),
+        // Resource types are anonymous once a component is instantiated, so
+        // handles are rendered without the name of the resource they point to.
+        Type::Own(_) => "own<resource>".to_string(),
+        Type::Borrow(_) => "borrow<resource>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{fs, path::PathBuf};
+
+    fn fixtures(file: &str) -> PathBuf {
+        PathBuf::from(format!("{}/fixtures/{file}", env!("CARGO_MANIFEST_DIR")))
+    }
+
+    #[tokio::test]
+    async fn describe_add_component() {
+        let runtime = Runtime::new(1, None).unwrap();
+        let wat = fs::read(fixtures("example_add_component.wat")).unwrap();
+        let description = runtime
+            .describe(Runtime::content_cid(&wat), &wat)
+            .await
+            .unwrap();
+
+        let names = description
+            .functions
+            .iter()
+            .map(|func| func.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["add-one", "add-two"]);
+
+        let add_two = &description.functions[1];
+        assert_eq!(add_two.params, vec!["s32"]);
+        assert_eq!(add_two.results, vec!["s32"]);
+        assert_eq!(add_two.wit, "add-two: func(arg0: s32) -> s32;");
+        assert_eq!(add_two.args_schema["minItems"], 1);
+        assert_eq!(
+            add_two.args_schema["items"][0],
+            json_schema(&InterfaceType::Type(Type::S32))
+        );
+    }
+}
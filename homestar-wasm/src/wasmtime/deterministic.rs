@@ -0,0 +1,51 @@
+//! Deterministic WASI context, with a fixed clock and randomness seeded by
+//! the host, for reproducible execution across nodes.
+
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use std::time::Duration;
+use wasmtime_wasi::preview2::{HostMonotonicClock, HostWallClock, WasiCtxBuilder};
+
+/// Stream of the seeded RNG used for `wasi:random/insecure`, kept apart from
+/// the one for `wasi:random/random`.
+const INSECURE_STREAM: u64 = 1;
+
+/// Clock stuck at its epoch.
+#[derive(Debug, Clone, Copy)]
+struct FixedClock;
+
+impl HostWallClock for FixedClock {
+    fn resolution(&self) -> Duration {
+        Duration::from_nanos(1)
+    }
+
+    fn now(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+impl HostMonotonicClock for FixedClock {
+    fn resolution(&self) -> u64 {
+        1
+    }
+
+    fn now(&self) -> u64 {
+        0
+    }
+}
+
+/// Fix the clocks of a [WasiCtxBuilder] at their epoch and derive its random
+/// sources from `seed`.
+pub(crate) fn configure(builder: &mut WasiCtxBuilder, seed: [u8; 32]) {
+    let mut insecure = ChaCha20Rng::from_seed(seed);
+    insecure.set_stream(INSECURE_STREAM);
+
+    let mut insecure_seed = [0; 16];
+    insecure_seed.copy_from_slice(&seed[..16]);
+
+    builder
+        .secure_random(ChaCha20Rng::from_seed(seed))
+        .insecure_random(insecure)
+        .insecure_random_seed(u128::from_le_bytes(insecure_seed))
+        .wall_clock(FixedClock)
+        .monotonic_clock(FixedClock);
+}
@@ -22,9 +22,15 @@ pub enum Error {
     /// Generic unknown error.
     #[error("unknown error")]
     Unknown,
+    /// Failure to open a host directory to preopen for a guest.
+    #[error("cannot preopen directory {0}: {1}")]
+    WasiPreopen(String, String),
     /// Failure to instantiate Wasm component and its host bindings.
     #[error("bindings not yet instantiated for wasm environment")]
     WasmInstantiation,
+    /// Wasm execution interrupted, having run past its time limit.
+    #[error("Wasm execution interrupted after exceeding its time limit")]
+    WasmInterrupted,
     /// Failure to parse Wasm binary.
     ///
     /// Transparently forwards from [wasmparser::BinaryReaderError]'s `source`
@@ -36,7 +42,7 @@ pub enum Error {
     /// Transparently forwards from [anyhow::Error]'s `source` and
     /// `Display` methods through to an underlying error.
     #[error(transparent)]
-    WasmRuntime(#[from] anyhow::Error),
+    WasmRuntime(anyhow::Error),
     /// Failure to find Wasm function for execution.
     #[error("Wasm function {0} not found in given Wasm component/resource")]
     WasmFunctionNotFound(String),
@@ -52,3 +58,14 @@ pub enum Error {
     #[error(transparent)]
     Wat(#[from] wat::Error),
 }
+
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        // Epoch deadlines are only ever set from time limits, so an interrupt
+        // trap means the guest ran out of time.
+        match err.downcast_ref::<wasmtime::Trap>() {
+            Some(wasmtime::Trap::Interrupt) => Error::WasmInterrupted,
+            _ => Error::WasmRuntime(err),
+        }
+    }
+}
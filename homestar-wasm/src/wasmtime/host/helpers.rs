@@ -1,19 +1,24 @@
 //! Helper functions that can be used in guest Wasm components.
 
 use crate::wasmtime::{
+    logs::{LogLevel, LogRecord, PRINT_CONTEXT},
     world::{homestar::host::helpers, wasi},
     State,
 };
 use async_trait::async_trait;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::instrument;
 
 #[async_trait]
 impl helpers::Host for State {
-    /// Get the current time.
+    /// Get the current time, fixed at the start when running
+    /// deterministically.
     async fn get_current_time(&mut self) -> wasmtime::Result<helpers::Time> {
-        let now = Instant::now();
-        let duration = now.duration_since(self.start_time());
+        let duration = if self.is_deterministic() {
+            Duration::ZERO
+        } else {
+            Instant::now().duration_since(self.start_time())
+        };
         Ok(helpers::Time {
             seconds: duration.as_secs(),
             milliseconds: duration.subsec_millis(),
@@ -21,16 +26,22 @@ impl helpers::Host for State {
         })
     }
 
-    /// Print a message.
+    /// Print a message, also captured as a guest log record.
     async fn print(&mut self, from_wasm: String) -> wasmtime::Result<()> {
         println!("{from_wasm}");
+        self.log(LogRecord {
+            level: LogLevel::Info,
+            context: PRINT_CONTEXT.to_string(),
+            message: from_wasm,
+        });
         Ok(())
     }
 }
 
 #[async_trait]
 impl wasi::logging::logging::Host for State {
-    /// Log a message, formatted by the runtime subscriber.
+    /// Log a message, formatted by the runtime subscriber and captured as a
+    /// guest log record.
     #[instrument(name = "wasi_log", skip_all)]
     async fn log(
         &mut self,
@@ -38,6 +49,15 @@ impl wasi::logging::logging::Host for State {
         context: String,
         message: String,
     ) -> wasmtime::Result<()> {
+        let record_level = match level {
+            wasi::logging::logging::Level::Trace => LogLevel::Trace,
+            wasi::logging::logging::Level::Debug => LogLevel::Debug,
+            wasi::logging::logging::Level::Info => LogLevel::Info,
+            wasi::logging::logging::Level::Warn => LogLevel::Warn,
+            wasi::logging::logging::Level::Error => LogLevel::Error,
+            wasi::logging::logging::Level::Critical => LogLevel::Critical,
+        };
+
         match level {
             wasi::logging::logging::Level::Trace => {
                 tracing::trace!(
@@ -82,6 +102,15 @@ impl wasi::logging::logging::Host for State {
                 )
             }
         }
+
+        State::log(
+            self,
+            LogRecord {
+                level: record_level,
+                context,
+                message,
+            },
+        );
         Ok(())
     }
 }
@@ -0,0 +1,246 @@
+//! Content-addressed block functions that can be used in guest Wasm
+//! components.
+
+use crate::wasmtime::{
+    blocks::{Block, BlockStore},
+    world::homestar::host::ipld,
+    State,
+};
+use async_trait::async_trait;
+use libipld::{
+    cbor::DagCborCodec,
+    json::DagJsonCodec,
+    multihash::{Code, MultihashDigest},
+    prelude::Codec,
+    Cid, Ipld, IpldCodec,
+};
+use std::sync::Arc;
+use tracing::instrument;
+use wasmtime::component::Resource;
+use wasmtime_wasi::preview2::WasiView;
+
+#[async_trait]
+impl ipld::Host for State {
+    /// Get a block from the [State]'s block store.
+    #[instrument(name = "ipld_get_block", skip(self))]
+    async fn get_block(&mut self, cid: String) -> wasmtime::Result<Result<Vec<u8>, String>> {
+        Ok(match Cid::try_from(cid.as_str()) {
+            Ok(cid) => read_block(self.blocks(), cid).await,
+            Err(err) => Err(format!("invalid cid {cid}: {err}")),
+        })
+    }
+
+    /// Open a handle to a [Block], without fetching it.
+    #[instrument(name = "ipld_open_block", skip(self))]
+    async fn open_block(
+        &mut self,
+        cid: String,
+    ) -> wasmtime::Result<Result<Resource<Block>, String>> {
+        Ok(match Cid::try_from(cid.as_str()) {
+            Ok(cid) => Ok(self.table().push(Block::new(cid))?),
+            Err(err) => Err(format!("invalid cid {cid}: {err}")),
+        })
+    }
+
+    /// Put a block into the [State]'s block store, after checking it decodes
+    /// with the given codec and fits the task's block quota.
+    #[instrument(name = "ipld_put_block", skip(self, bytes))]
+    async fn put_block(
+        &mut self,
+        codec: ipld::Codec,
+        bytes: Vec<u8>,
+    ) -> wasmtime::Result<Result<String, String>> {
+        let decoded = match codec {
+            ipld::Codec::Raw => Ok(()),
+            ipld::Codec::DagCbor => DagCborCodec.decode::<Ipld>(&bytes).map(|_| ()),
+            ipld::Codec::DagJson => DagJsonCodec.decode::<Ipld>(&bytes).map(|_| ()),
+        };
+        if let Err(err) = decoded {
+            return Ok(Err(format!("block does not decode as {codec:?}: {err}")));
+        }
+
+        if let Err(err) = self.reserve_block(bytes.len()) {
+            return Ok(Err(err));
+        }
+
+        let cid = Cid::new_v1(IpldCodec::from(codec).into(), Code::Sha3_256.digest(&bytes));
+        Ok(match self.blocks().put_block(cid, bytes).await {
+            Ok(()) => Ok(cid.to_string()),
+            Err(err) => Err(format!("failed to put block {cid}: {err}")),
+        })
+    }
+
+    /// Encode DAG-JSON as DAG-CBOR.
+    async fn encode_dag_cbor(
+        &mut self,
+        dag_json: String,
+    ) -> wasmtime::Result<Result<Vec<u8>, String>> {
+        Ok(DagJsonCodec
+            .decode::<Ipld>(dag_json.as_bytes())
+            .and_then(|ipld| DagCborCodec.encode(&ipld))
+            .map_err(|err| err.to_string()))
+    }
+
+    /// Decode DAG-CBOR as DAG-JSON.
+    async fn decode_dag_cbor(
+        &mut self,
+        bytes: Vec<u8>,
+    ) -> wasmtime::Result<Result<String, String>> {
+        DagCborCodec
+            .decode::<Ipld>(&bytes)
+            .and_then(|ipld| DagJsonCodec.encode(&ipld))
+            .map(|json| String::from_utf8(json).map_err(|err| err.to_string()))
+            .or_else(|err| Ok(Err(err.to_string())))
+    }
+}
+
+#[async_trait]
+impl ipld::HostBlock for State {
+    /// Get the [Cid] backing a [Block].
+    async fn cid(&mut self, block: Resource<Block>) -> wasmtime::Result<String> {
+        Ok(self.table().get(&block)?.cid().to_string())
+    }
+
+    /// Get the bytes of a [Block] from the [State]'s block store.
+    #[instrument(name = "ipld_read_block", skip(self))]
+    async fn read(&mut self, block: Resource<Block>) -> wasmtime::Result<Result<Vec<u8>, String>> {
+        let cid = self.table().get(&block)?.cid();
+        Ok(read_block(self.blocks(), cid).await)
+    }
+
+    /// Drop a guest's handle to a [Block].
+    fn drop(&mut self, block: Resource<Block>) -> wasmtime::Result<()> {
+        self.table().delete(block)?;
+        Ok(())
+    }
+}
+
+/// Read a block from a [State]'s block store, taken as a handle so the
+/// [State] itself isn't held across the read.
+async fn read_block(blocks: Arc<dyn BlockStore>, cid: Cid) -> Result<Vec<u8>, String> {
+    match blocks.get_block(cid).await {
+        Ok(Some(bytes)) => Ok(bytes),
+        Ok(None) => Err(format!("block {cid} not found")),
+        Err(err) => Err(format!("failed to get block {cid}: {err}")),
+    }
+}
+
+impl From<ipld::Codec> for IpldCodec {
+    fn from(codec: ipld::Codec) -> Self {
+        match codec {
+            ipld::Codec::Raw => IpldCodec::Raw,
+            ipld::Codec::DagCbor => IpldCodec::DagCbor,
+            ipld::Codec::DagJson => IpldCodec::DagJson,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wasmtime::blocks::BlockQuota;
+    use ipld::{Host, HostBlock};
+
+    #[tokio::test]
+    async fn put_and_get_blocks() {
+        let mut state = State::default();
+
+        let bytes = state
+            .encode_dag_cbor(r#"{"a": [1, 2, {"/": "bafkqaaa"}]}"#.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        let cid = state
+            .put_block(ipld::Codec::DagCbor, bytes.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Cid::try_from(cid.as_str()).unwrap().codec(),
+            u64::from(IpldCodec::DagCbor)
+        );
+        assert_eq!(state.get_block(cid).await.unwrap().unwrap(), bytes);
+        assert_eq!(
+            state.decode_dag_cbor(bytes).await.unwrap().unwrap(),
+            r#"{"a":[1,2,{"/":"bafkqaaa"}]}"#
+        );
+
+        assert!(state
+            .put_block(ipld::Codec::DagCbor, b"not cbor".to_vec())
+            .await
+            .unwrap()
+            .is_err());
+        let missing = Cid::new_v1(0x55, Code::Sha3_256.digest(b"missing"));
+        assert!(state.get_block(missing.to_string()).await.unwrap().is_err());
+        assert!(state
+            .get_block("not a cid".to_string())
+            .await
+            .unwrap()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn open_and_read_block_handles() {
+        let mut state = State::default();
+
+        let cid = state
+            .put_block(ipld::Codec::Raw, b"hello".to_vec())
+            .await
+            .unwrap()
+            .unwrap();
+        let block = state.open_block(cid.clone()).await.unwrap().unwrap();
+        let rep = block.rep();
+
+        assert_eq!(state.cid(Resource::new_borrow(rep)).await.unwrap(), cid);
+        assert_eq!(
+            state
+                .read(Resource::new_borrow(rep))
+                .await
+                .unwrap()
+                .unwrap(),
+            b"hello"
+        );
+
+        HostBlock::drop(&mut state, block).unwrap();
+        assert!(state.cid(Resource::new_borrow(rep)).await.is_err());
+        assert!(state
+            .open_block("not a cid".to_string())
+            .await
+            .unwrap()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn blocks_past_quota_are_refused() {
+        let mut state = State::default();
+        state.set_block_quota(BlockQuota {
+            max_blocks: 2,
+            max_bytes: 8,
+        });
+
+        assert!(state
+            .put_block(ipld::Codec::Raw, b"hello".to_vec())
+            .await
+            .unwrap()
+            .is_ok());
+        // Past the byte quota.
+        let err = state
+            .put_block(ipld::Codec::Raw, b"world".to_vec())
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(err.contains("8 block bytes"));
+        assert!(state
+            .put_block(ipld::Codec::Raw, b"hi".to_vec())
+            .await
+            .unwrap()
+            .is_ok());
+        // Past the block count.
+        let err = state
+            .put_block(ipld::Codec::Raw, b"!".to_vec())
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(err.contains("2 blocks"));
+    }
+}
@@ -1,3 +1,4 @@
 //! Host-based modules for the Wasmtime runtime.
 
 mod helpers;
+mod ipld;
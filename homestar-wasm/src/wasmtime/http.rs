@@ -0,0 +1,396 @@
+//! Outgoing HTTP for guests through `wasi:http/outgoing-handler`, limited to
+//! an allowlist of hosts and methods and to per-task quotas.
+//!
+//! Responses are buffered, up to the byte quota, so their content can be
+//! hashed and recorded for each call a guest makes.
+
+use crate::wasmtime::Runtime;
+use http_body_util::{BodyExt, Full};
+use libipld::{Cid, Ipld};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tracing::warn;
+use wasmtime::component::{Resource, ResourceTable};
+use wasmtime_wasi::preview2;
+use wasmtime_wasi_http::{
+    bindings::http::types::ErrorCode,
+    types::{
+        default_send_request, HostFutureIncomingResponse, IncomingResponseInternal, OutgoingRequest,
+    },
+    WasiHttpCtx, WasiHttpView,
+};
+
+/// Host, and the methods, guests may send requests to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpAllow {
+    /// Host, optionally with a port, e.g. `api.internal` or
+    /// `api.internal:8080`.
+    pub host: String,
+    /// Methods allowed, e.g. `GET`, or any method if empty.
+    pub methods: Vec<String>,
+}
+
+/// Policy for outgoing HTTP requests of a task. Requests are denied by
+/// default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpPolicy {
+    /// Hosts and methods guests may send requests to.
+    pub allow: Vec<HttpAllow>,
+    /// Maximum number of requests per task.
+    pub max_requests: u32,
+    /// Maximum number of response body bytes per task.
+    pub max_response_bytes: u64,
+}
+
+impl HttpPolicy {
+    /// Whether a request with `method` to `authority`, i.e. `host:port`, is
+    /// allowed.
+    pub fn allows(&self, method: &str, authority: &str) -> bool {
+        let host = authority
+            .rsplit_once(':')
+            .map_or(authority, |(host, _port)| host);
+
+        self.allow.iter().any(|allow| {
+            (allow.host.eq_ignore_ascii_case(authority) || allow.host.eq_ignore_ascii_case(host))
+                && (allow.methods.is_empty()
+                    || allow
+                        .methods
+                        .iter()
+                        .any(|allowed| allowed.eq_ignore_ascii_case(method)))
+        })
+    }
+}
+
+/// Outgoing HTTP request made by a guest, with the hash of its response, or
+/// the error it failed with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpCall {
+    /// Method of the request.
+    pub method: String,
+    /// Url of the request.
+    pub url: String,
+    /// Status code of the response, if received.
+    pub status: Option<u16>,
+    /// Content Cid of the response body, if received.
+    pub response: Option<Cid>,
+    /// Error the request failed with, if any, e.g. if denied.
+    pub error: Option<String>,
+}
+
+impl From<HttpCall> for Ipld {
+    fn from(call: HttpCall) -> Self {
+        let mut map = BTreeMap::from([
+            ("method".into(), Ipld::String(call.method)),
+            ("url".into(), Ipld::String(call.url)),
+        ]);
+        if let Some(status) = call.status {
+            map.insert("status".into(), Ipld::Integer(status.into()));
+        }
+        if let Some(response) = call.response {
+            map.insert("response".into(), Ipld::Link(response));
+        }
+        if let Some(error) = call.error {
+            map.insert("error".into(), Ipld::String(error));
+        }
+        Ipld::Map(map)
+    }
+}
+
+/// Requests made, bytes received, and calls completed or failed by a task.
+#[derive(Debug, Default)]
+struct HttpUsage {
+    requests: u32,
+    bytes: u64,
+    calls: Vec<HttpCall>,
+}
+
+impl HttpUsage {
+    /// Reserve `len` bytes of a response body against the task's byte
+    /// quota, failing once the quota would be exceeded.
+    fn reserve(&mut self, len: usize, max_bytes: u64) -> Result<(), ErrorCode> {
+        let reserved = self.bytes.saturating_add(len as u64);
+        if reserved > max_bytes {
+            return Err(ErrorCode::HttpResponseBodySize(Some(max_bytes)));
+        }
+        self.bytes = reserved;
+        Ok(())
+    }
+}
+
+/// Outgoing HTTP state of a task.
+pub(crate) struct HttpState {
+    ctx: WasiHttpCtx,
+    policy: Arc<HttpPolicy>,
+    usage: Arc<Mutex<HttpUsage>>,
+}
+
+impl Default for HttpState {
+    fn default() -> Self {
+        Self {
+            ctx: WasiHttpCtx,
+            policy: Arc::new(HttpPolicy::default()),
+            usage: Arc::new(Mutex::new(HttpUsage::default())),
+        }
+    }
+}
+
+impl HttpState {
+    /// Set the [HttpPolicy] requests are checked against.
+    pub(crate) fn set_policy(&mut self, policy: HttpPolicy) {
+        self.policy = Arc::new(policy)
+    }
+
+    /// Calls completed or failed so far, in the order their responses or
+    /// errors were received.
+    pub(crate) fn calls(&self) -> Vec<HttpCall> {
+        lock(&self.usage).calls.clone()
+    }
+
+    /// Number of requests admitted so far, whether or not they completed.
+    pub(crate) fn requests(&self) -> u32 {
+        lock(&self.usage).requests
+    }
+
+    /// [WasiHttpCtx] of the task.
+    pub(crate) fn ctx(&mut self) -> &mut WasiHttpCtx {
+        &mut self.ctx
+    }
+
+    /// Send an outgoing request if admitted, recording the call once its
+    /// response is received or it fails. Requests not admitted resolve to
+    /// [ErrorCode::HttpRequestDenied].
+    pub(crate) fn send_request(
+        &mut self,
+        table: &mut ResourceTable,
+        request: OutgoingRequest,
+    ) -> wasmtime::Result<Resource<HostFutureIncomingResponse>> {
+        let method = request.request.method().to_string();
+        let url = request.request.uri().to_string();
+        let sent = match self.admit(&method, &request.authority) {
+            Ok(()) => {
+                let mut view = Outgoing {
+                    ctx: &mut self.ctx,
+                    table,
+                };
+                let sent = default_send_request(&mut view, request)?;
+                let HostFutureIncomingResponse::Pending(sent) = table.delete(sent)? else {
+                    return Err(anyhow::anyhow!(
+                        "outgoing request resolved before it was sent"
+                    ));
+                };
+                Ok(sent)
+            }
+            Err(code) => Err(code),
+        };
+        let policy = self.policy.clone();
+        let usage = self.usage.clone();
+
+        let handle = preview2::spawn(async move {
+            let response = async {
+                let response = sent?
+                    .await
+                    .map_err(|err| ErrorCode::InternalError(Some(err.to_string())))??;
+                record(response, method.clone(), url.clone(), &policy, &usage).await
+            }
+            .await;
+
+            if let Err(code) = &response {
+                lock(&usage).calls.push(HttpCall {
+                    method,
+                    url,
+                    status: None,
+                    response: None,
+                    error: Some(format!("{code:?}")),
+                });
+            }
+            Ok::<_, anyhow::Error>(response)
+        });
+
+        Ok(table.push(HostFutureIncomingResponse::new(handle))?)
+    }
+
+    /// Admit a request if allowed by the [HttpPolicy] and within its request
+    /// quota, counting it against the quota.
+    fn admit(&self, method: &str, authority: &str) -> Result<(), ErrorCode> {
+        if !self.policy.allows(method, authority) {
+            warn!(
+                subject = "wasm.http.denied",
+                category = "wasm.http",
+                method = method,
+                authority = authority,
+                "denied outgoing request to host not allowed"
+            );
+            return Err(ErrorCode::HttpRequestDenied);
+        }
+
+        let mut usage = lock(&self.usage);
+        if usage.requests >= self.policy.max_requests {
+            warn!(
+                subject = "wasm.http.denied",
+                category = "wasm.http",
+                max_requests = self.policy.max_requests,
+                "denied outgoing request over the task's request quota"
+            );
+            return Err(ErrorCode::HttpRequestDenied);
+        }
+        usage.requests += 1;
+        Ok(())
+    }
+}
+
+/// [WasiHttpView] over a task's [WasiHttpCtx] and resource table, through
+/// which admitted requests are sent by the default `wasi:http` handler.
+struct Outgoing<'a> {
+    ctx: &'a mut WasiHttpCtx,
+    table: &'a mut ResourceTable,
+}
+
+impl WasiHttpView for Outgoing<'_> {
+    fn ctx(&mut self) -> &mut WasiHttpCtx {
+        self.ctx
+    }
+
+    fn table(&mut self) -> &mut ResourceTable {
+        self.table
+    }
+}
+
+fn lock(usage: &Mutex<HttpUsage>) -> MutexGuard<'_, HttpUsage> {
+    usage
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Buffer a response body, reserving its bytes against the task's byte
+/// quota as they're received, and record the call with the response's
+/// content Cid.
+///
+/// Responses are buffered concurrently, so the quota is checked for each
+/// frame rather than once per response.
+async fn record(
+    response: IncomingResponseInternal,
+    method: String,
+    url: String,
+    policy: &HttpPolicy,
+    usage: &Mutex<HttpUsage>,
+) -> Result<IncomingResponseInternal, ErrorCode> {
+    let IncomingResponseInternal {
+        resp,
+        worker,
+        between_bytes_timeout,
+    } = response;
+    let (parts, mut body) = resp.into_parts();
+
+    let mut buf = Vec::new();
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            lock(usage).reserve(data.len(), policy.max_response_bytes)?;
+            buf.extend_from_slice(&data);
+        }
+    }
+    let bytes = hyper::body::Bytes::from(buf);
+
+    lock(usage).calls.push(HttpCall {
+        method,
+        url,
+        status: Some(parts.status.as_u16()),
+        response: Some(Runtime::content_cid(&bytes)),
+        error: None,
+    });
+
+    let body = Full::new(bytes).map_err(|never| match never {}).boxed();
+    Ok(IncomingResponseInternal {
+        resp: hyper::Response::from_parts(parts, body),
+        worker,
+        between_bytes_timeout,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn policy_allows_hosts_and_methods() {
+        let policy = HttpPolicy {
+            allow: vec![
+                HttpAllow {
+                    host: "api.internal".to_string(),
+                    methods: vec!["GET".to_string()],
+                },
+                HttpAllow {
+                    host: "store.internal:8080".to_string(),
+                    methods: vec![],
+                },
+            ],
+            max_requests: 1,
+            max_response_bytes: 1024,
+        };
+
+        assert!(policy.allows("GET", "api.internal:443"));
+        assert!(policy.allows("get", "API.internal:80"));
+        assert!(!policy.allows("POST", "api.internal:443"));
+        assert!(policy.allows("PUT", "store.internal:8080"));
+        assert!(!policy.allows("PUT", "store.internal:9090"));
+        assert!(!policy.allows("GET", "example.com:443"));
+        assert!(!HttpPolicy::default().allows("GET", "api.internal:443"));
+    }
+
+    #[test]
+    fn requests_past_quota_are_denied() {
+        let mut http = HttpState::default();
+        http.set_policy(HttpPolicy {
+            allow: vec![HttpAllow {
+                host: "api.internal".to_string(),
+                methods: vec![],
+            }],
+            max_requests: 1,
+            max_response_bytes: 1024,
+        });
+
+        assert!(http.admit("GET", "api.internal:443").is_ok());
+        assert_eq!(http.requests(), 1);
+        assert!(matches!(
+            http.admit("GET", "api.internal:443"),
+            Err(ErrorCode::HttpRequestDenied)
+        ));
+        assert!(matches!(
+            http.admit("GET", "example.com:443"),
+            Err(ErrorCode::HttpRequestDenied)
+        ));
+        assert_eq!(http.requests(), 1);
+    }
+
+    #[test]
+    fn response_bytes_past_quota_are_refused() {
+        let usage = Mutex::new(HttpUsage::default());
+
+        assert!(lock(&usage).reserve(600, 1024).is_ok());
+        assert!(matches!(
+            lock(&usage).reserve(600, 1024),
+            Err(ErrorCode::HttpResponseBodySize(Some(1024)))
+        ));
+        assert!(lock(&usage).reserve(424, 1024).is_ok());
+        assert_eq!(lock(&usage).bytes, 1024);
+    }
+
+    #[test]
+    fn failed_call_to_ipld() {
+        let call = HttpCall {
+            method: "GET".to_string(),
+            url: "https://api.internal/".to_string(),
+            status: None,
+            response: None,
+            error: Some("HttpRequestDenied".to_string()),
+        };
+
+        let ipld = Ipld::from(call);
+        assert_eq!(
+            ipld.get("error").unwrap(),
+            &Ipld::String("HttpRequestDenied".into())
+        );
+        assert!(ipld.get("status").is_err());
+    }
+}
@@ -19,12 +19,13 @@ use rust_decimal::{
     prelude::{FromPrimitive, ToPrimitive},
     Decimal,
 };
+use serde_json::{json, Value};
 use std::{
     collections::{BTreeMap, VecDeque},
     rc::Rc,
     str,
 };
-use wasmtime::component::{Type, Val};
+use wasmtime::component::{ResourceAny, ResourceType, Type, Val};
 
 const DEFAULT_RED_ZONE: usize = 32 * 1024;
 const DEFAULT_EXTRA_STACK: usize = 1024 * 1024;
@@ -82,7 +83,9 @@ impl<'a> From<&'a Type> for InterfaceType<'a> {
             | Type::U32
             | Type::U64
             | Type::Float32
-            | Type::Float64 => InterfaceType::TypeRef(typ),
+            | Type::Float64
+            | Type::Own(_)
+            | Type::Borrow(_) => InterfaceType::TypeRef(typ),
             _ => InterfaceType::Any,
         }
     }
@@ -147,6 +150,65 @@ impl Default for Tags {
     }
 }
 
+/// Host resources, backed by [Cid]s, that resource handles, i.e. `own` and
+/// `borrow` values, are converted to and from.
+///
+/// Handles are only valid within the [wasmtime::Store] they were created in,
+/// so conversions involving them go through [RuntimeVal::try_from_with] and
+/// [RuntimeVal::try_into_ipld_with].
+pub trait CidResources {
+    /// Get a handle, of the given resource type, to the resource backed by
+    /// `cid`, lent to the guest for the duration of a call if `borrow`.
+    fn lift(&mut self, cid: Cid, ty: &ResourceType, borrow: bool) -> Result<Val, InterpreterError>;
+
+    /// Get the [Cid] backing the resource of an owned handle, releasing the
+    /// handle.
+    fn lower(&mut self, handle: ResourceAny) -> Result<Cid, InterpreterError>;
+}
+
+/// [CidResources] for conversions outside of a [wasmtime::Store], where
+/// resource handles are unsupported.
+#[derive(Clone, Copy, Debug, Default)]
+struct NoResources;
+
+impl CidResources for NoResources {
+    fn lift(
+        &mut self,
+        cid: Cid,
+        _ty: &ResourceType,
+        _borrow: bool,
+    ) -> Result<Val, InterpreterError> {
+        Err(InterpreterError::ResourceHandle(format!(
+            "no resources to back {cid} with outside of a store"
+        )))
+    }
+
+    fn lower(&mut self, _handle: ResourceAny) -> Result<Cid, InterpreterError> {
+        Err(InterpreterError::ResourceHandle(
+            "no resources to resolve handle with outside of a store".to_string(),
+        ))
+    }
+}
+
+/// Whether values of a [Type] are, or contain, resource handles.
+pub fn contains_resource(ty: &Type) -> bool {
+    match ty {
+        Type::Own(_) | Type::Borrow(_) => true,
+        Type::List(list) => contains_resource(&list.ty()),
+        Type::Record(record) => record.fields().any(|field| contains_resource(&field.ty)),
+        Type::Tuple(tuple) => tuple.types().any(|ty| contains_resource(&ty)),
+        Type::Variant(variant) => variant
+            .cases()
+            .any(|case| case.ty.as_ref().is_some_and(contains_resource)),
+        Type::Option(option) => contains_resource(&option.ty()),
+        Type::Result(result) => {
+            result.ok().as_ref().is_some_and(contains_resource)
+                || result.err().as_ref().is_some_and(contains_resource)
+        }
+        _ => false,
+    }
+}
+
 /// Wrapper-type for runtime [wasmtime::component::Val].
 #[derive(Debug, PartialEq)]
 pub struct RuntimeVal(Val, Tags);
@@ -176,6 +238,16 @@ impl RuntimeVal {
     pub fn try_from(
         ipld: Ipld,
         interface_ty: &InterfaceType<'_>,
+    ) -> Result<Self, InterpreterError> {
+        Self::try_from_with(ipld, interface_ty, &mut NoResources)
+    }
+
+    /// Convert from Ipld to [RuntimeVal] with a given [InterfaceType],
+    /// lifting links into resource handles through [CidResources].
+    pub fn try_from_with(
+        ipld: Ipld,
+        interface_ty: &InterfaceType<'_>,
+        resources: &mut dyn CidResources,
     ) -> Result<Self, InterpreterError> {
         // TODO: Configure for recursion.
         stacker::maybe_grow(DEFAULT_RED_ZONE, DEFAULT_EXTRA_STACK, || {
@@ -202,7 +274,11 @@ impl RuntimeVal {
                             })?;
 
                     let opt_inst = inner.unwrap_option();
-                    let inner_v = RuntimeVal::try_from(v, &InterfaceType::TypeRef(&opt_inst.ty()))?;
+                    let inner_v = RuntimeVal::try_from_with(
+                        v,
+                        &InterfaceType::TypeRef(&opt_inst.ty()),
+                        resources,
+                    )?;
                     RuntimeVal::new(opt_inst.new_val(Some(inner_v.value()))?)
                 }
                 v if matches!(interface_ty.inner(), Some(Type::Result(_))) => {
@@ -233,16 +309,18 @@ impl RuntimeVal {
                                 RuntimeVal::new(res_inst.new_val(Err(None))?)
                             }
                             ([ipld, Ipld::Null], Some(ty), _) => {
-                                let inner_v = RuntimeVal::try_from(
+                                let inner_v = RuntimeVal::try_from_with(
                                     ipld.to_owned(),
                                     &InterfaceType::TypeRef(&ty),
+                                    resources,
                                 )?;
                                 RuntimeVal::new(res_inst.new_val(Ok(Some(inner_v.value())))?)
                             }
                             ([Ipld::Null, ipld], _, Some(ty)) => {
-                                let inner_v = RuntimeVal::try_from(
+                                let inner_v = RuntimeVal::try_from_with(
                                     ipld.to_owned(),
                                     &InterfaceType::TypeRef(&ty),
+                                    resources,
                                 )?;
                                 RuntimeVal::new(res_inst.new_val(Err(Some(inner_v.value())))?)
                             }
@@ -369,6 +447,12 @@ impl RuntimeVal {
                         expected: "<list>".to_string(),
                         given: format!("{} as a string", v).into(),
                     })?,
+                    Some(Type::Own(ty)) => {
+                        RuntimeVal::new(resources.lift(Cid::try_from(v.as_str())?, ty, false)?)
+                    }
+                    Some(Type::Borrow(ty)) => {
+                        RuntimeVal::new(resources.lift(Cid::try_from(v.as_str())?, ty, true)?)
+                    }
                     _ => RuntimeVal::new(Val::String(Box::from(v))),
                 },
                 Ipld::Bytes(v) => match interface_ty.inner() {
@@ -383,19 +467,26 @@ impl RuntimeVal {
                     }
                     _ => RuntimeVal::new(Val::String(Box::from(Base::Base64.encode(v)))),
                 },
-                Ipld::Link(v) => match v.version() {
-                    cid::Version::V0 => RuntimeVal::new(Val::String(Box::from(
-                        v.to_string_of_base(Base::Base58Btc)?,
-                    ))),
-                    cid::Version::V1 => RuntimeVal::new(Val::String(Box::from(
-                        v.to_string_of_base(Base::Base32Lower)?,
-                    ))),
+                Ipld::Link(v) => match interface_ty.inner() {
+                    Some(Type::Own(ty)) => RuntimeVal::new(resources.lift(v, ty, false)?),
+                    Some(Type::Borrow(ty)) => RuntimeVal::new(resources.lift(v, ty, true)?),
+                    _ => match v.version() {
+                        cid::Version::V0 => RuntimeVal::new(Val::String(Box::from(
+                            v.to_string_of_base(Base::Base58Btc)?,
+                        ))),
+                        cid::Version::V1 => RuntimeVal::new(Val::String(Box::from(
+                            v.to_string_of_base(Base::Base32Lower)?,
+                        ))),
+                    },
                 },
                 Ipld::List(v) => match interface_ty.inner() {
                     Some(Type::List(list_inst)) => {
                         let vec = v.into_iter().try_fold(vec![], |mut acc, elem| {
-                            let RuntimeVal(value, _) =
-                                RuntimeVal::try_from(elem, &InterfaceType::Type(list_inst.ty()))?;
+                            let RuntimeVal(value, _) = RuntimeVal::try_from_with(
+                                elem,
+                                &InterfaceType::Type(list_inst.ty()),
+                                resources,
+                            )?;
                             acc.push(value);
                             Ok::<_, InterpreterError>(acc)
                         })?;
@@ -406,8 +497,11 @@ impl RuntimeVal {
                         let fields = tuple_inst.types().zip(v.into_iter()).try_fold(
                             vec![],
                             |mut acc, (ty, elem)| {
-                                let RuntimeVal(value, _) =
-                                    RuntimeVal::try_from(elem, &InterfaceType::TypeRef(&ty))?;
+                                let RuntimeVal(value, _) = RuntimeVal::try_from_with(
+                                    elem,
+                                    &InterfaceType::TypeRef(&ty),
+                                    resources,
+                                )?;
                                 acc.push(value);
                                 Ok::<_, InterpreterError>(acc)
                             },
@@ -434,7 +528,7 @@ impl RuntimeVal {
                     _ => v
                         .into_iter()
                         .fold_while(Ok(RuntimeVal::new(Val::Bool(false))), |_acc, elem| {
-                            match RuntimeVal::try_from(elem, interface_ty) {
+                            match RuntimeVal::try_from_with(elem, interface_ty, resources) {
                                 Ok(runtime_val) => Done(Ok(runtime_val)),
                                 Err(e) => Done(Err(e)),
                             }
@@ -460,8 +554,11 @@ impl RuntimeVal {
                         let (vec, tags) = v.into_iter().try_fold(
                             (vec![], VecDeque::new()),
                             |(mut acc_tuples, mut acc_tags), (key, elem)| {
-                                let RuntimeVal(value, tags) =
-                                    RuntimeVal::try_from(elem, &InterfaceType::TypeRef(&ty))?;
+                                let RuntimeVal(value, tags) = RuntimeVal::try_from_with(
+                                    elem,
+                                    &InterfaceType::TypeRef(&ty),
+                                    resources,
+                                )?;
 
                                 let tuple = Box::new([Val::String(Box::from(key)), value]);
                                 let new_tuple = tuple_inst.new_val(tuple)?;
@@ -487,9 +584,10 @@ impl RuntimeVal {
                             Some(case) => {
                                 let opt_ty = case.ty;
                                 if let Some(ty) = opt_ty {
-                                    let RuntimeVal(value, _) = RuntimeVal::try_from(
+                                    let RuntimeVal(value, _) = RuntimeVal::try_from_with(
                                         v_inner.to_owned(),
                                         &InterfaceType::TypeRef(&ty),
+                                        resources,
                                     )?;
                                     RuntimeVal::new(variant_inst.new_val(case.name, Some(value))?)
                                 } else {
@@ -508,9 +606,10 @@ impl RuntimeVal {
                                 .try_fold(IndexMap::new(), |mut acc, field| {
                                     if let Some((k, v_inner)) = v.get_key_value(field.name) {
                                         if field.name == *k {
-                                            let RuntimeVal(value, _) = RuntimeVal::try_from(
+                                            let RuntimeVal(value, _) = RuntimeVal::try_from_with(
                                                 v_inner.to_owned(),
                                                 &InterfaceType::TypeRef(&field.ty),
+                                                resources,
                                             )?;
                                             acc.insert(field.name, value);
                                             Ok::<_, InterpreterError>(acc)
@@ -542,15 +641,184 @@ impl RuntimeVal {
     }
 }
 
+/// JSON Schema of the DAG-JSON accepted for an [InterfaceType], following
+/// the conversions of [RuntimeVal::try_from_with].
+///
+/// Lists given for types other than lists, tuples and flags are converted
+/// by their first element; schemas leave this lenience out.
+pub fn json_schema(interface_ty: &InterfaceType<'_>) -> Value {
+    fn any_of(schemas: impl IntoIterator<Item = Value>) -> Value {
+        json!({ "anyOf": schemas.into_iter().collect::<Vec<_>>() })
+    }
+
+    fn nil() -> Value {
+        json!({ "type": "null" })
+    }
+
+    // `Ipld::Bytes`, as encoded in DAG-JSON.
+    fn bytes() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "/": {
+                    "type": "object",
+                    "properties": { "bytes": { "type": "string" } },
+                    "required": ["bytes"],
+                },
+            },
+            "required": ["/"],
+        })
+    }
+
+    // `Ipld::Link`, as encoded in DAG-JSON.
+    fn link() -> Value {
+        json!({
+            "type": "object",
+            "properties": { "/": { "type": "string" } },
+            "required": ["/"],
+        })
+    }
+
+    // `Ipld::Integer`s are range-checked, while `Ipld::Float`s are cast.
+    fn integer(min: i128, max: i128) -> Value {
+        json!({
+            "type": "number",
+            "if": { "type": "integer" },
+            "then": { "minimum": min, "maximum": max },
+        })
+    }
+
+    let Some(ty) = interface_ty.inner() else {
+        return json!({});
+    };
+
+    match ty {
+        Type::Option(option) => any_of([nil(), json_schema(&InterfaceType::Type(option.ty()))]),
+        // Results are given as `[ok, null]` or `[null, err]`, with `1` in
+        // place of a missing payload.
+        Type::Result(result) => {
+            let payload = |ty: Option<Type>| match ty {
+                Some(ty) => json_schema(&InterfaceType::Type(ty)),
+                None => json!({}),
+            };
+            let (ok, err) = (payload(result.ok()), payload(result.err()));
+            any_of([
+                json!({
+                    "type": "array",
+                    "items": [ok, nil()],
+                    "minItems": 2,
+                    "maxItems": 2,
+                }),
+                json!({
+                    "type": "array",
+                    "items": [nil(), err],
+                    "minItems": 2,
+                    "maxItems": 2,
+                }),
+            ])
+        }
+        Type::Bool => json!({ "type": "boolean" }),
+        Type::U8 => integer(u8::MIN.into(), u8::MAX.into()),
+        Type::U16 => integer(u16::MIN.into(), u16::MAX.into()),
+        Type::U32 => integer(u32::MIN.into(), u32::MAX.into()),
+        Type::U64 => integer(u64::MIN.into(), u64::MAX.into()),
+        Type::S8 => integer(i8::MIN.into(), i8::MAX.into()),
+        Type::S16 => integer(i16::MIN.into(), i16::MAX.into()),
+        Type::S32 => integer(i32::MIN.into(), i32::MAX.into()),
+        // Only `Ipld::Integer`s convert to `s64`.
+        Type::S64 => json!({ "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX }),
+        Type::Float32 | Type::Float64 => json!({ "type": "number" }),
+        Type::Enum(enum_ty) => json!({
+            "type": "string",
+            "enum": enum_ty.names().collect::<Vec<_>>(),
+        }),
+        Type::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        // Nulls, bytes and links are converted to their string encodings.
+        Type::String => any_of([nil(), json!({ "type": "string" }), bytes(), link()]),
+        Type::List(list) => {
+            let items = json_schema(&InterfaceType::Type(list.ty()));
+            let mut schemas = vec![json!({ "type": "array", "items": items })];
+            match list.ty() {
+                Type::U8 => schemas.push(bytes()),
+                // Maps are converted to lists of `(key, value)` tuples.
+                Type::Tuple(tuple) => {
+                    if let Some(value) = tuple.types().nth(1) {
+                        schemas.push(json!({
+                            "type": "object",
+                            "additionalProperties": json_schema(&InterfaceType::Type(value)),
+                        }));
+                    }
+                }
+                _ => {}
+            }
+            any_of(schemas)
+        }
+        Type::Tuple(tuple) => {
+            let items = tuple
+                .types()
+                .map(|ty| json_schema(&InterfaceType::Type(ty)))
+                .collect::<Vec<_>>();
+            json!({ "type": "array", "items": items, "minItems": items.len() })
+        }
+        Type::Flags(flags) => json!({
+            "type": "array",
+            "items": { "type": "string", "enum": flags.names().collect::<Vec<_>>() },
+        }),
+        // Variants are given as a map from a case to its payload.
+        Type::Variant(variant) => json!({
+            "oneOf": variant.cases().map(|case| json!({
+                "type": "object",
+                "properties": {
+                    case.name: case
+                        .ty
+                        .map(|ty| json_schema(&InterfaceType::Type(ty)))
+                        .unwrap_or_else(|| json!({})),
+                },
+                "required": [case.name],
+                "maxProperties": 1,
+            })).collect::<Vec<_>>()
+        }),
+        Type::Record(record) => {
+            let (properties, required): (serde_json::Map<_, _>, Vec<_>) = record
+                .fields()
+                .map(|field| {
+                    (
+                        (
+                            field.name.to_string(),
+                            json_schema(&InterfaceType::TypeRef(&field.ty)),
+                        ),
+                        field.name.to_string(),
+                    )
+                })
+                .unzip();
+            json!({ "type": "object", "properties": properties, "required": required })
+        }
+        // Resource handles are given as links, or CID strings, to the
+        // resources backing them.
+        Type::Own(_) | Type::Borrow(_) => any_of([link(), json!({ "type": "string" })]),
+    }
+}
+
 impl TryFrom<RuntimeVal> for Ipld {
     type Error = InterpreterError;
 
     fn try_from(val: RuntimeVal) -> Result<Self, Self::Error> {
+        val.try_into_ipld_with(&mut NoResources)
+    }
+}
+
+impl RuntimeVal {
+    /// Convert from [RuntimeVal] to Ipld, lowering resource handles into
+    /// links through [CidResources].
+    pub fn try_into_ipld_with(
+        self,
+        resources: &mut dyn CidResources,
+    ) -> Result<Ipld, InterpreterError> {
         fn cid(s: &str) -> Result<Cid, cid::Error> {
             Cid::try_from(s)
         }
         stacker::maybe_grow(DEFAULT_RED_ZONE, DEFAULT_EXTRA_STACK, || {
-            let ipld = match val {
+            let ipld = match self {
                 RuntimeVal(Val::Char(c), _) => Ipld::String(c.to_string()),
                 RuntimeVal(Val::String(v), _) => match v.to_string() {
                     s if s.eq("null") => Ipld::Null,
@@ -587,12 +855,10 @@ impl TryFrom<RuntimeVal> for Ipld {
                         if let Val::Tuple(tup) = elem {
                             let tup_values = tup.values();
                             if let [Val::String(s), v] = tup_values {
-                                let ipld = Ipld::try_from(RuntimeVal::new_with_tags(
-                                    v.to_owned(),
-                                    tags.clone(),
-                                ))?;
+                                let ipld = RuntimeVal::new_with_tags(v.to_owned(), tags.clone())
+                                    .try_into_ipld_with(resources)?;
                                 acc.insert(s.to_string(), ipld);
-                                Ok::<_, Self::Error>(acc)
+                                Ok::<_, InterpreterError>(acc)
                             } else {
                                 Err(InterpreterError::TypeMismatch {
                                     expected: "<tuple> of (<string>, <&wasmtime::Val>)".to_string(),
@@ -613,7 +879,7 @@ impl TryFrom<RuntimeVal> for Ipld {
                         let inner = v.iter().try_fold(vec![], |mut acc, elem| {
                             if let Val::U8(v) = elem {
                                 acc.push(v.to_owned());
-                                Ok::<_, Self::Error>(acc)
+                                Ok::<_, InterpreterError>(acc)
                             } else {
                                 Err(InterpreterError::TypeMismatch {
                                     expected: "all <u8> types".to_string(),
@@ -626,9 +892,10 @@ impl TryFrom<RuntimeVal> for Ipld {
                     }
                     Some(_) => {
                         let inner = v.iter().try_fold(vec![], |mut acc, elem| {
-                            let ipld = Ipld::try_from(RuntimeVal::new(elem.to_owned()))?;
+                            let ipld =
+                                RuntimeVal::new(elem.to_owned()).try_into_ipld_with(resources)?;
                             acc.push(ipld);
-                            Ok::<_, Self::Error>(acc)
+                            Ok::<_, InterpreterError>(acc)
                         })?;
                         Ipld::List(inner)
                     }
@@ -636,7 +903,7 @@ impl TryFrom<RuntimeVal> for Ipld {
                 },
                 RuntimeVal(Val::Variant(v), _) => {
                     let inner = if let Some(payload) = v.payload() {
-                        Ipld::try_from(RuntimeVal::new(payload.to_owned()))?
+                        RuntimeVal::new(payload.to_owned()).try_into_ipld_with(resources)?
                     } else {
                         Ipld::Null
                     };
@@ -645,36 +912,37 @@ impl TryFrom<RuntimeVal> for Ipld {
                 }
                 RuntimeVal(Val::Record(v), _) => {
                     let inner = v.fields().try_fold(BTreeMap::new(), |mut acc, (k, v)| {
-                        let ipld = Ipld::try_from(RuntimeVal::new(v.to_owned()))?;
+                        let ipld = RuntimeVal::new(v.to_owned()).try_into_ipld_with(resources)?;
                         acc.insert(k.to_string(), ipld);
-                        Ok::<_, Self::Error>(acc)
+                        Ok::<_, InterpreterError>(acc)
                     })?;
                     Ipld::Map(inner)
                 }
                 RuntimeVal(Val::Option(opt), _) => {
                     if let Some(v) = opt.value() {
-                        Ipld::try_from(RuntimeVal::new(v.to_owned()))?
+                        RuntimeVal::new(v.to_owned()).try_into_ipld_with(resources)?
                     } else {
                         Ipld::Null
                     }
                 }
                 RuntimeVal(Val::Result(res), _) => match res.value() {
                     Ok(Some(v)) => Ipld::List(vec![
-                        Ipld::try_from(RuntimeVal::new(v.to_owned()))?,
+                        RuntimeVal::new(v.to_owned()).try_into_ipld_with(resources)?,
                         Ipld::Null,
                     ]),
                     Ok(None) => Ipld::List(vec![Ipld::Integer(1), Ipld::Null]),
                     Err(Some(v)) => Ipld::List(vec![
                         Ipld::Null,
-                        Ipld::try_from(RuntimeVal::new(v.to_owned()))?,
+                        RuntimeVal::new(v.to_owned()).try_into_ipld_with(resources)?,
                     ]),
                     Err(None) => Ipld::List(vec![Ipld::Null, Ipld::Integer(1)]),
                 },
                 RuntimeVal(Val::Tuple(v), _) => {
                     let inner = v.values().iter().try_fold(vec![], |mut acc, elem| {
-                        let ipld = Ipld::try_from(RuntimeVal::new(elem.to_owned()))?;
+                        let ipld =
+                            RuntimeVal::new(elem.to_owned()).try_into_ipld_with(resources)?;
                         acc.push(ipld);
-                        Ok::<_, Self::Error>(acc)
+                        Ok::<_, InterpreterError>(acc)
                     })?;
                     Ipld::List(inner)
                 }
@@ -686,8 +954,7 @@ impl TryFrom<RuntimeVal> for Ipld {
                     Ipld::List(inner)
                 }
                 RuntimeVal(Val::Enum(v), _) => Ipld::String(v.discriminant().to_string()),
-                // Rest of Wit types are unhandled going to Ipld.
-                v => Err(InterpreterError::WitToIpld(format!("{v:#?}").into()))?,
+                RuntimeVal(Val::Resource(handle), _) => Ipld::Link(resources.lower(handle)?),
             };
 
             Ok(ipld)
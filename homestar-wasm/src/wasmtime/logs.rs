@@ -0,0 +1,201 @@
+//! Guest log records, written through `print` and `wasi:logging`, buffered
+//! per task and optionally streamed to a [LogSink] as they're written.
+
+use libipld::Ipld;
+use std::{collections::BTreeMap, fmt};
+
+/// Default cap, in bytes of messages, on the logs buffered for a task.
+pub const DEFAULT_LOG_CAPACITY: usize = 64 * 1024;
+
+/// Context of records written through the host `print` helper.
+pub const PRINT_CONTEXT: &str = "print";
+
+/// Context of records written by the host itself, e.g. on dropped records.
+pub const HOST_CONTEXT: &str = "homestar";
+
+/// Level of a guest [LogRecord].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    /// Trace level.
+    Trace,
+    /// Debug level.
+    Debug,
+    /// Info level, also used for `print`.
+    Info,
+    /// Warn level.
+    Warn,
+    /// Error level.
+    Error,
+    /// Critical level.
+    Critical,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogLevel::Trace => write!(f, "trace"),
+            LogLevel::Debug => write!(f, "debug"),
+            LogLevel::Info => write!(f, "info"),
+            LogLevel::Warn => write!(f, "warn"),
+            LogLevel::Error => write!(f, "error"),
+            LogLevel::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Log record written by a guest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Level of the record.
+    pub level: LogLevel,
+    /// Context given by the guest, or [PRINT_CONTEXT].
+    pub context: String,
+    /// Message of the record.
+    pub message: String,
+}
+
+impl From<LogRecord> for Ipld {
+    fn from(record: LogRecord) -> Self {
+        Ipld::Map(BTreeMap::from([
+            ("level".into(), Ipld::String(record.level.to_string())),
+            ("context".into(), Ipld::String(record.context)),
+            ("message".into(), Ipld::String(record.message)),
+        ]))
+    }
+}
+
+/// Sink receiving guest [LogRecord]s as they're written, e.g. to publish
+/// them to subscribers.
+pub trait LogSink: Send + Sync {
+    /// Receive a [LogRecord].
+    fn log(&self, record: &LogRecord);
+}
+
+/// Buffer of a task's [LogRecord]s, capped by the total size of their
+/// messages. Records past the cap are dropped and counted.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    records: Vec<LogRecord>,
+    size: usize,
+    capacity: usize,
+    dropped: usize,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    /// Create a new [LogBuffer] holding up to `capacity` bytes of messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Vec::new(),
+            size: 0,
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Buffer a [LogRecord], returning `false` if it was dropped for
+    /// exceeding the cap.
+    pub fn push(&mut self, record: LogRecord) -> bool {
+        let size = record.context.len() + record.message.len();
+        if self.size + size > self.capacity {
+            self.dropped += 1;
+            return false;
+        }
+
+        self.size += size;
+        self.records.push(record);
+        true
+    }
+
+    /// Buffered [LogRecord]s, in the order written.
+    pub fn records(&self) -> &[LogRecord] {
+        &self.records
+    }
+
+    /// Number of [LogRecord]s dropped for exceeding the cap.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Notice of the [LogRecord]s dropped for exceeding the cap, if any.
+    pub fn dropped_notice(&self) -> Option<LogRecord> {
+        (self.dropped > 0).then(|| LogRecord {
+            level: LogLevel::Warn,
+            context: HOST_CONTEXT.to_string(),
+            message: format!(
+                "{} log records dropped past the cap of {} bytes",
+                self.dropped, self.capacity
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wasmtime::State;
+    use std::sync::{Arc, Mutex};
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord {
+            level: LogLevel::Info,
+            context: PRINT_CONTEXT.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn buffer_drops_past_capacity() {
+        let mut logs = LogBuffer::new(PRINT_CONTEXT.len() * 2 + 8);
+        assert!(logs.push(record("four")));
+        assert!(logs.push(record("four")));
+        assert!(!logs.push(record("x")));
+
+        assert_eq!(logs.records(), &[record("four"), record("four")]);
+        assert_eq!(logs.dropped(), 1);
+        assert_eq!(
+            logs.dropped_notice().map(|notice| notice.context),
+            Some(HOST_CONTEXT.to_string())
+        );
+        assert_eq!(LogBuffer::default().dropped_notice(), None);
+    }
+
+    #[test]
+    fn streams_only_buffered_logs() {
+        #[derive(Default)]
+        struct Sink(Mutex<Vec<LogRecord>>);
+
+        impl LogSink for Sink {
+            fn log(&self, record: &LogRecord) {
+                self.0.lock().unwrap().push(record.clone())
+            }
+        }
+
+        let sink = Arc::new(Sink::default());
+        let mut state = State::default();
+        state.set_log_capacity(PRINT_CONTEXT.len() + 4);
+        state.set_log_sink(sink.clone());
+
+        state.log(record("four"));
+        state.log(record("dropped"));
+        state.finish_logs();
+
+        let streamed = sink.0.lock().unwrap();
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed[0], record("four"));
+        assert_eq!(streamed[1].context, HOST_CONTEXT);
+        assert!(streamed[1].message.starts_with("1 log records dropped"));
+    }
+
+    #[test]
+    fn record_to_ipld() {
+        let ipld = Ipld::from(record("hello"));
+        assert_eq!(ipld.get("level").unwrap(), &Ipld::String("info".into()));
+        assert_eq!(ipld.get("message").unwrap(), &Ipld::String("hello".into()));
+    }
+}
@@ -3,12 +3,22 @@
 //!
 //! [Wasmtime]: <https://wasmtime.dev/>
 
+pub mod blocks;
 pub mod config;
+pub mod describe;
+mod deterministic;
 mod error;
 mod host;
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub mod http;
 pub mod ipld;
 pub mod limits;
+pub mod logs;
+pub mod runtime;
+pub mod wasi;
 pub mod world;
 
 pub use error::*;
+pub use runtime::Runtime;
 pub use world::{State, World};
@@ -0,0 +1,423 @@
+//! Process-wide [Wasmtime] [Engine] and [Linker], shared across
+//! [environments], along with a cache of compiled [Component]s keyed by
+//! resource [Cid].
+//!
+//! Compiled components are kept in memory, evicting the least-recently used
+//! past a given capacity, and can optionally be persisted to disk as
+//! precompiled artifacts, which are only loaded back by an engine with a
+//! matching compatibility hash.
+//!
+//! Each [Runtime] also advances its engine's epoch every [EPOCH_TICK], which
+//! interrupts guests running past their [State]'s time limit.
+//!
+//! [Wasmtime]: <https://docs.rs/wasmtime/latest/wasmtime/>
+//! [environments]: Env
+
+use crate::wasmtime::{
+    world::{component_binary, Env},
+    Error, State, World,
+};
+use indexmap::IndexMap;
+use libipld::{
+    cid::Cid,
+    multihash::{Code, MultihashDigest},
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt, fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock, Weak},
+    thread,
+    time::Duration,
+};
+use tracing::{debug, warn};
+use wasmtime::{
+    component::{Component, Linker},
+    Engine,
+};
+
+/// Default number of compiled [Component]s kept in memory.
+pub const DEFAULT_CACHE_CAPACITY: usize = 64;
+
+/// Interval the engine's epoch is advanced at, i.e. the resolution of
+/// time limits.
+pub const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Multicodec code for raw binary blocks.
+const RAW: u64 = 0x55;
+
+/// File extension for precompiled components.
+const PRECOMPILED_EXTENSION: &str = "cwasm";
+
+static GLOBAL: OnceLock<Runtime> = OnceLock::new();
+
+/// Shared [Engine], [Linker] and compiled [Component] cache.
+///
+/// Clones are shallow, sharing the same engine, linker and caches.
+#[derive(Clone)]
+pub struct Runtime(Arc<Inner>);
+
+struct Inner {
+    engine: Engine,
+    linker: Arc<Linker<State>>,
+    components: Mutex<IndexMap<Cid, Component>>,
+    capacity: usize,
+    precompiled_dir: Option<PathBuf>,
+}
+
+impl fmt::Debug for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Runtime")
+            .field("capacity", &self.0.capacity)
+            .field("precompiled_dir", &self.0.precompiled_dir)
+            .finish()
+    }
+}
+
+impl Runtime {
+    /// Create a new [Runtime], keeping up to `capacity` compiled
+    /// [Component]s in memory, and persisting precompiled components
+    /// under `precompiled_dir`, if given.
+    pub fn new(capacity: usize, precompiled_dir: Option<PathBuf>) -> Result<Self, Error> {
+        let engine = Engine::new(&World::configure())?;
+        let linker = World::define_linker(&engine)?;
+
+        // Namespace precompiled artifacts by the engine's compatibility hash,
+        // so artifacts from another wasmtime version or configuration are
+        // never picked up.
+        let precompiled_dir = precompiled_dir.map(|dir| {
+            let mut hasher = DefaultHasher::new();
+            engine.precompile_compatibility_hash().hash(&mut hasher);
+            dir.join(format!("{:016x}", hasher.finish()))
+        });
+
+        let inner = Arc::new(Inner {
+            engine,
+            linker: Arc::new(linker),
+            components: Mutex::new(IndexMap::with_capacity(capacity)),
+            capacity,
+            precompiled_dir,
+        });
+        spawn_epoch_ticker(Arc::downgrade(&inner))?;
+
+        Ok(Self(inner))
+    }
+
+    /// Process-wide default [Runtime], with an in-memory cache of
+    /// [DEFAULT_CACHE_CAPACITY] components and no on-disk cache.
+    pub fn global() -> Result<&'static Self, Error> {
+        if let Some(runtime) = GLOBAL.get() {
+            return Ok(runtime);
+        }
+
+        let runtime = Self::new(DEFAULT_CACHE_CAPACITY, None)?;
+        Ok(GLOBAL.get_or_init(|| runtime))
+    }
+
+    /// Content [Cid] of Wasm bytes, for resources not already addressed by
+    /// one.
+    pub fn content_cid(bytes: &[u8]) -> Cid {
+        Cid::new_v1(RAW, Code::Sha3_256.digest(bytes))
+    }
+
+    /// Shared [Engine].
+    pub fn engine(&self) -> &Engine {
+        &self.0.engine
+    }
+
+    /// Directory precompiled components are persisted to, if enabled.
+    pub fn precompiled_dir(&self) -> Option<&Path> {
+        self.0.precompiled_dir.as_deref()
+    }
+
+    /// Number of compiled [Component]s currently held in memory.
+    pub fn cached(&self) -> usize {
+        self.components().len()
+    }
+
+    /// Create a new [environment] for [State], on the shared engine and
+    /// linker.
+    ///
+    /// [environment]: Env
+    pub fn env(&self, data: State) -> Result<Env<State>, Error> {
+        let time_limit = data.time_limit();
+        let store = World::define_store(&self.0.engine, data)?;
+        Ok(Env::new(
+            self.0.engine.clone(),
+            self.0.linker.clone(),
+            store,
+            time_limit,
+        ))
+    }
+
+    /// Instantiate the resource identified by `cid`, compiling `bytes` only
+    /// if it's not already cached, into the [environment]'s store.
+    ///
+    /// [environment]: Env
+    pub async fn instantiate<'a>(
+        &self,
+        cid: Cid,
+        bytes: &[u8],
+        fun_name: &'a str,
+        env: &'a mut Env<State>,
+    ) -> Result<&'a mut Env<State>, Error> {
+        let component = self.component(cid, bytes)?;
+        World::instantiate_component(&component, fun_name, env).await
+    }
+
+    /// Retrieve the compiled [Component] for the resource identified by
+    /// `cid`, from memory, from disk, or by compiling `bytes`.
+    pub fn component(&self, cid: Cid, bytes: &[u8]) -> Result<Component, Error> {
+        {
+            let mut components = self.components();
+            if let Some(component) = components.shift_remove(&cid) {
+                components.insert(cid, component.clone());
+                return Ok(component);
+            }
+        }
+
+        let component = match self.load_precompiled(cid) {
+            Some(component) => component,
+            None => self.compile(cid, bytes)?,
+        };
+
+        if self.0.capacity > 0 {
+            let mut components = self.components();
+            while components.len() >= self.0.capacity {
+                components.shift_remove_index(0);
+            }
+            components.insert(cid, component.clone());
+        }
+
+        Ok(component)
+    }
+
+    fn components(&self) -> std::sync::MutexGuard<'_, IndexMap<Cid, Component>> {
+        self.0
+            .components
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn precompiled_path(&self, cid: Cid) -> Option<PathBuf> {
+        self.0
+            .precompiled_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{cid}.{PRECOMPILED_EXTENSION}")))
+    }
+
+    fn load_precompiled(&self, cid: Cid) -> Option<Component> {
+        let path = self.precompiled_path(cid).filter(|path| path.is_file())?;
+
+        // SAFETY: artifacts are only ever written by `compile` below, through
+        // `Engine::precompile_component`, into a directory namespaced by this
+        // engine's compatibility hash; wasmtime additionally checks the
+        // artifact's header against the engine before loading it.
+        match unsafe { Component::deserialize_file(&self.0.engine, &path) } {
+            Ok(component) => {
+                debug!(
+                    subject = "wasm.precompiled.load",
+                    category = "wasm.cache",
+                    cid = cid.to_string(),
+                    "loaded precompiled component from disk"
+                );
+                Some(component)
+            }
+            Err(err) => {
+                warn!(
+                    subject = "wasm.precompiled.err",
+                    category = "wasm.cache",
+                    cid = cid.to_string(),
+                    path = path.display().to_string(),
+                    err=?err,
+                    "failed to load precompiled component, recompiling"
+                );
+                None
+            }
+        }
+    }
+
+    fn compile(&self, cid: Cid, bytes: &[u8]) -> Result<Component, Error> {
+        let binary = component_binary(bytes)?;
+        let Some(path) = self.precompiled_path(cid) else {
+            return Component::from_binary(&self.0.engine, &binary)
+                .map_err(Error::IntoWasmComponent);
+        };
+
+        let precompiled = self
+            .0
+            .engine
+            .precompile_component(&binary)
+            .map_err(Error::IntoWasmComponent)?;
+
+        if let Err(err) = write_atomically(&path, &precompiled) {
+            warn!(
+                subject = "wasm.precompiled.err",
+                category = "wasm.cache",
+                cid = cid.to_string(),
+                path = path.display().to_string(),
+                err=?err,
+                "failed to persist precompiled component"
+            );
+        }
+
+        // SAFETY: `precompiled` was just produced by this same engine.
+        unsafe { Component::deserialize(&self.0.engine, &precompiled) }
+            .map_err(Error::IntoWasmComponent)
+    }
+}
+
+/// Advance the engine's epoch every [EPOCH_TICK], for as long as the
+/// [Runtime] is alive.
+fn spawn_epoch_ticker(inner: Weak<Inner>) -> Result<(), Error> {
+    thread::Builder::new()
+        .name("homestar-wasm-epoch".to_string())
+        .spawn(move || {
+            while let Some(inner) = inner.upgrade() {
+                inner.engine.increment_epoch();
+                drop(inner);
+                thread::sleep(EPOCH_TICK);
+            }
+        })
+        .map_err(|err| Error::WasmRuntime(err.into()))?;
+    Ok(())
+}
+
+/// Write to a temporary file alongside `path` first, so concurrent readers
+/// never observe a partially-written artifact.
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!(
+        "{PRECOMPILED_EXTENSION}.{}.tmp",
+        std::process::id()
+    ));
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::io::Output;
+    use homestar_invocation::task::instruction::{Args, Input};
+    use libipld::Ipld;
+
+    fn fixture(file: &str) -> Vec<u8> {
+        fs::read(format!("{}/fixtures/{file}", env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    fn precompiled_files(dir: &Path) -> usize {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .flat_map(|entry| fs::read_dir(entry.path()).into_iter().flatten())
+                    .flatten()
+                    .filter(|entry| {
+                        entry.path().extension().and_then(|ext| ext.to_str())
+                            == Some(PRECOMPILED_EXTENSION)
+                    })
+                    .count()
+            })
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn caches_and_evicts_components() {
+        let runtime = Runtime::new(1, None).unwrap();
+        let add = fixture("example_add_component.wat");
+        let add_cid = Runtime::content_cid(&add);
+        let test = fixture("example_test_component.wasm");
+        let test_cid = Runtime::content_cid(&test);
+
+        runtime.component(add_cid, &add).unwrap();
+        assert_eq!(runtime.cached(), 1);
+        // Cache hit, regardless of bytes.
+        runtime.component(add_cid, &[]).unwrap();
+
+        runtime.component(test_cid, &test).unwrap();
+        assert_eq!(runtime.cached(), 1);
+        assert!(runtime.component(add_cid, &[]).is_err());
+
+        let mut env = runtime.env(State::default()).unwrap();
+        let env = runtime
+            .instantiate(add_cid, &add, "add_two", &mut env)
+            .await
+            .unwrap();
+        let res = env
+            .execute(Args::new(vec![Input::Ipld(Ipld::Integer(1))]))
+            .await
+            .unwrap();
+        assert_eq!(res, Output::Value(wasmtime::component::Val::S32(3)));
+    }
+
+    #[tokio::test]
+    async fn interrupts_past_time_limit() {
+        let spin = br#"
+            (component
+              (core module $m
+                (func (export "spin") (loop $l (br $l))))
+              (core instance $i (instantiate $m))
+              (func (export "spin") (canon lift (core func $i "spin"))))
+        "#;
+
+        let runtime = Runtime::new(1, None).unwrap();
+        let mut state = State::default();
+        state.set_time_limit(Some(Duration::from_millis(50)));
+        let mut env = runtime.env(state).unwrap();
+        let env = runtime
+            .instantiate(Runtime::content_cid(spin), spin, "spin", &mut env)
+            .await
+            .unwrap();
+
+        let err = env.execute(Args::new(vec![])).await.unwrap_err();
+        assert!(matches!(err, Error::WasmInterrupted));
+    }
+
+    #[tokio::test]
+    async fn time_limit_excludes_setup() {
+        let runtime = Runtime::new(1, None).unwrap();
+        let add = fixture("example_add_component.wat");
+        let mut state = State::default();
+        state.set_time_limit(Some(Duration::from_millis(50)));
+        let mut env = runtime.env(state).unwrap();
+        let env = runtime
+            .instantiate(Runtime::content_cid(&add), &add, "add_two", &mut env)
+            .await
+            .unwrap();
+
+        // e.g. slow resolution of args, or compilation.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let res = env
+            .execute(Args::new(vec![Input::Ipld(Ipld::Integer(1))]))
+            .await
+            .unwrap();
+        assert_eq!(res, Output::Value(wasmtime::component::Val::S32(3)));
+    }
+
+    #[tokio::test]
+    async fn persists_precompiled_components() {
+        let dir =
+            std::env::temp_dir().join(format!("homestar_wasm_precompiled_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let add = fixture("example_add_component.wat");
+        let add_cid = Runtime::content_cid(&add);
+
+        let runtime = Runtime::new(0, Some(dir.clone())).unwrap();
+        assert!(runtime.precompiled_dir().unwrap().starts_with(&dir));
+        runtime.component(add_cid, &add).unwrap();
+        assert_eq!(runtime.cached(), 0);
+        assert_eq!(precompiled_files(&dir), 1);
+
+        // A fresh runtime loads the artifact without the original bytes.
+        let runtime = Runtime::new(0, Some(dir.clone())).unwrap();
+        runtime.component(add_cid, &[]).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
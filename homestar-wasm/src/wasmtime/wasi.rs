@@ -0,0 +1,105 @@
+//! WASI capabilities granted to a guest: read-only preopened directories,
+//! environment variables and arguments.
+//!
+//! Guests are given none of these by default; the host decides which to
+//! grant, e.g. from an operator's allowlist.
+
+use crate::wasmtime::Error;
+use cap_std::{ambient_authority, fs::Dir};
+use std::path::PathBuf;
+use wasmtime_wasi::preview2::{DirPerms, FilePerms, WasiCtxBuilder};
+
+/// Directory on the host preopened, read-only, for a guest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preopen {
+    /// Path of the directory on the host.
+    pub host: PathBuf,
+    /// Path the directory is mounted at in the guest.
+    pub guest: String,
+}
+
+/// WASI capabilities granted to a guest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasiCapabilities {
+    dirs: Vec<Preopen>,
+    env: Vec<(String, String)>,
+    args: Vec<String>,
+}
+
+impl WasiCapabilities {
+    /// Create a new, empty, set of [WasiCapabilities].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preopen a host directory, read-only, at `guest` in the guest.
+    pub fn with_dir(mut self, host: impl Into<PathBuf>, guest: impl Into<String>) -> Self {
+        self.dirs.push(Preopen {
+            host: host.into(),
+            guest: guest.into(),
+        });
+        self
+    }
+
+    /// Set an environment variable.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Append an argument.
+    pub fn with_arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Preopened directories.
+    pub fn dirs(&self) -> &[Preopen] {
+        &self.dirs
+    }
+
+    /// Environment variables.
+    pub fn env(&self) -> &[(String, String)] {
+        &self.env
+    }
+
+    /// Arguments.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Whether no capabilities are granted.
+    pub fn is_empty(&self) -> bool {
+        self.dirs.is_empty() && self.env.is_empty() && self.args.is_empty()
+    }
+
+    /// Grant the capabilities on a [WasiCtxBuilder], opening preopened
+    /// directories on the host.
+    pub(crate) fn configure(&self, builder: &mut WasiCtxBuilder) -> Result<(), Error> {
+        for preopen in &self.dirs {
+            let dir = Dir::open_ambient_dir(&preopen.host, ambient_authority()).map_err(|err| {
+                Error::WasiPreopen(preopen.host.display().to_string(), err.to_string())
+            })?;
+            builder.preopened_dir(dir, DirPerms::READ, FilePerms::READ, &preopen.guest);
+        }
+
+        builder.envs(&self.env).args(&self.args);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_preopen_is_an_error() {
+        let capabilities = WasiCapabilities::new().with_dir("/does/not/exist", "/data");
+        let mut builder = WasiCtxBuilder::new();
+
+        assert!(matches!(
+            capabilities.configure(&mut builder),
+            Err(Error::WasiPreopen(path, _)) if path == "/does/not/exist"
+        ));
+    }
+}
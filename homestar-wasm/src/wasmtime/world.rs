@@ -5,12 +5,20 @@
 //!
 //! [Wasmtime]: <https://docs.rs/wasmtime/latest/wasmtime/>
 
+#[cfg(feature = "http")]
+use crate::wasmtime::http::{HttpCall, HttpPolicy, HttpState};
 use crate::{
+    error::InterpreterError,
     io::{Arg, Output},
     wasmtime::{
-        ipld::{InterfaceType, RuntimeVal},
+        blocks::{BlockQuota, BlockStore, BlockUsage, MemoryBlockStore},
+        deterministic,
+        ipld::{contains_resource, CidResources, InterfaceType, RuntimeVal},
         limits::StoreLimitsAsync,
-        Error,
+        logs::{LogBuffer, LogRecord, LogSink},
+        runtime::EPOCH_TICK,
+        wasi::WasiCapabilities,
+        Error, Runtime,
     },
 };
 use heck::{ToKebabCase, ToLowerCamelCase, ToPascalCase, ToSnakeCase};
@@ -19,23 +27,56 @@ use homestar_invocation::{
     error::ResolveError,
     task::instruction::{Args, Input},
 };
-use std::{iter, time::Instant};
+use libipld::{Cid, Ipld};
+use std::{
+    borrow::Cow,
+    iter,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tracing::{instrument, Instrument};
 use wasmtime::{
-    component::{self, Component, Func, Instance, Linker},
+    component::{
+        self, Component, Func, Instance, InstancePre, Linker, ResourceAny, ResourceImportIndex,
+        ResourceType,
+    },
     Config, Engine, Store,
 };
+use wasmtime_wasi::preview2::WasiView;
 use wit_component::ComponentEncoder;
 
+pub use crate::wasmtime::blocks::Block;
+
 wasmtime::component::bindgen!({
     world: "imports",
     tracing: true,
-    async: true
+    async: true,
+    with: {
+        "homestar:host/ipld/block": Block,
+    }
 });
 
+// Index of the host [Block] resource in the [Linker], as the first resource
+// defined, see [World::define_linker].
+const BLOCK_RESOURCE_INDEX: usize = 0;
+
 // One unit of fuel represents around 100k instructions.
 const UNIT_OF_COMPUTE_INSTRUCTIONS: u64 = 100_000;
 
+// Epoch deadline, in ticks, for stores without a time limit, far enough out
+// to never be reached without overflowing the engine's current epoch.
+const NO_EPOCH_DEADLINE: u64 = u64::MAX / 2;
+
+/// Epoch deadline, in ticks from the current epoch, for a time limit.
+fn epoch_deadline(time_limit: Option<Duration>) -> u64 {
+    time_limit.map_or(NO_EPOCH_DEADLINE, |limit| {
+        limit
+            .as_nanos()
+            .div_ceil(EPOCH_TICK.as_nanos())
+            .clamp(1, NO_EPOCH_DEADLINE as u128) as u64
+    })
+}
+
 /// Incoming `state` from host runtime.
 #[allow(missing_debug_implementations)]
 pub struct State {
@@ -44,6 +85,8 @@ pub struct State {
     /// Fuel is a measure of how much computation a Wasm module is allowed to
     /// perform.
     fuel: u64,
+    /// Wall-clock time limit, after which execution is interrupted.
+    time_limit: Option<Duration>,
     /// Limits are a set of limits that can be applied to a store, i.e. memory,
     /// table elements.
     limits: StoreLimitsAsync,
@@ -51,6 +94,24 @@ pub struct State {
     wasi_ctx: wasmtime_wasi::preview2::WasiCtx,
     /// WASI table.
     table: wasmtime::component::ResourceTable,
+    /// Content-addressed blocks available through the host `ipld` interface.
+    blocks: Arc<dyn BlockStore>,
+    /// Quota on the blocks the guest may put.
+    block_quota: BlockQuota,
+    /// Blocks and bytes put by the guest.
+    block_usage: BlockUsage,
+    /// Seed randomness is derived from, with the clock fixed, for
+    /// reproducible execution.
+    seed: Option<[u8; 32]>,
+    /// WASI capabilities granted to the guest.
+    capabilities: WasiCapabilities,
+    /// Log records written by the guest.
+    logs: LogBuffer,
+    /// Sink guest log records are streamed to as they're written.
+    log_sink: Option<Arc<dyn LogSink>>,
+    /// Outgoing HTTP policy and calls made by the guest.
+    #[cfg(feature = "http")]
+    http: HttpState,
 }
 
 impl Default for State {
@@ -63,9 +124,19 @@ impl Default for State {
         Self {
             start_time: Instant::now(),
             fuel: u64::MAX,
+            time_limit: None,
             limits: StoreLimitsAsync::default(),
             wasi_ctx,
             table,
+            blocks: Arc::new(MemoryBlockStore::default()),
+            block_quota: BlockQuota::default(),
+            block_usage: BlockUsage::default(),
+            seed: None,
+            capabilities: WasiCapabilities::default(),
+            logs: LogBuffer::default(),
+            log_sink: None,
+            #[cfg(feature = "http")]
+            http: HttpState::default(),
         }
     }
 }
@@ -74,7 +145,7 @@ impl Default for State {
 /// modules.
 ///
 /// [WasiView]: wasmtime_wasi::preview2::WasiView
-impl wasmtime_wasi::preview2::WasiView for State {
+impl WasiView for State {
     fn table(&mut self) -> &mut wasmtime::component::ResourceTable {
         &mut self.table
     }
@@ -84,6 +155,31 @@ impl wasmtime_wasi::preview2::WasiView for State {
     }
 }
 
+/// [WasiHttpView] implementation for [State], checking outgoing requests
+/// against the task's [HttpPolicy].
+///
+/// [WasiHttpView]: wasmtime_wasi_http::WasiHttpView
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+impl wasmtime_wasi_http::WasiHttpView for State {
+    fn ctx(&mut self) -> &mut wasmtime_wasi_http::WasiHttpCtx {
+        self.http.ctx()
+    }
+
+    fn table(&mut self) -> &mut wasmtime::component::ResourceTable {
+        &mut self.table
+    }
+
+    fn send_request(
+        &mut self,
+        request: wasmtime_wasi_http::types::OutgoingRequest,
+    ) -> wasmtime::Result<
+        wasmtime::component::Resource<wasmtime_wasi_http::types::HostFutureIncomingResponse>,
+    > {
+        self.http.send_request(&mut self.table, request)
+    }
+}
+
 impl State {
     /// Create a new [State] object.
     pub fn new(fuel: u64, limits: StoreLimitsAsync) -> Self {
@@ -94,9 +190,19 @@ impl State {
         Self {
             start_time: Instant::now(),
             fuel,
+            time_limit: None,
             limits,
             wasi_ctx,
             table,
+            blocks: Arc::new(MemoryBlockStore::default()),
+            block_quota: BlockQuota::default(),
+            block_usage: BlockUsage::default(),
+            seed: None,
+            capabilities: WasiCapabilities::default(),
+            logs: LogBuffer::default(),
+            log_sink: None,
+            #[cfg(feature = "http")]
+            http: HttpState::default(),
         }
     }
 
@@ -105,6 +211,137 @@ impl State {
         self.fuel = fuel
     }
 
+    /// Set the wall-clock time limit, after which execution is interrupted.
+    pub fn set_time_limit(&mut self, time_limit: Option<Duration>) {
+        self.time_limit = time_limit
+    }
+
+    /// Wall-clock time limit, after which execution is interrupted.
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit
+    }
+
+    /// Run deterministically: clocks, including `get-current-time`, are
+    /// fixed at their epoch, and WASI random sources are derived from
+    /// `seed`.
+    pub fn set_deterministic(&mut self, seed: [u8; 32]) {
+        self.seed = Some(seed);
+    }
+
+    /// Whether the clock is fixed and randomness seeded.
+    pub fn is_deterministic(&self) -> bool {
+        self.seed.is_some()
+    }
+
+    /// Set the [WasiCapabilities] granted to the guest.
+    pub fn set_wasi_capabilities(&mut self, capabilities: WasiCapabilities) {
+        self.capabilities = capabilities
+    }
+
+    /// [WasiCapabilities] granted to the guest.
+    pub fn wasi_capabilities(&self) -> &WasiCapabilities {
+        &self.capabilities
+    }
+
+    /// Rebuild the WASI context if the guest runs deterministically or is
+    /// granted capabilities, opening preopened directories on the host.
+    ///
+    /// Stdin is not inherited by rebuilt contexts.
+    fn build_wasi_ctx(&mut self) -> Result<(), Error> {
+        if self.seed.is_none() && self.capabilities.is_empty() {
+            return Ok(());
+        }
+
+        let mut builder = wasmtime_wasi::preview2::WasiCtxBuilder::new();
+        builder.inherit_stdout().inherit_stderr();
+        if let Some(seed) = self.seed {
+            deterministic::configure(&mut builder, seed);
+        }
+        self.capabilities.configure(&mut builder)?;
+        self.wasi_ctx = builder.build();
+        Ok(())
+    }
+
+    /// Set the [BlockStore] backing the host `ipld` interface.
+    pub fn set_blocks(&mut self, blocks: Arc<dyn BlockStore>) {
+        self.blocks = blocks
+    }
+
+    /// [BlockStore] backing the host `ipld` interface.
+    pub fn blocks(&self) -> Arc<dyn BlockStore> {
+        self.blocks.clone()
+    }
+
+    /// Set the [BlockQuota] on the blocks the guest may put.
+    pub fn set_block_quota(&mut self, quota: BlockQuota) {
+        self.block_quota = quota
+    }
+
+    /// Reserve a block of `len` bytes against the guest's [BlockQuota].
+    pub(crate) fn reserve_block(&mut self, len: usize) -> Result<(), String> {
+        self.block_usage.reserve(len, &self.block_quota)
+    }
+
+    /// Set the cap, in bytes of messages, on buffered guest log records.
+    pub fn set_log_capacity(&mut self, capacity: usize) {
+        self.logs = LogBuffer::new(capacity)
+    }
+
+    /// Set the [LogSink] guest log records are streamed to.
+    pub fn set_log_sink(&mut self, sink: Arc<dyn LogSink>) {
+        self.log_sink = Some(sink)
+    }
+
+    /// Log records written by the guest.
+    pub fn logs(&self) -> &LogBuffer {
+        &self.logs
+    }
+
+    /// Buffer a guest log record and stream it to the [LogSink], if any,
+    /// unless dropped for exceeding the buffer's cap.
+    pub(crate) fn log(&mut self, record: LogRecord) {
+        match &self.log_sink {
+            Some(sink) => {
+                if self.logs.push(record.clone()) {
+                    sink.log(&record);
+                }
+            }
+            None => {
+                self.logs.push(record);
+            }
+        }
+    }
+
+    /// Stream a notice of the log records dropped for exceeding the
+    /// buffer's cap to the [LogSink], if any were dropped.
+    pub fn finish_logs(&self) {
+        if let (Some(sink), Some(notice)) = (&self.log_sink, self.logs.dropped_notice()) {
+            sink.log(&notice);
+        }
+    }
+
+    /// Set the [HttpPolicy] outgoing HTTP requests are checked against.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn set_http_policy(&mut self, policy: HttpPolicy) {
+        self.http.set_policy(policy)
+    }
+
+    /// Outgoing HTTP requests made by the guest, with their response hashes.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn http_calls(&self) -> Vec<HttpCall> {
+        self.http.calls()
+    }
+
+    /// Number of outgoing HTTP requests admitted for the guest, whether or
+    /// not they completed.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn http_requests(&self) -> u32 {
+        self.http.requests()
+    }
+
     /// Initial time from instantiation.
     pub fn start_time(&self) -> Instant {
         self.start_time
@@ -118,27 +355,48 @@ pub struct Env<T> {
     bindings: Option<World>,
     engine: Engine,
     instance: Option<Instance>,
-    linker: Linker<T>,
+    instance_pre: Option<InstancePre<T>>,
+    linker: Arc<Linker<T>>,
     store: Store<T>,
+    time_limit: Option<Duration>,
+    call_started: Option<Instant>,
 }
 
 impl<T> Env<T> {
-    fn new(engine: Engine, linker: Linker<T>, store: Store<T>) -> Env<T> {
+    pub(crate) fn new(
+        engine: Engine,
+        linker: Arc<Linker<T>>,
+        store: Store<T>,
+        time_limit: Option<Duration>,
+    ) -> Env<T> {
         Self {
             bindings: None,
             engine,
             instance: None,
+            instance_pre: None,
             linker,
             store,
+            time_limit,
+            call_started: None,
         }
     }
 
+    /// Wall-clock time spent in the most recent call into the guest, so far
+    /// if still running or cut short, excluding compilation, instantiation
+    /// and argument conversion.
+    pub fn call_duration(&self) -> Duration {
+        self.call_started
+            .map(|started| started.elapsed())
+            .unwrap_or_default()
+    }
+
     fn set_bindings(&mut self, bindings: World) {
         self.bindings = Some(bindings);
     }
 
-    fn set_instance(&mut self, instance: Instance) {
+    fn set_instance(&mut self, instance: Instance, instance_pre: InstancePre<T>) {
         self.instance = Some(instance);
+        self.instance_pre = Some(instance_pre);
     }
 
     /// Execute Wasm function dynamically given a list ([Args]) of Ipld or
@@ -149,8 +407,9 @@ impl<T> Env<T> {
     #[instrument(skip_all)]
     pub async fn execute(&mut self, args: Args<Arg>) -> Result<Output, Error>
     where
-        T: Send,
+        T: WasiView + Send,
     {
+        self.call_started = None;
         let param_types = self
             .bindings
             .as_mut()
@@ -164,16 +423,22 @@ impl<T> Env<T> {
             .func()
             .results(&self.store);
 
+        let instance_pre = self.instance_pre.as_ref().ok_or(Error::WasmInstantiation)?;
+        let mut resources = StoreResources::new(&mut self.store, instance_pre);
         let params: Vec<component::Val> = iter::zip(param_types.iter(), args.into_inner())
             .try_fold(vec![], |mut acc, (typ, arg)| {
                 let v = match arg {
                     Input::Ipld(ipld) => {
-                        RuntimeVal::try_from(ipld, &InterfaceType::from(typ))?.value()
+                        RuntimeVal::try_from_with(ipld, &InterfaceType::from(typ), &mut resources)?
+                            .value()
                     }
                     Input::Arg(val) => match val.into_inner() {
-                        Arg::Ipld(ipld) => {
-                            RuntimeVal::try_from(ipld, &InterfaceType::from(typ))?.value()
-                        }
+                        Arg::Ipld(ipld) => RuntimeVal::try_from_with(
+                            ipld,
+                            &InterfaceType::from(typ),
+                            &mut resources,
+                        )?
+                        .value(),
                         Arg::Value(v) => v,
                     },
                     Input::Deferred(await_promise) => {
@@ -187,12 +452,19 @@ impl<T> Env<T> {
                 acc.push(v);
                 Ok::<_, Error>(acc)
             })?;
+        let lent = resources.lent;
 
         let mut results_alloc: Vec<component::Val> = result_types
             .iter()
             .map(|_res| component::Val::Bool(false))
             .collect();
 
+        // The time limit only covers the call itself, not resolving args or
+        // compiling and instantiating the component beforehand.
+        self.store
+            .set_epoch_deadline(epoch_deadline(self.time_limit));
+        self.call_started = Some(Instant::now());
+
         self.bindings
             .as_mut()
             .ok_or(Error::WasmInstantiation)?
@@ -209,6 +481,27 @@ impl<T> Env<T> {
             .in_current_span()
             .await?;
 
+        // Handles lent for the call are released once it returns.
+        for handle in lent {
+            handle.resource_drop_async(&mut self.store).await?;
+        }
+
+        // Resource handles are only valid within the store, so results
+        // holding them are converted to Ipld while it's still around.
+        if result_types.iter().any(contains_resource) {
+            let instance_pre = self.instance_pre.as_ref().ok_or(Error::WasmInstantiation)?;
+            let mut resources = StoreResources::new(&mut self.store, instance_pre);
+            let mut ipld = results_alloc
+                .into_iter()
+                .map(|v| RuntimeVal::new(v).try_into_ipld_with(&mut resources))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            return Ok(match ipld.len() {
+                1 => Output::Ipld(ipld.remove(0)),
+                _ => Output::Ipld(Ipld::List(ipld)),
+            });
+        }
+
         let results = match &results_alloc[..] {
             [v] => Output::Value(v.to_owned()),
             [_v, ..] => Output::Values(results_alloc),
@@ -237,6 +530,82 @@ impl<T> Env<T> {
     pub fn store(&self) -> &Store<T> {
         &self.store
     }
+
+    /// Instantiate a compiled [Component] into the store, returning the
+    /// parameter and result types of each of the given root-level exported
+    /// functions, if found.
+    ///
+    /// Types are only resolved against an instance, as resource types are
+    /// tied to the instance, or host, defining them.
+    pub(crate) async fn func_types(
+        &mut self,
+        component: &Component,
+        names: &[String],
+    ) -> Result<Vec<Option<(Vec<component::Type>, Vec<component::Type>)>>, Error>
+    where
+        T: Send,
+    {
+        let instance = self
+            .linker
+            .instantiate_async(&mut self.store, component)
+            .await?;
+
+        Ok(names
+            .iter()
+            .map(|name| {
+                instance.get_func(&mut self.store, name).map(|func| {
+                    (
+                        func.params(&self.store).into_vec(),
+                        func.results(&self.store).into_vec(),
+                    )
+                })
+            })
+            .collect())
+    }
+}
+
+impl Env<State> {
+    /// Return the amount of fuel consumed by the [Store] since it was
+    /// last fueled.
+    pub fn fuel_consumed(&self) -> u64 {
+        self.store
+            .data()
+            .fuel
+            .saturating_sub(self.store.get_fuel().unwrap_or_default())
+    }
+
+    /// Return the peak amount of memory, in bytes, used by the instance.
+    ///
+    /// Linear memories only ever grow, so the memory consumed so far is
+    /// also its peak.
+    pub fn memory_consumed(&self) -> u64 {
+        self.store.data().limits.memory_consumed()
+    }
+
+    /// Return the log records written by the guest so far.
+    pub fn logs(&self) -> &LogBuffer {
+        self.store.data().logs()
+    }
+
+    /// Stream a notice of the guest's dropped log records, once done
+    /// running.
+    pub fn finish_logs(&self) {
+        self.store.data().finish_logs()
+    }
+
+    /// Return the outgoing HTTP requests made by the guest so far.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn http_calls(&self) -> Vec<HttpCall> {
+        self.store.data().http_calls()
+    }
+
+    /// Return the number of outgoing HTTP requests admitted so far.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn http_requests(&self) -> u32 {
+        self.store.data().http_requests()
+    }
 }
 
 /// Shim for Wasmtime [Function] execution.
@@ -247,29 +616,12 @@ pub struct World(Func);
 
 impl World {
     /// Instantiate a default [environment] given a configuration
-    /// for a [World], given [State].
+    /// for a [World], given [State], sharing the process-wide default
+    /// [Runtime]'s engine and linker.
     ///
     /// [environment]: Env
     pub fn default(data: State) -> Result<Env<State>, Error> {
-        let config = Self::configure();
-        let engine = Engine::new(&config)?;
-        let mut linker = Self::define_linker(&engine);
-
-        // Add WASI to the linker in order to support WASI modules.
-        // This is a temporary measure until WASI is supported by default and is
-        // unused otherwise.
-        wasmtime_wasi::preview2::command::add_to_linker(&mut linker)?;
-        Imports::add_to_linker(&mut linker, |state: &mut State| state)?;
-
-        let mut store = Store::new(&engine, data);
-        store.set_fuel(store.data().fuel)?;
-
-        // Configures a `Store` to yield execution of async WebAssembly code
-        // periodically and not cause extended polling.
-        store.fuel_async_yield_interval(Some(UNIT_OF_COMPUTE_INSTRUCTIONS))?;
-
-        let env = Env::new(engine, linker, store);
-        Ok(env)
+        Runtime::global()?.env(data)
     }
 
     /// Instantiates the provided `module` using the specified
@@ -277,42 +629,18 @@ impl World {
     /// that translates between wasm and the host, and gives access
     /// for future invocations to use the already-initialized linker, store.
     ///
-    /// Used when first initiating a module of a workflow.
+    /// Used when first initiating a module of a workflow. The module is
+    /// compiled once per process, through the default [Runtime]'s cache.
     pub async fn instantiate(
         bytes: Vec<u8>,
         fun_name: &str,
         data: State,
     ) -> Result<Env<State>, Error> {
-        let config = Self::configure();
-        let engine = Engine::new(&config)?;
-        let mut linker = Self::define_linker(&engine);
-
-        // Add WASI to the linker in order to support WASI modules.
-        // This is a temporary measure until WASI is supported by default and is
-        // unused otherwise.
-        wasmtime_wasi::preview2::command::add_to_linker(&mut linker)?;
-        Imports::add_to_linker(&mut linker, |state: &mut State| state)?;
-
-        let mut store = Store::new(&engine, data);
-        store.limiter_async(|s| &mut s.limits);
-        store.set_fuel(store.data().fuel)?;
-
-        // Configures a `Store` to yield execution of async WebAssembly code
-        // periodically and not cause extended polling.
-        store.fuel_async_yield_interval(Some(UNIT_OF_COMPUTE_INSTRUCTIONS))?;
-
-        // engine clones are shallow (not deep).
-        let component = component_from_bytes(&bytes, engine.clone())?;
-
-        let (_bindings, instance) =
-            Imports::instantiate_async(&mut store, &component, &linker).await?;
-
-        let bindings = Self::new(&mut store, &instance, fun_name)?;
-
-        //let bindings = Self::new(&mut store, &instance, fun_name)?;
-        let mut env = Env::new(engine, linker, store);
-        env.set_instance(instance);
-        env.set_bindings(bindings);
+        let runtime = Runtime::global()?;
+        let mut env = runtime.env(data)?;
+        runtime
+            .instantiate(Runtime::content_cid(&bytes), &bytes, fun_name, &mut env)
+            .await?;
         Ok(env)
     }
 
@@ -332,12 +660,26 @@ impl World {
     {
         // engine clones are shallow (not deep).
         let component = component_from_bytes(&bytes, env.engine.clone())?;
+        Self::instantiate_component(&component, fun_name, env).await
+    }
 
-        let (_bindings, instance) =
-            Imports::instantiate_async(&mut env.store, &component, &env.linker).await?;
+    /// Instantiate a compiled [Component] into the [environment]'s store,
+    /// overriding its instance and bindings.
+    ///
+    /// [environment]: Env
+    pub(crate) async fn instantiate_component<'a, T>(
+        component: &Component,
+        fun_name: &'a str,
+        env: &'a mut Env<T>,
+    ) -> Result<&'a mut Env<T>, Error>
+    where
+        T: Send,
+    {
+        let instance_pre = env.linker.instantiate_pre(component)?;
+        let instance = instance_pre.instantiate_async(&mut env.store).await?;
 
         let bindings = Self::new(&mut env.store, &instance, fun_name)?;
-        env.set_instance(instance);
+        env.set_instance(instance, instance_pre);
         env.set_bindings(bindings);
         Ok(env)
     }
@@ -346,7 +688,7 @@ impl World {
         self.0
     }
 
-    fn configure() -> Config {
+    pub(crate) fn configure() -> Config {
         let mut config = Config::new();
         config.strategy(wasmtime::Strategy::Cranelift);
         config.wasm_component_model(true);
@@ -361,11 +703,47 @@ impl World {
         // for Ops, instead of parsing each Op.
         config.consume_fuel(true);
 
+        // Interrupt execution past a store's time limit, checked against the
+        // epoch advanced by the [Runtime]'s ticker.
+        config.epoch_interruption(true);
+
         config
     }
 
-    fn define_linker<U>(engine: &Engine) -> Linker<U> {
-        Linker::<U>::new(engine)
+    /// Define a [Linker] with WASI and the host's [Imports] added.
+    pub(crate) fn define_linker(engine: &Engine) -> Result<Linker<State>, Error> {
+        let mut linker = Linker::<State>::new(engine);
+
+        // Host imports go first, so the [Block] resource is defined at
+        // [BLOCK_RESOURCE_INDEX].
+        Imports::add_to_linker(&mut linker, |state: &mut State| state)?;
+
+        // Add WASI to the linker in order to support WASI modules.
+        // This is a temporary measure until WASI is supported by default and is
+        // unused otherwise.
+        wasmtime_wasi::preview2::command::add_to_linker(&mut linker)?;
+        #[cfg(feature = "http")]
+        wasmtime_wasi_http::proxy::add_only_http_to_linker(&mut linker)?;
+        Ok(linker)
+    }
+
+    /// Create a [Store] for [State], with its limits and fuel set.
+    ///
+    /// The epoch deadline is only set for the time limit when the function
+    /// is called, see [Env::execute].
+    pub(crate) fn define_store(engine: &Engine, mut data: State) -> Result<Store<State>, Error> {
+        data.build_wasi_ctx()?;
+        let mut store = Store::new(engine, data);
+        store.limiter_async(|s| &mut s.limits);
+        store.set_fuel(store.data().fuel)?;
+
+        store.set_epoch_deadline(NO_EPOCH_DEADLINE);
+        store.epoch_deadline_trap();
+
+        // Configures a `Store` to yield execution of async WebAssembly code
+        // periodically and not cause extended polling.
+        store.fuel_async_yield_interval(Some(UNIT_OF_COMPUTE_INSTRUCTIONS))?;
+        Ok(store)
     }
 
     /// Low-level creation wrapper for wrapping up the exports
@@ -400,8 +778,85 @@ impl World {
     }
 }
 
+/// [CidResources] backed by a [Store]'s resource table, lifting [Cid]s into
+/// handles to host [Block]s and lowering them back.
+struct StoreResources<'a, T> {
+    store: &'a mut Store<T>,
+    /// Pre-instantiation of the component, resolving the host's resources.
+    instance_pre: &'a InstancePre<T>,
+    /// Handles lent to the guest for the duration of a call.
+    lent: Vec<ResourceAny>,
+}
+
+impl<'a, T> StoreResources<'a, T> {
+    fn new(store: &'a mut Store<T>, instance_pre: &'a InstancePre<T>) -> Self {
+        Self {
+            store,
+            instance_pre,
+            lent: Vec::new(),
+        }
+    }
+}
+
+impl<T: WasiView> CidResources for StoreResources<'_, T> {
+    fn lift(
+        &mut self,
+        cid: Cid,
+        ty: &ResourceType,
+        borrow: bool,
+    ) -> Result<component::Val, InterpreterError> {
+        if *ty != ResourceType::host::<Block>() {
+            return Err(InterpreterError::ResourceHandle(format!(
+                "only block resources can be backed by {cid}"
+            )));
+        }
+
+        let block = self
+            .store
+            .data_mut()
+            .table()
+            .push(Block::new(cid))
+            .map_err(|err| InterpreterError::ResourceHandle(err.to_string()))?;
+        let handle = ResourceAny::try_from_resource(
+            block,
+            &mut *self.store,
+            self.instance_pre,
+            ResourceImportIndex::from(BLOCK_RESOURCE_INDEX),
+        )?;
+        if borrow {
+            self.lent.push(handle);
+        }
+
+        Ok(component::Val::Resource(handle))
+    }
+
+    fn lower(&mut self, handle: ResourceAny) -> Result<Cid, InterpreterError> {
+        let block = handle
+            .try_into_resource::<Block>(&mut *self.store)
+            .map_err(|_| {
+                InterpreterError::ResourceHandle(
+                    "only block resources can be returned from guests".to_string(),
+                )
+            })?;
+
+        self.store
+            .data_mut()
+            .table()
+            .delete(block)
+            .map(|block| block.cid())
+            .map_err(|err| InterpreterError::ResourceHandle(err.to_string()))
+    }
+}
+
 /// Turn bytes into a Wasm [Component] module.
 fn component_from_bytes(bytes: &[u8], engine: Engine) -> Result<Component, Error> {
+    let binary = component_binary(bytes)?;
+    Component::from_binary(&engine, &binary).map_err(Error::IntoWasmComponent)
+}
+
+/// Turn bytes, i.e. a Wasm component, a core Wasm module or WAT referencing
+/// a component, into a Wasm component binary.
+pub(crate) fn component_binary(bytes: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
     fn is_component(chunk: wasmparser::Chunk<'_>) -> bool {
         matches!(
             chunk,
@@ -417,7 +872,7 @@ fn component_from_bytes(bytes: &[u8], engine: Engine) -> Result<Component, Error
     match wasmparser::Parser::new(0).parse(bytes, true) {
         Ok(chunk) => {
             if is_component(chunk) {
-                Component::from_binary(&engine, bytes).map_err(Error::IntoWasmComponent)
+                Ok(Cow::Borrowed(bytes))
             } else {
                 tracing::info!("converting Wasm binary into a Wasm component");
 
@@ -425,13 +880,13 @@ fn component_from_bytes(bytes: &[u8], engine: Engine) -> Result<Component, Error
                     .module(bytes)?
                     .validate(true)
                     .encode()?;
-                Component::from_binary(&engine, &component).map_err(Error::IntoWasmComponent)
+                Ok(Cow::Owned(component))
             }
         }
         Err(_) => {
             let wasm_bytes = wat::parse_bytes(bytes)?;
             if is_component(wasmparser::Parser::new(0).parse(&wasm_bytes, true)?) {
-                Component::from_binary(&engine, &wasm_bytes).map_err(Error::IntoWasmComponent)
+                Ok(Cow::Owned(wasm_bytes.into()))
             } else {
                 Err(Error::WatComponent(
                     "WAT must reference a Wasm component.".to_string(),
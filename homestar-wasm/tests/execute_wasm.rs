@@ -8,7 +8,11 @@ use homestar_invocation::{
 };
 use homestar_wasm::{
     io::{Arg, Output},
-    wasmtime::{limits::StoreLimitsAsync, Error, State, World},
+    wasmtime::{
+        limits::StoreLimitsAsync,
+        logs::{LogLevel, LogRecord},
+        Error, State, World,
+    },
 };
 use libipld::{
     cid::{
@@ -168,6 +172,31 @@ async fn test_append_string() {
     );
 }
 
+#[tokio::test]
+async fn test_guest_logs_captured() {
+    let ipld = Input::Ipld(Ipld::Map(BTreeMap::from([
+        ("func".into(), Ipld::String("append-string".to_string())),
+        (
+            "args".into(),
+            Ipld::List(vec![Ipld::String("Natural Science".to_string())]),
+        ),
+    ])));
+
+    let wasm = fs::read(fixtures("example_test.wasm")).unwrap();
+    let mut env = World::instantiate(wasm, "append-string", State::default())
+        .await
+        .unwrap();
+
+    env.execute(ipld.parse().unwrap().into()).await.unwrap();
+
+    assert!(env.logs().records().contains(&LogRecord {
+        level: LogLevel::Info,
+        context: "run-fn".to_string(),
+        message: "append-string".to_string(),
+    }));
+    assert_eq!(env.logs().dropped(), 0);
+}
+
 #[tokio::test]
 async fn test_crop_base64_wasi() {
     let img_uri = r#"data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAABQAAAAUCAYAAACNiR0NAAAACXBIWXMAAAsTAAALEwEAmpwYAAAAAXNSR0IArs4c6QAAAARnQU1BAACxjwv8YQUAAAG9SURBVHgBrVRLTgJBEK3qaYhLtiZGxxMoN8CdISTiCYSEmLgSTkA8AXFFAgv0BkNMDDu4gXAC2vhhy9IIU2X1CGb4T5CXzCRd3f26Xv0QBOna+ykiVJjBha2A3XhMlbz8vsFsdeCOtP/CAAn4H4bAcKbGMarsgMwiYVUqIj6FHYERXBU2oHV7BYI95HsH6VKk5eUzkw0TPqfDCwK400iGWDXmw+BrJ9mSoE/X59VBZ2/vazjy4xIyzk3tat6Tp8Kh54+d5J8HgRZuhsksWjf7xssfD5npNaxsXvLV9PDz9cGxlSaB7sopA0uQbfQlEeoorAalBvvC5E4IO1KLj0L2ABGQqb+lCLAd8sgsSI5KFtxHXii3GUJxPZWuf5QhIgici7WEwavAKSsFNsB2mCQru5HQFqfW2sAGSLveLuuwBULR7X77fluSlYMVyNQ+LVlx2Z6ec8+TXzOunY5XmK07C1smo3GsTEDFFW/Nls2vBYwtH/G0R9I1gYlUAh04kSzk1g4SuasXjCJZLuWCfVbTg8AEkaAQl3fBViDuKemM0ropExWWg2K6iHYhk8NVMmhF2FazUUiMhKQkXdb9AfsesrssluqmAAAAAElFTkSuQmCC"#;
@@ -642,3 +671,56 @@ async fn test_subtract_float_to_int() {
     let res = env.execute(ipld.parse().unwrap().into()).await.unwrap();
     assert_eq!(res, Output::Value(wasmtime::component::Val::S8(-1)));
 }
+
+#[tokio::test]
+async fn test_multiple_results_as_list() {
+    let pair = br#"
+        (component
+          (core module $m
+            (memory (export "mem") 1)
+            (func (export "pair") (result i32)
+              (i32.store (i32.const 0) (i32.const 1))
+              (i32.store (i32.const 4) (i32.const 2))
+              (i32.const 0)))
+          (core instance $i (instantiate $m))
+          (alias core export $i "mem" (core memory $mem))
+          (func (export "pair") (result "a" s32) (result "b" s32)
+            (canon lift (core func $i "pair") (memory $mem))))
+    "#;
+
+    let mut env = World::instantiate(pair.to_vec(), "pair", State::default())
+        .await
+        .unwrap();
+    let res = env.execute(Args::new(vec![])).await.unwrap();
+
+    assert_eq!(
+        Ipld::try_from(res).unwrap(),
+        Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2)])
+    );
+}
+
+#[tokio::test]
+async fn test_block_resources_as_links() {
+    let identity = br#"
+        (component
+          (import "homestar:host/ipld@0.0.1" (instance $ipld
+            (export "block" (type (sub resource)))))
+          (alias export $ipld "block" (type $block))
+          (core module $m
+            (func (export "identity") (param i32) (result i32) local.get 0))
+          (core instance $i (instantiate $m))
+          (func (export "identity") (param "b" (own $block)) (result (own $block))
+            (canon lift (core func $i "identity"))))
+    "#;
+
+    let mut env = World::instantiate(identity.to_vec(), "identity", State::default())
+        .await
+        .unwrap();
+
+    let cid = Cid::new_v1(0x55, Code::Blake3_256.digest(b"block"));
+    let res = env
+        .execute(Args::new(vec![Input::Ipld(Ipld::Link(cid))]))
+        .await
+        .unwrap();
+    assert_eq!(res, Output::Ipld(Ipld::Link(cid)));
+}
@@ -30,4 +30,4 @@ mod linkmap;
 pub mod workflow;
 
 pub use linkmap::LinkMap;
-pub use workflow::Workflow;
+pub use workflow::{Workflow, WorkflowBuilder};
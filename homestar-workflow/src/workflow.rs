@@ -13,6 +13,9 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+mod builder;
+pub use builder::{Args, BuildError, Fun, Output, Param, Step, TaskBuilder, WorkflowBuilder};
+
 const TASKS_KEY: &str = "tasks";
 
 /// Workflow composed of [tasks].
@@ -0,0 +1,852 @@
+//! Builder for authoring a [Workflow] in Rust, wiring the [Await]s between
+//! its tasks from typed [Step] handles on their outputs.
+//!
+//! Tasks calling a [Fun] declared with its argument and output types are
+//! type-checked: a task's [Step] output can only be passed where its type is
+//! expected.
+//!
+//! ```
+//! use homestar_invocation::Unit;
+//! use homestar_workflow::{workflow::Fun, WorkflowBuilder};
+//! use libipld::Cid;
+//!
+//! let module =
+//!     Cid::try_from("bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q").unwrap();
+//! let add_two = Fun::<(i32,), i32>::new("add_two");
+//! let double = Fun::<(i32,), i32>::new("double");
+//!
+//! let mut builder = WorkflowBuilder::<Unit>::new();
+//! let add = builder.task("add").module(module).call(&add_two, (1,)).add();
+//! builder
+//!     .task("double")
+//!     .module(module)
+//!     .call(&double, (add.ok(),))
+//!     .add();
+//!
+//! let workflow = builder.build().unwrap();
+//! assert_eq!(workflow.len(), 2);
+//! ```
+//!
+//! Passing an output of the wrong type doesn't compile:
+//!
+//! ```compile_fail
+//! # use homestar_invocation::Unit;
+//! # use homestar_workflow::{workflow::Fun, WorkflowBuilder};
+//! # use libipld::Cid;
+//! # let module =
+//! #     Cid::try_from("bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q").unwrap();
+//! let add_two = Fun::<(i32,), i32>::new("add_two");
+//! let shout = Fun::<(String,), String>::new("shout");
+//!
+//! let mut builder = WorkflowBuilder::<Unit>::new();
+//! let add = builder.task("add").module(module).call(&add_two, (1,)).add();
+//! builder.task("shout").module(module).call(&shout, (add.ok(),)).add();
+//! ```
+
+use super::Workflow;
+use homestar_invocation::{
+    authority::UcanPrf,
+    error::Error,
+    ipld::{DagCbor, Link},
+    pointer::{Await, AwaitResult},
+    task::{
+        instruction::{Ability, Input, Nonce, RunInstruction},
+        Instruction, Resources,
+    },
+    Pointer, Task, Unit,
+};
+use libipld::{Cid, Ipld};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use url::Url;
+
+const FUNC_KEY: &str = "func";
+const ARGS_KEY: &str = "args";
+const WASM_RUN: &str = "wasm/run";
+
+/// Source of ids distinguishing [WorkflowBuilder]s, so a [Step] handle can
+/// only be awaited within the builder it came from.
+static NEXT_BUILDER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Error building a [Workflow] with a [WorkflowBuilder].
+#[derive(thiserror::Error, Debug)]
+pub enum BuildError {
+    /// Workflow has no tasks.
+    #[error("workflow has no tasks")]
+    Empty,
+    /// Two tasks share the same name.
+    #[error("workflow contains more than one task named {0}")]
+    DuplicateName(String),
+    /// Task has no resource set.
+    #[error("task {0} has no resource")]
+    MissingResource(String),
+    /// Task's resource is not a valid URL.
+    #[error("task {0} has an invalid resource: {1}")]
+    InvalidResource(String, #[source] url::ParseError),
+    /// Task has no function set.
+    #[error("task {0} has no function")]
+    MissingFunction(String),
+    /// Task awaits the output of a step that is not an earlier task of the
+    /// same builder.
+    #[error("task {0} awaits a step that is not an earlier task of this workflow")]
+    UnknownStep(String),
+    /// Task is identical to an earlier one.
+    #[error("task {0} duplicates an earlier task: use a nonce to ensure uniqueness")]
+    DuplicateTask(String),
+    /// Task's instruction could not be encoded to a Cid.
+    #[error(transparent)]
+    Encode(#[from] Error<Unit>),
+}
+
+/// Position of a task within the [WorkflowBuilder] it was added to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct StepId {
+    builder: u64,
+    index: usize,
+}
+
+/// Function called by tasks, declared with the types of its arguments `A`,
+/// as a tuple, and of its output `O`.
+///
+/// Tasks calling it through [TaskBuilder::call] only take arguments, values
+/// or awaited outputs, of the declared types, and return a [Step] handle on
+/// an output of type `O`.
+pub struct Fun<A, O> {
+    name: String,
+    _signature: PhantomData<fn(A) -> O>,
+}
+
+impl<A, O> Fun<A, O> {
+    /// Declare a function, given its name.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            _signature: PhantomData,
+        }
+    }
+
+    /// Name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<A, O> Clone for Fun<A, O> {
+    fn clone(&self) -> Self {
+        Self::new(self.name.clone())
+    }
+}
+
+impl<A, O> fmt::Debug for Fun<A, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fun").field("name", &self.name).finish()
+    }
+}
+
+/// Handle on a task added to a [WorkflowBuilder], whose output of type `O`
+/// can be awaited by later tasks.
+///
+/// Tasks whose function wasn't declared with a [Fun] have [Ipld] outputs.
+pub struct Step<O = Ipld> {
+    id: StepId,
+    _output: PhantomData<fn() -> O>,
+}
+
+impl<O> Step<O> {
+    fn new(id: StepId) -> Self {
+        Self {
+            id,
+            _output: PhantomData,
+        }
+    }
+
+    /// Await the task's successful result.
+    pub fn ok(self) -> Output<O> {
+        Output::new(self.id, AwaitResult::Ok)
+    }
+
+    /// Await the task's error.
+    pub fn error(self) -> Output {
+        Output::new(self.id, AwaitResult::Error)
+    }
+
+    /// Await the task's result, whether it succeeded or failed.
+    pub fn ptr(self) -> Output {
+        Output::new(self.id, AwaitResult::Ptr)
+    }
+}
+
+impl<O> Clone for Step<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O> Copy for Step<O> {}
+
+impl<O> PartialEq for Step<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<O> Eq for Step<O> {}
+
+impl<O> Hash for Step<O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<O> fmt::Debug for Step<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Step")
+            .field("builder", &self.id.builder)
+            .field("index", &self.id.index)
+            .finish()
+    }
+}
+
+/// Awaited output of type `O` of a [Step], which becomes an [Await] on the
+/// step's instruction when the [Workflow] is built.
+pub struct Output<O = Ipld> {
+    id: StepId,
+    result: AwaitResult,
+    _output: PhantomData<fn() -> O>,
+}
+
+impl<O> Output<O> {
+    fn new(id: StepId, result: AwaitResult) -> Self {
+        Self {
+            id,
+            result,
+            _output: PhantomData,
+        }
+    }
+
+    /// Branch of the step's output that is awaited.
+    pub fn result(&self) -> &AwaitResult {
+        &self.result
+    }
+
+    /// Pass the output as an [Ipld] argument, e.g. to a task whose function
+    /// wasn't declared with a [Fun].
+    pub fn untyped(self) -> Output {
+        Output::new(self.id, self.result)
+    }
+}
+
+impl<O> Clone for Output<O> {
+    fn clone(&self) -> Self {
+        Output::new(self.id, self.result.clone())
+    }
+}
+
+impl<O> PartialEq for Output<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.result == other.result
+    }
+}
+
+impl<O> fmt::Debug for Output<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Output")
+            .field("step", &Step::<O>::new(self.id))
+            .field("result", &self.result)
+            .finish()
+    }
+}
+
+/// Argument of type `I` passed to a task's function: either a value, or the
+/// awaited [Output] of an earlier task.
+pub struct Param<I = Ipld> {
+    arg: Arg,
+    _input: PhantomData<fn() -> I>,
+}
+
+/// Untyped argument collected for a task.
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    Value(Ipld),
+    Output(StepId, AwaitResult),
+}
+
+impl<I> Param<I> {
+    fn new(arg: Arg) -> Self {
+        Self {
+            arg,
+            _input: PhantomData,
+        }
+    }
+}
+
+impl<I> Clone for Param<I> {
+    fn clone(&self) -> Self {
+        Param::new(self.arg.clone())
+    }
+}
+
+impl<I> PartialEq for Param<I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.arg == other.arg
+    }
+}
+
+impl<I> fmt::Debug for Param<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.arg.fmt(f)
+    }
+}
+
+impl<I> From<Output<I>> for Param<I> {
+    fn from(output: Output<I>) -> Self {
+        Param::new(Arg::Output(output.id, output.result))
+    }
+}
+
+impl<I> From<Step<I>> for Param<I> {
+    fn from(step: Step<I>) -> Self {
+        step.ok().into()
+    }
+}
+
+impl From<Ipld> for Param {
+    fn from(ipld: Ipld) -> Self {
+        Param::new(Arg::Value(ipld))
+    }
+}
+
+macro_rules! impl_param_from_value {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Param<$ty> {
+                fn from(value: $ty) -> Self {
+                    Param::new(Arg::Value(value.into()))
+                }
+            }
+
+            impl From<$ty> for Param {
+                fn from(value: $ty) -> Self {
+                    Param::new(Arg::Value(value.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_param_from_value!(
+    bool,
+    i32,
+    i64,
+    u32,
+    u64,
+    f32,
+    f64,
+    String,
+    &str,
+    Vec<u8>,
+    Cid
+);
+
+/// Arguments, as a tuple of [Param]s, matching the argument types `A`
+/// declared by a [Fun].
+pub trait Args<A> {
+    /// Collect the arguments, in order.
+    fn into_params(self) -> Vec<Param>;
+}
+
+macro_rules! impl_args_for_tuple {
+    ($(($($ty:ident $param:ident),*)),*) => {
+        $(
+            impl<$($ty, $param: Into<Param<$ty>>),*> Args<($($ty,)*)> for ($($param,)*) {
+                #[allow(non_snake_case)]
+                fn into_params(self) -> Vec<Param> {
+                    let ($($param,)*) = self;
+                    vec![$(Param::new(Into::<Param<$ty>>::into($param).arg)),*]
+                }
+            }
+        )*
+    };
+}
+
+impl Args<()> for () {
+    fn into_params(self) -> Vec<Param> {
+        vec![]
+    }
+}
+
+impl_args_for_tuple!(
+    (A1 P1),
+    (A1 P1, A2 P2),
+    (A1 P1, A2 P2, A3 P3),
+    (A1 P1, A2 P2, A3 P3, A4 P4),
+    (A1 P1, A2 P2, A3 P3, A4 P4, A5 P5),
+    (A1 P1, A2 P2, A3 P3, A4 P4, A5 P5, A6 P6),
+    (A1 P1, A2 P2, A3 P3, A4 P4, A5 P5, A6 P6, A7 P7),
+    (A1 P1, A2 P2, A3 P3, A4 P4, A5 P5, A6 P6, A7 P7, A8 P8)
+);
+
+/// Task definition collected by a [TaskBuilder].
+#[derive(Debug, Clone)]
+struct StepSpec {
+    name: String,
+    resource: Option<Result<Url, url::ParseError>>,
+    ability: Ability,
+    fun: Option<String>,
+    args: Vec<Arg>,
+    nonce: Option<Nonce>,
+    resources: Resources,
+    prf: UcanPrf,
+}
+
+/// Builder for a [Workflow], adding tasks one at a time and awaiting the
+/// outputs of earlier tasks through their [Step] handles.
+///
+/// The workflow's graph is validated by [WorkflowBuilder::build].
+#[derive(Debug)]
+pub struct WorkflowBuilder<'a, T> {
+    id: u64,
+    steps: Vec<StepSpec>,
+    _marker: PhantomData<Task<'a, T>>,
+}
+
+impl<'a, T> Default for WorkflowBuilder<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> WorkflowBuilder<'a, T> {
+    /// Create a new, empty [WorkflowBuilder].
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_BUILDER_ID.fetch_add(1, Ordering::Relaxed),
+            steps: vec![],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Start defining a task, given a name unique within the workflow.
+    ///
+    /// Tasks default to the `wasm/run` ability.
+    pub fn task<S: Into<String>>(&mut self, name: S) -> TaskBuilder<'_, 'a, T> {
+        TaskBuilder {
+            builder: self,
+            spec: StepSpec {
+                name: name.into(),
+                resource: None,
+                ability: Ability::from(WASM_RUN),
+                fun: None,
+                args: vec![],
+                nonce: None,
+                resources: Resources::default(),
+                prf: UcanPrf::default(),
+            },
+            _output: PhantomData,
+        }
+    }
+
+    /// Build the [Workflow], resolving each awaited [Output] to an [Await] on
+    /// the instruction of its task.
+    ///
+    /// Fails if any task is incomplete, named or defined more than once, or
+    /// awaits a step that isn't an earlier task of this builder.
+    pub fn build(self) -> Result<Workflow<'a, T>, BuildError>
+    where
+        T: Clone,
+        Ipld: From<T>,
+    {
+        let WorkflowBuilder { id, steps, .. } = self;
+        if steps.is_empty() {
+            return Err(BuildError::Empty);
+        }
+
+        let mut names = HashSet::new();
+        let mut instructions = HashSet::new();
+        let mut cids: Vec<Cid> = Vec::with_capacity(steps.len());
+        let mut tasks = Vec::with_capacity(steps.len());
+
+        for (index, spec) in steps.into_iter().enumerate() {
+            if !names.insert(spec.name.clone()) {
+                return Err(BuildError::DuplicateName(spec.name));
+            }
+            let resource = spec
+                .resource
+                .ok_or_else(|| BuildError::MissingResource(spec.name.clone()))?
+                .map_err(|err| BuildError::InvalidResource(spec.name.clone(), err))?;
+            let fun = spec
+                .fun
+                .ok_or_else(|| BuildError::MissingFunction(spec.name.clone()))?;
+
+            let args = spec
+                .args
+                .into_iter()
+                .map(|arg| match arg {
+                    Arg::Value(ipld) => Ok(ipld),
+                    Arg::Output(step, result) if step.builder == id && step.index < index => {
+                        Ok(Await::new(Pointer::new(cids[step.index]), result).into())
+                    }
+                    Arg::Output(..) => Err(BuildError::UnknownStep(spec.name.clone())),
+                })
+                .collect::<Result<Vec<Ipld>, _>>()?;
+
+            let input = Input::Ipld(Ipld::Map(BTreeMap::from([
+                (FUNC_KEY.into(), Ipld::String(fun)),
+                (ARGS_KEY.into(), Ipld::List(args)),
+            ])));
+            let instruction = match spec.nonce {
+                Some(nonce) => Instruction::new_with_nonce(resource, spec.ability, input, nonce),
+                None => Instruction::new(resource, spec.ability, input),
+            };
+
+            let cid = instruction.clone().to_cid()?;
+            if !instructions.insert(cid) {
+                return Err(BuildError::DuplicateTask(spec.name));
+            }
+            cids.push(cid);
+
+            tasks.push(Task::new(
+                RunInstruction::Expanded(instruction),
+                spec.resources.into(),
+                spec.prf,
+            ));
+        }
+
+        Ok(Workflow::new(tasks))
+    }
+}
+
+/// Builder for a single task of a [WorkflowBuilder], added to the workflow
+/// by [TaskBuilder::add], with an output of type `O`.
+#[derive(Debug)]
+pub struct TaskBuilder<'b, 'a, T, O = Ipld> {
+    builder: &'b mut WorkflowBuilder<'a, T>,
+    spec: StepSpec,
+    _output: PhantomData<fn() -> O>,
+}
+
+impl<'b, 'a, T, O> TaskBuilder<'b, 'a, T, O> {
+    /// Set the task's resource, e.g. the URL of a Wasm module.
+    pub fn resource(mut self, resource: Url) -> Self {
+        self.spec.resource = Some(Ok(resource));
+        self
+    }
+
+    /// Set the task's resource to a Wasm module on IPFS, given its Cid.
+    pub fn module(mut self, cid: Cid) -> Self {
+        self.spec.resource = Some(Url::parse(&format!("ipfs://{cid}")));
+        self
+    }
+
+    /// Set the task's ability, overriding the default `wasm/run`.
+    pub fn ability<A: Into<Ability>>(mut self, ability: A) -> Self {
+        self.spec.ability = ability.into();
+        self
+    }
+
+    /// Call a declared [Fun] with `args` of its argument types, replacing
+    /// any function and arguments set so far.
+    ///
+    /// The task's [Step] handle then has the function's output type.
+    pub fn call<A, R, P: Args<A>>(self, fun: &Fun<A, R>, args: P) -> TaskBuilder<'b, 'a, T, R> {
+        let mut spec = self.spec;
+        spec.fun = Some(fun.name.clone());
+        spec.args = args
+            .into_params()
+            .into_iter()
+            .map(|param| param.arg)
+            .collect();
+
+        TaskBuilder {
+            builder: self.builder,
+            spec,
+            _output: PhantomData,
+        }
+    }
+
+    /// Set the function the task calls, without declaring its types.
+    pub fn fun<S: Into<String>>(mut self, fun: S) -> Self {
+        self.spec.fun = Some(fun.into());
+        self
+    }
+
+    /// Append an [Ipld] argument to the task's function call.
+    pub fn arg<P: Into<Param>>(mut self, arg: P) -> Self {
+        self.spec.args.push(arg.into().arg);
+        self
+    }
+
+    /// Append [Ipld] arguments to the task's function call.
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Param>,
+    {
+        self.spec
+            .args
+            .extend(args.into_iter().map(|arg| arg.into().arg));
+        self
+    }
+
+    /// Set the task's nonce, distinguishing it from otherwise identical
+    /// tasks.
+    pub fn nonce(mut self, nonce: Nonce) -> Self {
+        self.spec.nonce = Some(nonce);
+        self
+    }
+
+    /// Make the task unique by generating a nonce for it.
+    pub fn unique(self) -> Self {
+        self.nonce(Nonce::generate())
+    }
+
+    /// Set the [Resources] requested by the task.
+    pub fn resources(mut self, resources: Resources) -> Self {
+        self.spec.resources = resources;
+        self
+    }
+
+    /// Set the task's proofs, linking to the UCANs that authorize it.
+    pub fn prf(mut self, prf: UcanPrf) -> Self {
+        self.spec.prf = prf;
+        self
+    }
+
+    /// Append a proof to the task, given the Cid of a UCAN that authorizes
+    /// it.
+    pub fn proof(mut self, ucan: Cid) -> Self {
+        let mut links = self.spec.prf.into_inner();
+        links.push(Link::new(ucan));
+        self.spec.prf = UcanPrf::new(links);
+        self
+    }
+
+    /// Add the task to the workflow, returning a [Step] handle on its
+    /// output.
+    pub fn add(self) -> Step<O> {
+        let step = Step::new(StepId {
+            builder: self.builder.id,
+            index: self.builder.steps.len(),
+        });
+        self.builder.steps.push(self.spec);
+        step
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WASM_CID: &str = "bafybeia32q3oy6u47x624rmsmgrrlpn7ulruissmz5z2ap6alv7goe7h3q";
+
+    #[test]
+    fn builds_workflow_with_awaits() {
+        let module = Cid::try_from(WASM_CID).unwrap();
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        let add = builder
+            .task("add")
+            .module(module)
+            .fun("add_one")
+            .arg(1)
+            .add();
+        let double = builder
+            .task("double")
+            .module(module)
+            .fun("add_one")
+            .arg(add.ok())
+            .add();
+        builder
+            .task("sum")
+            .module(module)
+            .fun("add_three")
+            .args([
+                Param::from(double),
+                Param::from(add.error()),
+                Param::from(42),
+            ])
+            .add();
+
+        let workflow = builder.build().unwrap();
+        let tasks = workflow.tasks();
+        assert_eq!(tasks.len(), 3);
+
+        let cid = |task: &Task<'_, Unit>| task.instruction_cid().unwrap();
+        let RunInstruction::Expanded(sum) = tasks[2].run() else {
+            panic!("expected an expanded instruction");
+        };
+        let Input::Ipld(Ipld::Map(input)) = sum.input() else {
+            panic!("expected an Ipld map input");
+        };
+        assert_eq!(
+            input.get(ARGS_KEY),
+            Some(&Ipld::List(vec![
+                Await::new(Pointer::new(cid(&tasks[1])), AwaitResult::Ok).into(),
+                Await::new(Pointer::new(cid(&tasks[0])), AwaitResult::Error).into(),
+                Ipld::Integer(42),
+            ]))
+        );
+    }
+
+    #[test]
+    fn builds_workflow_with_typed_steps() {
+        let module = Cid::try_from(WASM_CID).unwrap();
+        let add_one = Fun::<(i32,), i32>::new("add_one");
+        let join = Fun::<(String, i32), String>::new("join");
+
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        let add: Step<i32> = builder
+            .task("add")
+            .module(module)
+            .call(&add_one, (1,))
+            .add();
+        let joined: Step<String> = builder
+            .task("join")
+            .module(module)
+            .call(&join, ("sum".to_string(), add.ok()))
+            .add();
+        builder
+            .task("log")
+            .module(module)
+            .fun("log")
+            .arg(joined.ok().untyped())
+            .add();
+
+        let workflow = builder.build().unwrap();
+        let tasks = workflow.tasks();
+        assert_eq!(tasks.len(), 3);
+
+        let RunInstruction::Expanded(join) = tasks[1].run() else {
+            panic!("expected an expanded instruction");
+        };
+        let Input::Ipld(Ipld::Map(input)) = join.input() else {
+            panic!("expected an Ipld map input");
+        };
+        assert_eq!(input.get(FUNC_KEY), Some(&Ipld::String("join".to_string())));
+        assert_eq!(
+            input.get(ARGS_KEY),
+            Some(&Ipld::List(vec![
+                Ipld::String("sum".to_string()),
+                Await::new(
+                    Pointer::new(tasks[0].instruction_cid().unwrap()),
+                    AwaitResult::Ok
+                )
+                .into(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn sets_task_proofs() {
+        let module = Cid::try_from(WASM_CID).unwrap();
+        let ucan =
+            Cid::try_from("bafkreigegqrsyn5gzgbnfi64v6yoxzufuklv5skj76yuabmc5m6xs5rwdi").unwrap();
+        let other =
+            Cid::try_from("bafkreieq2kvmpdt63aln53a3hvn7xdtc4oml5bvzhsk6ydgmdduengiuaq").unwrap();
+
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        builder
+            .task("add")
+            .module(module)
+            .fun("add_one")
+            .proof(ucan)
+            .proof(other)
+            .add();
+        builder.task("unproven").module(module).fun("add_two").add();
+
+        let workflow = builder.build().unwrap();
+        let tasks = workflow.tasks();
+        assert_eq!(
+            tasks[0].prf(),
+            &UcanPrf::new(vec![Link::new(ucan), Link::new(other)])
+        );
+        assert_eq!(tasks[1].prf(), &UcanPrf::default());
+    }
+
+    #[test]
+    fn validates_workflow_at_build_time() {
+        let module = Cid::try_from(WASM_CID).unwrap();
+
+        assert!(matches!(
+            WorkflowBuilder::<Unit>::new().build(),
+            Err(BuildError::Empty)
+        ));
+
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        builder.task("add").fun("add_one").add();
+        assert!(matches!(
+            builder.build(),
+            Err(BuildError::MissingResource(name)) if name == "add"
+        ));
+
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        builder
+            .task("add")
+            .module(module)
+            .fun("add_one")
+            .arg(1)
+            .add();
+        builder
+            .task("add")
+            .module(module)
+            .fun("add_one")
+            .arg(2)
+            .add();
+        assert!(matches!(
+            builder.build(),
+            Err(BuildError::DuplicateName(name)) if name == "add"
+        ));
+
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        builder
+            .task("one")
+            .module(module)
+            .fun("add_one")
+            .arg(1)
+            .add();
+        builder
+            .task("two")
+            .module(module)
+            .fun("add_one")
+            .arg(1)
+            .add();
+        assert!(matches!(
+            builder.build(),
+            Err(BuildError::DuplicateTask(name)) if name == "two"
+        ));
+
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        builder
+            .task("one")
+            .module(module)
+            .fun("add_one")
+            .arg(1)
+            .add();
+        builder
+            .task("two")
+            .module(module)
+            .fun("add_one")
+            .arg(1)
+            .unique()
+            .add();
+        assert!(builder.build().is_ok());
+
+        let mut other = WorkflowBuilder::<Unit>::new();
+        let foreign = other.task("add").module(module).fun("add_one").add();
+        let mut builder = WorkflowBuilder::<Unit>::new();
+        builder
+            .task("add")
+            .module(module)
+            .fun("add_one")
+            .arg(foreign.ok())
+            .add();
+        assert!(matches!(
+            builder.build(),
+            Err(BuildError::UnknownStep(name)) if name == "add"
+        ));
+    }
+}